@@ -0,0 +1,442 @@
+//! Delta-query plans for conjunctive queries.
+//!
+//! A conjunctive query is described by a list of relations, each of which binds its columns to
+//! variables, and a list of output variables. For example, the triangle query
+//!
+//! ```text
+//! Q(a,b,c) := E1(a,b), E2(b,c), E3(a,c)
+//! ```
+//!
+//! is described by relations `[vec![0,1], vec![1,2], vec![0,2]]` and output `vec![0,1,2]`.
+//!
+//! The query is maintained by a delta query: for each relation, the changes to that relation are
+//! extended to full results by looking up the other relations. Relations before the changing relation
+//! are looked up as of the time of the change including the change, and relations after it as of the
+//! time of the change excluding the change, which is arranged with the `AltNeu` timestamp. Each stage
+//! of the extension is either a binary join with one relation, or a worst-case optimal extension of
+//! a single variable proposed and validated by all relations that would constrain it. Each collection
+//! is arranged once for each way it is looked up, outside the delta query, and the arrangements are
+//! shared by all stages and brought into the delta query at `alt` and `neu` times as needed.
+//!
+//! Relations are assumed to be sets, as the prefix extenders assume.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use timely::dataflow::Scope;
+use timely::dataflow::scopes::Child;
+
+use differential_dataflow::{ExchangeData, Collection};
+use differential_dataflow::difference::{Monoid, Multiply};
+use differential_dataflow::lattice::Lattice;
+use differential_dataflow::operators::Threshold;
+use differential_dataflow::operators::arrange::{Arranged, ArrangeByKey, ArrangeBySelf, TraceAgent};
+use differential_dataflow::trace::{Cursor, TraceReader, BatchReader};
+use differential_dataflow::trace::implementations::ord::{OrdKeySpine, OrdValSpine};
+use differential_dataflow::trace::wrappers::enter_at::TraceEnter as TraceEnterAt;
+
+use crate::altneu::AltNeu;
+use crate::operators;
+use crate::operators::propose;
+use crate::{PrefixExtender, ProposeExtensionMethod};
+
+/// A stage in the extension of changes to one relation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// Joins prefixes with `relation` on the variables `keys`, binding the variables `values`.
+    ///
+    /// If `values` is empty the relation only restricts the prefixes.
+    Join {
+        /// The index of the joined relation.
+        relation: usize,
+        /// Bound variables shared with the relation.
+        keys: Vec<usize>,
+        /// Unbound variables bound by the relation.
+        values: Vec<usize>,
+    },
+    /// Extends prefixes with `variable`, proposed and validated by each of `relations`.
+    ///
+    /// Each relation is listed with the bound variables it is keyed by.
+    Extend {
+        /// The variable bound by the extension.
+        variable: usize,
+        /// The relations constraining the variable, and their bound variables.
+        relations: Vec<(usize, Vec<usize>)>,
+    },
+}
+
+/// A conjunctive query over relations binding their columns to variables.
+#[derive(Clone, Debug)]
+pub struct DeltaQuery {
+    relations: Vec<Vec<usize>>,
+    output: Vec<usize>,
+}
+
+impl DeltaQuery {
+    /// Creates a query from the variables bound by the columns of each relation, and the output variables.
+    ///
+    /// A variable repeated within a relation requires its columns to be equal.
+    pub fn new(relations: Vec<Vec<usize>>, output: Vec<usize>) -> Self {
+        assert!(!relations.is_empty(), "queries require at least one relation");
+        for variable in output.iter() {
+            assert!(relations.iter().any(|bindings| bindings.contains(variable)), "output variable {} is not bound", variable);
+        }
+        DeltaQuery { relations, output }
+    }
+
+    /// The stages that extend changes to relation `source` to results of the query.
+    ///
+    /// Relations whose variables are all bound are joined first, as they only restrict the prefixes.
+    /// Otherwise, if some variable is constrained by at least two relations with no other unbound
+    /// variables, the variable constrained by the most such relations is bound by prefix extension.
+    /// Otherwise, the relation sharing the most variables with the prefixes is joined.
+    pub fn plan(&self, source: usize) -> Vec<Stage> {
+
+        let mut bound = distinct(&self.relations[source]);
+        let mut remaining = (0 .. self.relations.len()).filter(|&index| index != source).collect::<Vec<_>>();
+        let mut stages = Vec::new();
+
+        while !remaining.is_empty() {
+
+            if let Some(position) = remaining.iter().position(|&index| self.relations[index].iter().all(|v| bound.contains(v))) {
+                let relation = remaining.remove(position);
+                stages.push(Stage::Join { relation, keys: distinct(&self.relations[relation]), values: Vec::new() });
+                continue;
+            }
+
+            // Unbound variables, and the relations that would constrain each were it bound.
+            let mut unbound = remaining.iter().flat_map(|&index| self.relations[index].iter().cloned()).filter(|v| !bound.contains(v)).collect::<Vec<_>>();
+            unbound.sort();
+            unbound.dedup();
+
+            let mut extension: Option<(usize, Vec<usize>)> = None;
+            for variable in unbound {
+                let relations = remaining.iter().cloned().filter(|&index| {
+                    self.relations[index].contains(&variable) &&
+                    self.relations[index].iter().all(|v| v == &variable || bound.contains(v))
+                }).collect::<Vec<_>>();
+                if relations.len() > 1 && extension.as_ref().map(|x| relations.len() > x.1.len()).unwrap_or(true) {
+                    extension = Some((variable, relations));
+                }
+            }
+
+            if let Some((variable, relations)) = extension {
+                remaining.retain(|index| !relations.contains(index));
+                let relations = relations.into_iter().map(|index| {
+                    let keys = distinct(&self.relations[index]).into_iter().filter(|v| v != &variable).collect();
+                    (index, keys)
+                }).collect();
+                stages.push(Stage::Extend { variable, relations });
+                bound.push(variable);
+            }
+            else {
+                let mut position = 0;
+                let mut shared = 0;
+                for (pos, &index) in remaining.iter().enumerate() {
+                    let count = distinct(&self.relations[index]).iter().filter(|v| bound.contains(v)).count();
+                    if count > shared {
+                        position = pos;
+                        shared = count;
+                    }
+                }
+                let relation = remaining.remove(position);
+                let (keys, values): (Vec<_>, Vec<_>) = distinct(&self.relations[relation]).into_iter().partition(|v| bound.contains(v));
+                bound.extend(values.iter().cloned());
+                stages.push(Stage::Join { relation, keys, values });
+            }
+        }
+
+        stages
+    }
+
+    /// Renders the delta query, producing the output variables of each result.
+    ///
+    /// The collections in `relations` correspond to the relations of the query, and contain tuples
+    /// with one value for each column. Each collection is arranged once for each way it is looked up,
+    /// and the same collection may be supplied for several relations.
+    ///
+    /// The function `prior` maps each time to the greatest time strictly less than it, for example
+    /// `|t| t.saturating_sub(1)` for integer times. It determines how far arrangements looked up as of
+    /// the time of a change excluding the change may be compacted. Mapping each time to the minimum time
+    /// is correct, but prevents these arrangements from being compacted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate timely;
+    /// extern crate differential_dataflow;
+    /// extern crate dogsdogsdogs;
+    ///
+    /// use differential_dataflow::input::Input;
+    /// use dogsdogsdogs::planner::DeltaQuery;
+    ///
+    /// fn main() {
+    ///     ::timely::example(|scope| {
+    ///
+    ///         let edges = scope.new_collection_from(vec![vec![1u32, 2], vec![2, 3], vec![1, 3], vec![3, 4]]).1;
+    ///         let triangles = scope.new_collection_from(vec![vec![1u32, 2, 3]]).1;
+    ///
+    ///         // Q(a,b,c) := E(a,b), E(b,c), E(a,c)
+    ///         DeltaQuery::new(vec![vec![0, 1], vec![1, 2], vec![0, 2]], vec![0, 1, 2])
+    ///             .render(&[edges.clone(), edges.clone(), edges], |t| t.saturating_sub(1))
+    ///             .assert_eq(&triangles);
+    ///     });
+    /// }
+    /// ```
+    pub fn render<G, D, R, P>(&self, relations: &[Collection<G, Vec<D>, R>], prior: P) -> Collection<G, Vec<D>, R>
+    where
+        G: Scope,
+        G::Timestamp: Lattice+ExchangeData,
+        D: ExchangeData+Hash+Default,
+        R: Monoid+Multiply<Output = R>+ExchangeData,
+        P: Fn(&G::Timestamp)->G::Timestamp+Clone+'static,
+    {
+        assert_eq!(relations.len(), self.relations.len(), "queries require one collection for each relation");
+
+        // Each relation restricted to tuples whose repeated variables agree, once for each collection.
+        let mut filtered = HashMap::new();
+        let relations = relations.iter().zip(self.relations.iter()).map(|(collection, bindings)| {
+            let repeats = repeats(bindings);
+            filtered
+                .entry((*collection.inner.name(), repeats.clone()))
+                .or_insert_with(|| collection.filter(move |tuple| repeats.iter().all(|&(x, y)| tuple[x] == tuple[y])))
+                .clone()
+        }).collect::<Vec<_>>();
+
+        // Arrangements shared by all stages, by collection and by the columns they are keyed by and present.
+        let plans = (0 .. self.relations.len()).map(|source| self.plan(source)).collect::<Vec<_>>();
+        let mut arrangements = HashMap::new();
+        let mut indices = HashMap::new();
+        for stage in plans.iter().flat_map(|plan| plan.iter()) {
+            match stage {
+                Stage::Join { relation, keys, values } => {
+                    let collection = &relations[*relation];
+                    let keys = locate(&self.relations[*relation], keys);
+                    let values = locate(&self.relations[*relation], values);
+                    arrangements
+                        .entry((*collection.inner.name(), keys.clone(), values.clone()))
+                        .or_insert_with(|| {
+                            collection
+                                .map(move |tuple| (select(&tuple, &keys), select(&tuple, &values)))
+                                .arrange_by_key()
+                        });
+                },
+                Stage::Extend { variable, relations: constraints } => {
+                    for (relation, keys) in constraints.iter() {
+                        let collection = &relations[*relation];
+                        let keys = locate(&self.relations[*relation], keys);
+                        let value = locate(&self.relations[*relation], &[*variable])[0];
+                        indices
+                            .entry((*collection.inner.name(), keys.clone(), value))
+                            .or_insert_with(|| Index::new(&collection.map(move |tuple| (select(&tuple, &keys), tuple[value].clone()))));
+                    }
+                },
+            }
+        }
+
+        let mut scope = relations[0].scope();
+        scope.scoped::<AltNeu<G::Timestamp>,_,_>("DeltaQuery", |inner| {
+
+            let mut results = Vec::new();
+            for (source, plan) in plans.into_iter().enumerate() {
+
+                let mut bound = distinct(&self.relations[source]);
+                let columns = locate(&self.relations[source], &bound);
+                let mut prefixes = relations[source].enter(inner).map(move |tuple| select(&tuple, &columns));
+
+                // Relations before `source` are looked up at `alt` times, and those after it at `neu` times.
+                for stage in plan {
+                    match stage {
+                        Stage::Join { relation, keys, values } => {
+                            let bindings = &self.relations[relation];
+                            let name = *relations[relation].inner.name();
+                            let arrangement = &arrangements[&(name, locate(bindings, &keys), locate(bindings, &values))];
+                            let arrangement = enter_at(arrangement, inner, relation > source, prior.clone());
+                            let positions = locate(&bound, &keys);
+                            prefixes =
+                            propose(&prefixes, arrangement, move |prefix: &Vec<D>| select(prefix, &positions))
+                                .map(|(mut prefix, values)| { prefix.extend(values); prefix });
+                            bound.extend(values);
+                        },
+                        Stage::Extend { variable, relations: constraints } => {
+                            let mut extenders = constraints.into_iter().map(|(relation, keys)| {
+                                let bindings = &self.relations[relation];
+                                let name = *relations[relation].inner.name();
+                                let positions = locate(&bound, &keys);
+                                Extender {
+                                    index: indices[&(name, locate(bindings, &keys), locate(bindings, &[variable])[0])].clone(),
+                                    neu: relation > source,
+                                    prior: prior.clone(),
+                                    key_selector: move |prefix: &Vec<D>| select(prefix, &positions),
+                                }
+                            }).collect::<Vec<_>>();
+                            let mut extenders = extenders
+                                .iter_mut()
+                                .map(|extender| extender as &mut dyn PrefixExtender<_, R, Prefix=Vec<D>, Extension=D>)
+                                .collect::<Vec<_>>();
+                            prefixes =
+                            prefixes
+                                .extend(&mut extenders[..])
+                                .map(|(mut prefix, value)| { prefix.push(value); prefix });
+                            bound.push(variable);
+                        },
+                    }
+                }
+
+                let positions = locate(&bound, &self.output);
+                results.push(prefixes.map(move |prefix| select(&prefix, &positions)));
+            }
+
+            differential_dataflow::collection::concatenate(inner, results).leave()
+        })
+    }
+}
+
+/// Arrangements of `(key, value)` pairs, used to count, propose, and validate values for keys.
+struct Index<G, D, R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+ExchangeData,
+    D: ExchangeData+Hash,
+    R: Monoid+Multiply<Output = R>+ExchangeData,
+{
+    count: Arranged<G, TraceAgent<OrdKeySpine<Vec<D>, G::Timestamp, isize>>>,
+    propose: Arranged<G, TraceAgent<OrdValSpine<Vec<D>, D, G::Timestamp, R>>>,
+    validate: Arranged<G, TraceAgent<OrdKeySpine<(Vec<D>, D), G::Timestamp, R>>>,
+}
+
+impl<G, D, R> Clone for Index<G, D, R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+ExchangeData,
+    D: ExchangeData+Hash,
+    R: Monoid+Multiply<Output = R>+ExchangeData,
+{
+    fn clone(&self) -> Self {
+        Index {
+            count: self.count.clone(),
+            propose: self.propose.clone(),
+            validate: self.validate.clone(),
+        }
+    }
+}
+
+impl<G, D, R> Index<G, D, R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+ExchangeData,
+    D: ExchangeData+Hash,
+    R: Monoid+Multiply<Output = R>+ExchangeData,
+{
+    fn new(collection: &Collection<G, (Vec<D>, D), R>) -> Self {
+        // As for `CollectionIndex`, counts and validation share an arrangement of the pairs.
+        let validate = collection.arrange_by_self();
+        let count = validate.distinct().map(|(key, _value)| key).arrange_by_self();
+        let propose = collection.arrange_by_key();
+        Index { count, propose, validate }
+    }
+}
+
+/// Extends prefixes using an index from the enclosing scope, looked up at `neu` times if `neu` is set,
+/// and at `alt` times otherwise.
+struct Extender<G, D, R, F, P>
+where
+    G: Scope,
+    G::Timestamp: Lattice+ExchangeData,
+    D: ExchangeData+Hash,
+    R: Monoid+Multiply<Output = R>+ExchangeData,
+{
+    index: Index<G, D, R>,
+    neu: bool,
+    prior: P,
+    key_selector: F,
+}
+
+impl<'a, G, D, R, F, P> PrefixExtender<Child<'a, G, AltNeu<G::Timestamp>>, R> for Extender<G, D, R, F, P>
+where
+    G: Scope,
+    G::Timestamp: Lattice+ExchangeData,
+    D: ExchangeData+Hash+Default,
+    R: Monoid+Multiply<Output = R>+ExchangeData,
+    F: Fn(&Vec<D>)->Vec<D>+Clone+'static,
+    P: Fn(&G::Timestamp)->G::Timestamp+Clone+'static,
+{
+    type Prefix = Vec<D>;
+    type Extension = D;
+
+    fn count(&mut self, prefixes: &Collection<Child<'a, G, AltNeu<G::Timestamp>>, (Vec<D>, usize, usize), R>, index: usize) -> Collection<Child<'a, G, AltNeu<G::Timestamp>>, (Vec<D>, usize, usize), R> {
+        let counts = enter_at(&self.index.count, &prefixes.scope(), self.neu, self.prior.clone());
+        operators::count::count(prefixes, counts, self.key_selector.clone(), index)
+    }
+
+    fn propose(&mut self, prefixes: &Collection<Child<'a, G, AltNeu<G::Timestamp>>, Vec<D>, R>) -> Collection<Child<'a, G, AltNeu<G::Timestamp>>, (Vec<D>, D), R> {
+        let propose = enter_at(&self.index.propose, &prefixes.scope(), self.neu, self.prior.clone());
+        operators::propose::propose(prefixes, propose, self.key_selector.clone())
+    }
+
+    fn validate(&mut self, extensions: &Collection<Child<'a, G, AltNeu<G::Timestamp>>, (Vec<D>, D), R>) -> Collection<Child<'a, G, AltNeu<G::Timestamp>>, (Vec<D>, D), R> {
+        let validate = enter_at(&self.index.validate, &extensions.scope(), self.neu, self.prior.clone());
+        operators::validate::validate(extensions, validate, self.key_selector.clone())
+    }
+}
+
+/// Brings an arrangement into the delta query, presenting its updates at `neu` times if `neu` is set,
+/// and at `alt` times otherwise.
+///
+/// Compaction frontiers at `alt` times are mapped back to the enclosing scope by `prior` for arrangements
+/// presented at `neu` times, which must distinguish updates at the time of the frontier from those before it.
+fn enter_at<'a, G, Tr, P>(arrangement: &Arranged<G, Tr>, inner: &Child<'a, G, AltNeu<G::Timestamp>>, neu: bool, prior: P)
+    -> Arranged<Child<'a, G, AltNeu<G::Timestamp>>, TraceEnterAt<Tr, AltNeu<G::Timestamp>, impl FnMut(&Tr::Key, &Tr::Val, &G::Timestamp)->AltNeu<G::Timestamp>+Clone+'static, impl FnMut(&AltNeu<G::Timestamp>)->G::Timestamp+Clone+'static>>
+where
+    G: Scope,
+    G::Timestamp: Lattice+ExchangeData,
+    Tr: TraceReader<Time=G::Timestamp>+Clone,
+    Tr::Key: 'static,
+    Tr::Val: 'static,
+    Tr::R: 'static,
+    Tr::Batch: BatchReader<Tr::Key, Tr::Val, G::Timestamp, Tr::R>,
+    Tr::Cursor: Cursor<Tr::Key, Tr::Val, G::Timestamp, Tr::R>,
+    P: Fn(&G::Timestamp)->G::Timestamp+Clone+'static,
+{
+    arrangement.enter_at(
+        inner,
+        move |_, _, time| AltNeu { time: time.clone(), neu },
+        move |time| if neu && !time.neu { prior(&time.time) } else { time.time.clone() },
+    )
+}
+
+/// The variables of `bindings` in order of first occurrence.
+fn distinct(bindings: &[usize]) -> Vec<usize> {
+    let mut result = Vec::new();
+    for variable in bindings.iter() {
+        if !result.contains(variable) {
+            result.push(*variable);
+        }
+    }
+    result
+}
+
+/// Pairs of columns of `bindings` that bind the same variable.
+fn repeats(bindings: &[usize]) -> Vec<(usize, usize)> {
+    let mut result = Vec::new();
+    for (index, variable) in bindings.iter().enumerate() {
+        let first = bindings.iter().position(|v| v == variable).unwrap();
+        if first < index {
+            result.push((first, index));
+        }
+    }
+    result
+}
+
+/// The first column of `bindings` binding each of `variables`.
+fn locate(bindings: &[usize], variables: &[usize]) -> Vec<usize> {
+    variables
+        .iter()
+        .map(|variable| bindings.iter().position(|v| v == variable).expect("variable not bound"))
+        .collect()
+}
+
+/// The values of `tuple` in each of `columns`.
+fn select<D: Clone>(tuple: &[D], columns: &[usize]) -> Vec<D> {
+    columns.iter().map(|&column| tuple[column].clone()).collect()
+}
@@ -0,0 +1,130 @@
+extern crate timely;
+extern crate differential_dataflow;
+extern crate dogsdogsdogs;
+
+use timely::dataflow::Scope;
+use timely::dataflow::operators::Capture;
+use timely::dataflow::operators::capture::Extract;
+
+use differential_dataflow::input::Input;
+use differential_dataflow::operators::Consolidate;
+use differential_dataflow::operators::arrange::{ArrangeByKey, ArrangeBySelf};
+
+use dogsdogsdogs::altneu::AltNeu;
+use dogsdogsdogs::operators::{propose, validate};
+use dogsdogsdogs::planner::{DeltaQuery, Stage};
+
+#[test]
+fn plan_triangles() {
+
+    // Q(a,b,c) := E1(a,b), E2(b,c), E3(a,c)
+    let query = DeltaQuery::new(vec![vec![0, 1], vec![1, 2], vec![0, 2]], vec![0, 1, 2]);
+
+    // each change binds two variables, and the third is proposed and validated by the other two relations.
+    assert_eq!(query.plan(0), vec![Stage::Extend { variable: 2, relations: vec![(1, vec![1]), (2, vec![0])] }]);
+    assert_eq!(query.plan(1), vec![Stage::Extend { variable: 0, relations: vec![(0, vec![1]), (2, vec![2])] }]);
+    assert_eq!(query.plan(2), vec![Stage::Extend { variable: 1, relations: vec![(0, vec![0]), (1, vec![2])] }]);
+}
+
+#[test]
+fn plan_joins() {
+
+    // Q(a,b,c) := E1(a,b), E2(b,c)
+    let query = DeltaQuery::new(vec![vec![0, 1], vec![1, 2]], vec![0, 1, 2]);
+
+    // a variable constrained by a single relation is bound by joining the relation.
+    assert_eq!(query.plan(0), vec![Stage::Join { relation: 1, keys: vec![1], values: vec![2] }]);
+    assert_eq!(query.plan(1), vec![Stage::Join { relation: 0, keys: vec![1], values: vec![0] }]);
+
+    // Q(a,b,c) := E1(a,b), E2(b,c), E3(a,b)
+    let query = DeltaQuery::new(vec![vec![0, 1], vec![1, 2], vec![0, 1]], vec![0, 1, 2]);
+
+    // relations whose variables are all bound only restrict the prefixes, and are joined first.
+    assert_eq!(query.plan(0), vec![
+        Stage::Join { relation: 2, keys: vec![0, 1], values: vec![] },
+        Stage::Join { relation: 1, keys: vec![1], values: vec![2] },
+    ]);
+    // relations constraining the same variable are combined into one extension.
+    assert_eq!(query.plan(1), vec![Stage::Extend { variable: 0, relations: vec![(0, vec![1]), (2, vec![1])] }]);
+}
+
+#[test]
+fn render_triangles() {
+
+    let (differences, triangles) = timely::execute_directly(|worker| {
+
+        let (mut input, differences, triangles) = worker.dataflow::<usize,_,_>(|scope| {
+
+            let (input, edges) = scope.new_collection::<(u32, u32), isize>();
+
+            // Q(a,b,c) := E1(a,b), E2(b,c), E3(a,c)
+            let relation = edges.map(|(x, y)| vec![x, y]);
+            let planned = DeltaQuery::new(vec![vec![0, 1], vec![1, 2], vec![0, 2]], vec![0, 1, 2])
+                .render(&[relation.clone(), relation.clone(), relation], |t| t.saturating_sub(1))
+                .map(|tuple| (tuple[0], tuple[1], tuple[2]));
+
+            // The same delta query, wired by hand.
+            let forward_key = edges.arrange_by_key();
+            let reverse_key = edges.map(|(x, y)| (y, x)).arrange_by_key();
+            let forward_self = edges.arrange_by_self();
+            let reverse_self = edges.map(|(x, y)| (y, x)).arrange_by_self();
+
+            let wired = scope.scoped::<AltNeu<usize>,_,_>("DeltaQuery (Triangles)", |inner| {
+
+                let changes = edges.enter(inner);
+
+                let forward_key_alt = forward_key.enter_at(inner, |_,_,t| AltNeu::alt(t.clone()), |t| t.time.saturating_sub(1));
+                let reverse_key_alt = reverse_key.enter_at(inner, |_,_,t| AltNeu::alt(t.clone()), |t| t.time.saturating_sub(1));
+                let forward_key_neu = forward_key.enter_at(inner, |_,_,t| AltNeu::neu(t.clone()), |t| t.time.saturating_sub(1));
+                let reverse_self_alt = reverse_self.enter_at(inner, |_,_,t| AltNeu::alt(t.clone()), |t| t.time.saturating_sub(1));
+                let forward_self_neu = forward_self.enter_at(inner, |_,_,t| AltNeu::neu(t.clone()), |t| t.time.saturating_sub(1));
+                let reverse_self_neu = reverse_self.enter_at(inner, |_,_,t| AltNeu::neu(t.clone()), |t| t.time.saturating_sub(1));
+
+                let key1 = |x: &(u32, u32)| x.0;
+                let key2 = |x: &(u32, u32)| x.1;
+
+                //   dQ/dE1 := dE1(a,b), E2(b,c), E3(a,c)
+                let changes1 = propose(&changes, forward_key_neu, key2.clone());
+                let changes1 = validate(&changes1, forward_self_neu, key1.clone());
+                let changes1 = changes1.map(|((a,b),c)| (a,b,c));
+
+                //   dQ/dE2 := dE2(b,c), E1(a,b), E3(a,c)
+                let changes2 = propose(&changes, reverse_key_alt, key1.clone());
+                let changes2 = validate(&changes2, reverse_self_neu, key2.clone());
+                let changes2 = changes2.map(|((b,c),a)| (a,b,c));
+
+                //   dQ/dE3 := dE3(a,c), E1(a,b), E2(b,c)
+                let changes3 = propose(&changes, forward_key_alt, key1.clone());
+                let changes3 = validate(&changes3, reverse_self_alt, key2.clone());
+                let changes3 = changes3.map(|((a,c),b)| (a,b,c));
+
+                changes1.concat(&changes2).concat(&changes3).leave()
+            });
+
+            let differences = planned.concat(&wired.negate()).consolidate();
+            (input, differences.inner.capture(), planned.inner.capture())
+        });
+
+        // insertions and retractions of edges that form and break triangles.
+        for &edge in [(1, 2), (2, 3), (1, 3), (3, 4), (2, 4), (1, 4)].iter() {
+            input.insert(edge);
+        }
+        input.advance_to(1);
+        input.remove((2, 3));
+        input.insert((4, 5));
+        input.insert((3, 5));
+        input.advance_to(2);
+        input.remove((1, 3));
+        input.insert((2, 3));
+        input.close();
+        while worker.step() { }
+
+        (differences, triangles)
+    });
+
+    assert!(differences.extract().into_iter().all(|(_, data)| data.is_empty()));
+
+    let mut triangles = triangles.extract().into_iter().flat_map(|(_, data)| data).map(|(triangle, _time, diff)| (triangle, diff)).collect::<Vec<_>>();
+    differential_dataflow::consolidation::consolidate(&mut triangles);
+    assert_eq!(triangles, vec![((1, 2, 4), 1), ((2, 3, 4), 1), ((3, 4, 5), 1)]);
+}
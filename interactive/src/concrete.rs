@@ -1,7 +1,7 @@
 //! An example value type.
 
 use std::time::Duration;
-use super::{Datum, VectorFrom, Command};
+use super::{Datum, VectorFrom, Command, Diff};
 
 /// A session.
 pub struct Session<W: std::io::Write> {
@@ -38,6 +38,52 @@ impl Datum for Value {
     type Expression = usize;
     fn subject_to(data: &[Self], expr: &Self::Expression) -> Self { data[*expr].clone() }
     fn projection(index: usize) -> Self::Expression { index }
+    fn from_count(count: Diff) -> Option<Self> {
+        if count > 0 { Some(Value::Usize(count as usize)) } else { None }
+    }
+    fn sum(values: &[(&Self, Diff)]) -> Option<Self> {
+        let (example, total, count) = Value::total(values)?;
+        if count > 0 { Value::number_like(example, total) } else { None }
+    }
+    fn average(values: &[(&Self, Diff)]) -> Option<Self> {
+        let (example, total, count) = Value::total(values)?;
+        if count > 0 { Value::number_like(example, total / count) } else { None }
+    }
+}
+
+impl Value {
+    /// The first value, the total of the values with their multiplicities, and their count.
+    ///
+    /// Durations are totalled in nanoseconds. The result is `None` if the values are not all
+    /// integers or all durations, or if the total overflows.
+    fn total<'a>(values: &[(&'a Self, Diff)]) -> Option<(&'a Self, i128, i128)> {
+        let example = values.first()?.0;
+        let mut total: i128 = 0;
+        let mut count: i128 = 0;
+        for (value, diff) in values.iter() {
+            let number = match (example, value) {
+                (Value::Usize(_), Value::Usize(number)) => *number as i128,
+                (Value::Duration(_), Value::Duration(duration)) => duration.as_nanos() as i128,
+                _ => return None,
+            };
+            total = total.checked_add(number.checked_mul(*diff as i128)?)?;
+            count += *diff as i128;
+        }
+        Some((example, total, count))
+    }
+    /// The number `number` as a value of the same variant as `example`, if it is representable.
+    fn number_like(example: &Self, number: i128) -> Option<Self> {
+        use std::convert::TryFrom;
+        match example {
+            Value::Usize(_) => usize::try_from(number).ok().map(Value::Usize),
+            Value::Duration(_) => {
+                let nanos = u128::try_from(number).ok()?;
+                let secs = u64::try_from(nanos / 1_000_000_000).ok()?;
+                Some(Value::Duration(Duration::new(secs, (nanos % 1_000_000_000) as u32)))
+            },
+            _ => None,
+        }
+    }
 }
 
 impl From<usize> for Value { fn from(x: usize) -> Self { Value::Usize(x) } }
@@ -27,6 +27,8 @@ pub mod logging;
 
 pub mod concrete;
 
+pub mod sql;
+
 /// System-wide notion of time.
 pub type Time = ::std::time::Duration;
 /// System-wide update type.
@@ -44,6 +46,12 @@ pub trait Datum : Hash+Sized+Debug {
     fn subject_to(data: &[Self], expr: &Self::Expression) -> Self;
     /// Creates a expression that implements projection.
     fn projection(index: usize) -> Self::Expression;
+    /// Creates a value from a number of tuples, if the type can represent it.
+    fn from_count(_count: Diff) -> Option<Self> { None }
+    /// Sums values, each with a multiplicity, if the values are numbers with a positive count.
+    fn sum(_values: &[(&Self, Diff)]) -> Option<Self> { None }
+    /// Averages values, each with a multiplicity, if the values are numbers with a positive count.
+    fn average(_values: &[(&Self, Diff)]) -> Option<Self> { None }
 }
 
 /// A type that can be converted to a vector of another type.
@@ -7,19 +7,19 @@ use differential_dataflow::{Collection, ExchangeData};
 
 use {TraceManager, Time, Diff};
 
-// pub mod count;
 pub mod filter;
 pub mod join;
 pub mod map;
+pub mod reduce;
 pub mod sfw;
 
 use crate::Datum;
 
-// pub use self::count::Count;
 pub use self::filter::{Filter, Predicate};
 pub use self::join::Join;
 pub use self::sfw::MultiwayJoin;
 pub use self::map::Map;
+pub use self::reduce::{Reduce, Aggregate};
 
 /// A type that can be rendered as a collection.
 pub trait Render : Sized {
@@ -56,6 +56,8 @@ pub enum Plan<V: Datum> {
     MultiwayJoin(MultiwayJoin<V>),
     /// Negation
     Negate(Box<Plan<V>>),
+    /// Groups by key and aggregates
+    Reduce(Reduce<V>),
     /// Filters bindings by one of the built-in predicates
     Filter(Filter<V>),
     /// Sources data from another relation.
@@ -116,6 +118,14 @@ impl<V: ExchangeData+Hash+Datum> Plan<V> {
     pub fn negate(self) -> Self {
         Plan::Negate(Box::new(self))
     }
+    /// Groups tuples by the values at `keys`, and produces the key values followed by each aggregate.
+    pub fn reduce(self, keys: Vec<usize>, aggregates: Vec<Aggregate>) -> Self {
+        Plan::Reduce(Reduce {
+            keys,
+            aggregates,
+            plan: Box::new(self),
+        })
+    }
     /// Restricts collection to tuples satisfying the predicate.
     pub fn filter(self, predicate: Predicate<V>) -> Self {
         Plan::Filter(Filter { predicate, plan: Box::new(self) } )
@@ -205,6 +215,7 @@ impl<V: ExchangeData+Hash+Datum> Render for Plan<V> {
                 Plan::Negate(negate) => {
                     negate.render(scope, collections, arrangements).negate()
                 },
+                Plan::Reduce(reduce) => reduce.render(scope, collections, arrangements),
                 Plan::Filter(filter) => filter.render(scope, collections, arrangements),
                 Plan::Source(source) => {
                     arrangements
@@ -0,0 +1,157 @@
+//! Grouping and aggregation expression plan.
+
+use std::hash::Hash;
+
+use timely::dataflow::Scope;
+
+use differential_dataflow::operators::reduce::ReduceCore;
+use differential_dataflow::trace::implementations::ord::OrdValSpine;
+
+use differential_dataflow::{Collection, ExchangeData};
+use plan::{Plan, Render};
+use {TraceManager, Time, Diff, Datum};
+
+/// Functions aggregating the values of a group.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Aggregate {
+    /// The number of tuples.
+    Count,
+    /// The sum of values at an index.
+    Sum(usize),
+    /// The least value at an index.
+    Min(usize),
+    /// The greatest value at an index.
+    Max(usize),
+    /// The average of values at an index.
+    Avg(usize),
+}
+
+/// A plan stage grouping tuples by the values at key indices, and producing
+/// for each group its key values followed by each of the aggregates.
+///
+/// A group produces no result while any of its aggregates is undefined, for
+/// example a sum of values that are not numbers, or of tuples whose count is
+/// not positive.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Reduce<V: Datum> {
+    /// Indices of the values to group by.
+    pub keys: Vec<usize>,
+    /// Aggregates to produce for each group.
+    pub aggregates: Vec<Aggregate>,
+    /// Plan for the data source.
+    pub plan: Box<Plan<V>>,
+}
+
+impl<V: ExchangeData+Hash+Datum> Render for Reduce<V> {
+
+    type Value = V;
+
+    fn render<S: Scope<Timestamp = Time>>(
+        &self,
+        scope: &mut S,
+        collections: &mut std::collections::HashMap<Plan<Self::Value>, Collection<S, Vec<Self::Value>, Diff>>,
+        arrangements: &mut TraceManager<Self::Value>,
+    ) -> Collection<S, Vec<Self::Value>, Diff>
+    {
+        use differential_dataflow::operators::arrange::ArrangeByKey;
+
+        // Results are arranged by their leading key values, with aggregates as values.
+        let plan = Plan::Reduce(self.clone());
+        let output_keys = (0 .. self.keys.len()).collect::<Vec<_>>();
+        if let Some(mut trace) = arrangements.get_keyed(&plan, &output_keys[..]) {
+            return trace
+                .import(scope)
+                .as_collection(|keys, aggregates| keys.iter().cloned().chain(aggregates.iter().cloned()).collect());
+        }
+
+        // acquire an arrangement of the input by its keys.
+        let mut trace =
+        if let Some(arrangement) = arrangements.get_keyed(&self.plan, &self.keys[..]) {
+            arrangement
+        }
+        else {
+            let keys = self.keys.clone();
+            let arrangement =
+            self.plan
+                .render(scope, collections, arrangements)
+                .map(move |tuple|
+                    (
+                        keys.iter().map(|index| tuple[*index].clone()).collect::<Vec<_>>(),
+                        tuple
+                            .into_iter()
+                            .enumerate()
+                            .filter(|(index,_value)| !keys.contains(index))
+                            .map(|(_index,value)| value)
+                            .collect::<Vec<_>>(),
+                    )
+                )
+                .arrange_by_key();
+
+            arrangements.set_keyed(&self.plan, &self.keys[..], &arrangement.trace);
+            arrangement.trace
+        };
+
+        // Each aggregated index is found either among the keys or among the values.
+        let keys = self.keys.clone();
+        let locate = move |index: usize| {
+            if let Some(position) = keys.iter().position(|key| key == &index) {
+                Err(position)
+            }
+            else {
+                Ok(index - keys.iter().filter(|key| key < &&index).count())
+            }
+        };
+        let aggregates =
+        self.aggregates
+            .iter()
+            .map(|aggregate| {
+                let index = match aggregate {
+                    Aggregate::Count => None,
+                    Aggregate::Sum(index) | Aggregate::Min(index) | Aggregate::Max(index) | Aggregate::Avg(index) => Some(locate(*index)),
+                };
+                (aggregate.clone(), index)
+            })
+            .collect::<Vec<_>>();
+
+        let output =
+        trace
+            .import(scope)
+            .reduce_abelian::<_,OrdValSpine<Vec<V>,Vec<V>,Time,Diff>>("Reduce", move |keys, input, output| {
+                let results =
+                aggregates
+                    .iter()
+                    .map(|(aggregate, index)| {
+                        let values =
+                        index
+                            .as_ref()
+                            .map(|index| {
+                                input
+                                    .iter()
+                                    .map(|(vals, diff)| {
+                                        let value = match index {
+                                            Err(position) => &keys[*position],
+                                            Ok(position) => &vals[*position],
+                                        };
+                                        (value, *diff)
+                                    })
+                                    .collect::<Vec<_>>()
+                            })
+                            .unwrap_or_default();
+                        match aggregate {
+                            Aggregate::Count => V::from_count(input.iter().map(|(_, diff)| diff).sum()),
+                            Aggregate::Sum(_) => V::sum(&values[..]),
+                            Aggregate::Min(_) => values.iter().filter(|(_, diff)| *diff > 0).map(|(value, _)| *value).min().cloned(),
+                            Aggregate::Max(_) => values.iter().filter(|(_, diff)| *diff > 0).map(|(value, _)| *value).max().cloned(),
+                            Aggregate::Avg(_) => V::average(&values[..]),
+                        }
+                    })
+                    .collect::<Option<Vec<_>>>();
+                if let Some(results) = results {
+                    output.push((results, 1));
+                }
+            });
+
+        arrangements.set_keyed(&plan, &output_keys[..], &output.trace);
+        output.as_collection(|keys, aggregates| keys.iter().cloned().chain(aggregates.iter().cloned()).collect())
+    }
+}
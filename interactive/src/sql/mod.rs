@@ -0,0 +1,526 @@
+//! A SQL frontend producing query plans.
+//!
+//! Queries are written against named collections whose column names and kinds are recorded in a
+//! `Catalog`, either when inputs are created through the catalog or when rules are defined from SQL.
+//! The supported language is a subset of SQL:
+//!
+//! ```text
+//! query  := select ( UNION [ALL] select )*
+//! select := SELECT [DISTINCT] ( * | item , ... )
+//!           FROM table [[AS] alias] ( , table [[AS] alias] | [INNER] JOIN table [[AS] alias] ON cond | CROSS JOIN table [[AS] alias] )*
+//!           [ WHERE cond ]
+//!           [ GROUP BY column , ... ]
+//! item   := column [[AS] alias] | COUNT(*) [[AS] alias] | func(column) [[AS] alias]
+//! func   := COUNT | SUM | MIN | MAX | AVG
+//! cond   := cond OR cond | cond AND cond | NOT cond | ( cond ) | operand op operand
+//! op     := < | <= | > | >= | = | <> | !=
+//! ```
+//!
+//! Operands are columns, optionally qualified by a table, unsigned integers, quoted strings, and
+//! `TRUE` and `FALSE`. Conditions equating columns of different tables become join constraints,
+//! and other conditions filter the joined results. Two tables are joined with `Plan::Join`, and
+//! more tables with `Plan::MultiwayJoin`, which requires the tables to be connected by equality
+//! constraints. Queries with aggregates or `GROUP BY` clauses produce one result for each group,
+//! with `Plan::Reduce`; aggregates without `GROUP BY` produce a single group if there are any
+//! results. `SUM` and `AVG` require numeric columns.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use differential_dataflow::ExchangeData;
+
+use plan::{Plan, Predicate, Aggregate};
+use plan::filter::SecondArgument;
+use {Command, Datum, Rule};
+
+mod parse;
+
+use self::parse::{Query, Select, SelectItem, ColumnRef, Expr, Comparison, Operand, Literal, Function};
+
+/// The kinds of values held by a column.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Kind {
+    /// Numbers, which can be summed and averaged.
+    Number,
+    /// Any other values.
+    Other,
+}
+
+/// Column names and kinds of named collections, against which queries are resolved.
+///
+/// # Examples
+///
+/// ```
+/// extern crate interactive;
+///
+/// use interactive::Plan;
+/// use interactive::plan::Aggregate;
+/// use interactive::concrete::Value;
+/// use interactive::sql::{Catalog, Kind};
+///
+/// fn main() {
+///
+///     let mut catalog = Catalog::new();
+///     let _command = catalog.create_input::<Value>("Edges", &[("src", Kind::Number), ("dst", Kind::Number)], Vec::new());
+///
+///     let plan = catalog.plan::<Value>("SELECT e1.src, e2.dst FROM Edges e1 JOIN Edges e2 ON e1.dst = e2.src").unwrap();
+///     assert_eq!(plan, Plan::source("Edges").join(Plan::source("Edges"), vec![(1, 0)]).project(vec![1, 2]));
+///
+///     let plan = catalog.plan::<Value>("SELECT src, COUNT(*) FROM Edges GROUP BY src").unwrap();
+///     assert_eq!(plan, Plan::source("Edges").reduce(vec![0], vec![Aggregate::Count]).project(vec![0, 1]));
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Catalog {
+    schemas: HashMap<String, (Vec<String>, Vec<Kind>)>,
+}
+
+impl Catalog {
+    /// Creates a new, empty catalog.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Records the column names and kinds of the collection `name`.
+    pub fn register(&mut self, name: &str, columns: Vec<(String, Kind)>) {
+        self.schemas.insert(name.to_string(), columns.into_iter().unzip());
+    }
+    /// The column names of the collection `name`, if registered.
+    pub fn columns(&self, name: &str) -> Option<&[String]> {
+        self.schemas.get(name).map(|(columns, _kinds)| &columns[..])
+    }
+    /// The column kinds of the collection `name`, if registered.
+    pub fn kinds(&self, name: &str) -> Option<&[Kind]> {
+        self.schemas.get(name).map(|(_columns, kinds)| &kinds[..])
+    }
+    /// Registers the columns of a new named input, and produces the command creating it.
+    pub fn create_input<V: Datum>(&mut self, name: &str, columns: &[(&str, Kind)], data: Vec<Vec<V>>) -> Command<V> {
+        self.register(name, columns.iter().map(|(column, kind)| (column.to_string(), *kind)).collect());
+        Command::CreateInput(name.to_string(), data)
+    }
+    /// Plans the query `sql`.
+    pub fn plan<V>(&self, sql: &str) -> Result<Plan<V>, String>
+    where
+        V: ExchangeData+Hash+Datum+From<usize>+From<String>+From<bool>,
+    {
+        let query = parse::parse(sql)?;
+        self.lower(&query).map(|(plan, _columns)| plan)
+    }
+    /// Plans the query `sql` as a rule named `name`, and registers the columns of its results.
+    ///
+    /// Columns of the results of `UNION` queries are numbers only if they are numbers in each query.
+    pub fn rule<V>(&mut self, name: &str, sql: &str) -> Result<Rule<V>, String>
+    where
+        V: ExchangeData+Hash+Datum+From<usize>+From<String>+From<bool>,
+    {
+        let query = parse::parse(sql)?;
+        let (plan, columns) = self.lower(&query)?;
+        self.register(name, columns);
+        Ok(plan.into_rule(name))
+    }
+
+    /// Lowers a query to a plan, and the names and kinds of its result columns.
+    fn lower<V>(&self, query: &Query) -> Result<(Plan<V>, Vec<(String, Kind)>), String>
+    where
+        V: ExchangeData+Hash+Datum+From<usize>+From<String>+From<bool>,
+    {
+        match query {
+            Query::Select(select) => self.lower_select(select),
+            Query::Union(query1, query2, all) => {
+                let (plan1, columns1) = self.lower(query1)?;
+                let (plan2, columns2) = self.lower(query2)?;
+                if columns1.len() != columns2.len() {
+                    return Err(format!("UNION of {} and {} columns", columns1.len(), columns2.len()));
+                }
+                let columns =
+                columns1
+                    .into_iter()
+                    .zip(columns2.into_iter())
+                    .map(|((name, kind1), (_, kind2))| (name, if kind1 == kind2 { kind1 } else { Kind::Other }))
+                    .collect();
+                let plan = plan1.concat(plan2);
+                Ok((if *all { plan } else { plan.distinct() }, columns))
+            },
+        }
+    }
+
+    fn lower_select<V>(&self, select: &Select) -> Result<(Plan<V>, Vec<(String, Kind)>), String>
+    where
+        V: ExchangeData+Hash+Datum+From<usize>+From<String>+From<bool>,
+    {
+        let mut bindings = Bindings { tables: Vec::new(), layout: HashMap::new() };
+        for (name, alias) in select.from.iter() {
+            let (columns, kinds) = self.schemas.get(name).ok_or_else(|| format!("Unknown table: {}", name))?;
+            let alias = alias.clone().unwrap_or_else(|| name.clone());
+            if bindings.tables.iter().any(|table| table.alias == alias) {
+                return Err(format!("Duplicate table name: {}", alias));
+            }
+            bindings.tables.push(Table { alias, name: name.clone(), columns, kinds });
+        }
+
+        // Top-level conditions equating columns of different tables become join constraints.
+        let mut conjuncts = Vec::new();
+        for condition in select.conditions.iter() {
+            conjunctions(condition, &mut conjuncts);
+        }
+        let mut equalities = Vec::new();
+        let mut residual = Vec::new();
+        for conjunct in conjuncts {
+            if let Expr::Compare(Operand::Column(column1), Comparison::Equal, Operand::Column(column2)) = conjunct {
+                let attribute1 = bindings.resolve(column1)?;
+                let attribute2 = bindings.resolve(column2)?;
+                if attribute1.0 != attribute2.0 {
+                    unify(&mut equalities, attribute1, attribute2);
+                    continue;
+                }
+            }
+            residual.push(conjunct);
+        }
+
+        // The columns and aggregates to produce, and their names.
+        let mut outputs = Vec::new();
+        for item in select.items.iter() {
+            match item {
+                SelectItem::Wildcard => {
+                    for (input, table) in bindings.tables.iter().enumerate() {
+                        for (attr, name) in table.columns.iter().enumerate() {
+                            outputs.push((Output::Column((input, attr)), name.clone()));
+                        }
+                    }
+                },
+                SelectItem::Column(column, alias) => {
+                    let name = alias.clone().unwrap_or_else(|| column.name.clone());
+                    outputs.push((Output::Column(bindings.resolve(column)?), name));
+                },
+                SelectItem::Aggregate(function, column, alias) => {
+                    let name = alias.clone().unwrap_or_else(|| format!("{:?}", function).to_lowercase());
+                    let attribute = match column {
+                        Some(column) => {
+                            let attribute = bindings.resolve(column)?;
+                            if (*function == Function::Sum || *function == Function::Avg) && bindings.kind(attribute) != Kind::Number {
+                                return Err(format!("{:?} requires a numeric column: {}", function, display(column)));
+                            }
+                            Some(attribute)
+                        },
+                        None => None,
+                    };
+                    outputs.push((Output::Aggregate(*function, attribute), name));
+                },
+            }
+        }
+
+        let groups = select.group_by.iter().map(|column| bindings.resolve(column)).collect::<Result<Vec<_>, _>>()?;
+        let grouped = !groups.is_empty() || outputs.iter().any(|(output, _)| if let Output::Aggregate(..) = output { true } else { false });
+        if grouped {
+            for (output, name) in outputs.iter() {
+                if let Output::Column(attribute) = output {
+                    if !groups.contains(attribute) {
+                        return Err(format!("Column {} must appear in GROUP BY", name));
+                    }
+                }
+            }
+        }
+
+        // Attributes required of the join, for outputs, filters, and equalities within a table.
+        let mut required = groups.clone();
+        for (output, _) in outputs.iter() {
+            match output {
+                Output::Column(attribute) => required.push(*attribute),
+                Output::Aggregate(_, Some(attribute)) => required.push(*attribute),
+                Output::Aggregate(_, None) => { },
+            }
+        }
+        for conjunct in residual.iter() {
+            let mut columns = Vec::new();
+            referenced(conjunct, &mut columns);
+            for column in columns {
+                required.push(bindings.resolve(column)?);
+            }
+        }
+        for class in equalities.iter() {
+            for attribute in class.iter() {
+                if class.iter().filter(|other| other.0 == attribute.0).count() > 1 {
+                    required.push(*attribute);
+                }
+            }
+        }
+        required.sort();
+        required.dedup();
+
+        let mut plan = match bindings.tables.len() {
+            1 => {
+                for attr in 0 .. bindings.tables[0].columns.len() {
+                    bindings.layout.insert((0, attr), attr);
+                }
+                Plan::source(&bindings.tables[0].name)
+            },
+            2 => {
+                // Join keys precede the remaining columns of each table.
+                let mut keys = Vec::new();
+                for class in equalities.iter() {
+                    for &(_, attr1) in class.iter().filter(|attribute| attribute.0 == 0) {
+                        for &(_, attr2) in class.iter().filter(|attribute| attribute.0 == 1) {
+                            keys.push((attr1, attr2));
+                        }
+                    }
+                }
+                for (position, &(attr1, attr2)) in keys.iter().enumerate() {
+                    bindings.layout.insert((0, attr1), position);
+                    bindings.layout.insert((1, attr2), position);
+                }
+                let mut position = keys.len();
+                for (input, table) in bindings.tables.iter().enumerate() {
+                    for attr in 0 .. table.columns.len() {
+                        if !keys.iter().any(|&(attr1, attr2)| attr == if input == 0 { attr1 } else { attr2 }) {
+                            bindings.layout.insert((input, attr), position);
+                            position += 1;
+                        }
+                    }
+                }
+                Plan::source(&bindings.tables[0].name)
+                    .join(Plan::source(&bindings.tables[1].name), keys)
+            },
+            _ => {
+                // Multiway joins extend results along equality constraints, and form no cross products.
+                if !connected(bindings.tables.len(), &equalities) {
+                    return Err("Tables of multiway joins must be connected by equality constraints".to_string());
+                }
+                for (position, attribute) in required.iter().enumerate() {
+                    bindings.layout.insert(*attribute, position);
+                }
+                Plan::multiway_join(
+                    bindings.tables.iter().map(|table| Plan::source(&table.name)).collect(),
+                    equalities.iter().map(|class| class.iter().map(|&(input, attr)| (attr, input)).collect()).collect(),
+                    required.iter().map(|&(input, attr)| (attr, input)).collect(),
+                )
+            },
+        };
+
+        // Multiway joins do not enforce equalities within a table, and remaining conditions filter results.
+        let mut predicates = Vec::new();
+        if bindings.tables.len() > 2 {
+            for class in equalities.iter() {
+                for attribute in class.iter() {
+                    let first = class.iter().find(|other| other.0 == attribute.0).expect("attribute is in its class");
+                    if first != attribute {
+                        predicates.push(Predicate::Equal(bindings.layout[first], SecondArgument::Position(bindings.layout[attribute])));
+                    }
+                }
+            }
+        }
+        for conjunct in residual {
+            predicates.push(bindings.predicate(conjunct)?);
+        }
+        if !predicates.is_empty() {
+            let predicate = if predicates.len() == 1 { predicates.pop().unwrap() } else { Predicate::All(predicates) };
+            plan = plan.filter(predicate);
+        }
+
+        if grouped {
+            // Groups are produced as their keys followed by their aggregates.
+            let mut aggregates = Vec::new();
+            let mut positions = Vec::new();
+            for (output, _) in outputs.iter() {
+                match output {
+                    Output::Column(attribute) => {
+                        positions.push(groups.iter().position(|group| group == attribute).expect("grouped columns are keys"));
+                    },
+                    Output::Aggregate(function, attribute) => {
+                        let index = attribute.map(|attribute| bindings.layout[&attribute]);
+                        let aggregate = match (function, index) {
+                            (Function::Count, _) => Aggregate::Count,
+                            (Function::Sum, Some(index)) => Aggregate::Sum(index),
+                            (Function::Min, Some(index)) => Aggregate::Min(index),
+                            (Function::Max, Some(index)) => Aggregate::Max(index),
+                            (Function::Avg, Some(index)) => Aggregate::Avg(index),
+                            (function, None) => return Err(format!("{:?} requires a column", function)),
+                        };
+                        positions.push(groups.len() + aggregates.len());
+                        aggregates.push(aggregate);
+                    },
+                }
+            }
+            let keys = groups.iter().map(|group| bindings.layout[group]).collect();
+            plan = plan.reduce(keys, aggregates).project(positions);
+        }
+        else {
+            let positions = outputs.iter().map(|(output, _)| match output {
+                Output::Column(attribute) => bindings.layout[attribute],
+                Output::Aggregate(..) => unreachable!("aggregates are grouped"),
+            }).collect();
+            plan = plan.project(positions);
+        }
+        if select.distinct {
+            plan = plan.distinct();
+        }
+
+        let columns = outputs.into_iter().map(|(output, name)| {
+            let kind = match output {
+                Output::Column(attribute) => bindings.kind(attribute),
+                Output::Aggregate(Function::Min, Some(attribute)) |
+                Output::Aggregate(Function::Max, Some(attribute)) => bindings.kind(attribute),
+                Output::Aggregate(..) => Kind::Number,
+            };
+            (name, kind)
+        }).collect();
+
+        Ok((plan, columns))
+    }
+}
+
+/// A column of results, before any grouping.
+enum Output {
+    /// A (table, column) attribute.
+    Column((usize, usize)),
+    /// An aggregate function, of an attribute or of all tuples.
+    Aggregate(Function, Option<(usize, usize)>),
+}
+
+/// A table in a `FROM` clause.
+struct Table<'a> {
+    alias: String,
+    name: String,
+    columns: &'a [String],
+    kinds: &'a [Kind],
+}
+
+/// Tables in scope, and the positions of their attributes in joined tuples.
+struct Bindings<'a> {
+    tables: Vec<Table<'a>>,
+    layout: HashMap<(usize, usize), usize>,
+}
+
+impl<'a> Bindings<'a> {
+
+    /// Resolves a column to a (table, column) attribute.
+    fn resolve(&self, column: &ColumnRef) -> Result<(usize, usize), String> {
+        let mut found = Vec::new();
+        for (input, table) in self.tables.iter().enumerate() {
+            if column.table.as_ref().map(|name| name == &table.alias).unwrap_or(true) {
+                if let Some(attr) = table.columns.iter().position(|name| name == &column.name) {
+                    found.push((input, attr));
+                }
+            }
+        }
+        match found.len() {
+            0 => Err(format!("Unknown column: {}", display(column))),
+            1 => Ok(found[0]),
+            _ => Err(format!("Ambiguous column: {}", display(column))),
+        }
+    }
+
+    /// The kind of a (table, column) attribute.
+    fn kind(&self, attribute: (usize, usize)) -> Kind {
+        self.tables[attribute.0].kinds[attribute.1]
+    }
+
+    /// The position of a column in joined tuples.
+    fn position(&self, column: &ColumnRef) -> Result<usize, String> {
+        let attribute = self.resolve(column)?;
+        Ok(self.layout[&attribute])
+    }
+
+    fn argument<V>(&self, operand: &Operand) -> Result<SecondArgument<V>, String>
+    where
+        V: From<usize>+From<String>+From<bool>,
+    {
+        match operand {
+            Operand::Column(column) => self.position(column).map(SecondArgument::Position),
+            Operand::Literal(Literal::Integer(value)) => Ok(SecondArgument::Constant((*value).into())),
+            Operand::Literal(Literal::String(value)) => Ok(SecondArgument::Constant(value.clone().into())),
+            Operand::Literal(Literal::Bool(value)) => Ok(SecondArgument::Constant((*value).into())),
+        }
+    }
+
+    /// Converts a condition to a predicate on joined tuples.
+    fn predicate<V>(&self, expr: &Expr) -> Result<Predicate<V>, String>
+    where
+        V: From<usize>+From<String>+From<bool>,
+    {
+        match expr {
+            Expr::And(exprs) => exprs.iter().map(|expr| self.predicate(expr)).collect::<Result<_, _>>().map(Predicate::All),
+            Expr::Or(exprs) => exprs.iter().map(|expr| self.predicate(expr)).collect::<Result<_, _>>().map(Predicate::Any),
+            Expr::Not(expr) => self.predicate(expr).map(|predicate| Predicate::Not(Box::new(predicate))),
+            Expr::Compare(left, comparison, right) => {
+                let (position, comparison, argument) = match (left, right) {
+                    (Operand::Column(column), other) => (self.position(column)?, *comparison, self.argument(other)?),
+                    (other, Operand::Column(column)) => (self.position(column)?, comparison.flip(), self.argument(other)?),
+                    _ => return Err("Comparisons require a column".to_string()),
+                };
+                Ok(match comparison {
+                    Comparison::LessThan => Predicate::LessThan(position, argument),
+                    Comparison::LessEqual => Predicate::LessEqual(position, argument),
+                    Comparison::GreaterThan => Predicate::GreaterThan(position, argument),
+                    Comparison::GreaterEqual => Predicate::GreaterEqual(position, argument),
+                    Comparison::Equal => Predicate::Equal(position, argument),
+                    Comparison::NotEqual => Predicate::NotEqual(position, argument),
+                })
+            },
+        }
+    }
+}
+
+fn display(column: &ColumnRef) -> String {
+    match &column.table {
+        Some(table) => format!("{}.{}", table, column.name),
+        None => column.name.clone(),
+    }
+}
+
+/// Collects the top-level conjuncts of `expr`.
+fn conjunctions<'a>(expr: &'a Expr, conjuncts: &mut Vec<&'a Expr>) {
+    match expr {
+        Expr::And(exprs) => { for expr in exprs.iter() { conjunctions(expr, conjuncts); } },
+        _ => conjuncts.push(expr),
+    }
+}
+
+/// Collects the columns referenced by `expr`.
+fn referenced<'a>(expr: &'a Expr, columns: &mut Vec<&'a ColumnRef>) {
+    match expr {
+        Expr::And(exprs) | Expr::Or(exprs) => { for expr in exprs.iter() { referenced(expr, columns); } },
+        Expr::Not(expr) => referenced(expr, columns),
+        Expr::Compare(left, _, right) => {
+            for operand in vec![left, right] {
+                if let Operand::Column(column) = operand {
+                    columns.push(column);
+                }
+            }
+        },
+    }
+}
+
+/// Records that two attributes are equal, merging their equivalence classes.
+fn unify(classes: &mut Vec<Vec<(usize, usize)>>, attribute1: (usize, usize), attribute2: (usize, usize)) {
+    let class1 = classes.iter().position(|class| class.contains(&attribute1));
+    let class2 = classes.iter().position(|class| class.contains(&attribute2));
+    match (class1, class2) {
+        (Some(index1), Some(index2)) if index1 == index2 => { },
+        (Some(index1), Some(index2)) => {
+            let other = classes.remove(std::cmp::max(index1, index2));
+            classes[std::cmp::min(index1, index2)].extend(other);
+        },
+        (Some(index1), None) => classes[index1].push(attribute2),
+        (None, Some(index2)) => classes[index2].push(attribute1),
+        (None, None) => classes.push(vec![attribute1, attribute2]),
+    }
+}
+
+/// Indicates whether all `tables` are connected by the equivalence classes.
+fn connected(tables: usize, classes: &[Vec<(usize, usize)>]) -> bool {
+    let mut reached = vec![0];
+    let mut active = true;
+    while active {
+        active = false;
+        for class in classes.iter() {
+            if class.iter().any(|(input, _)| reached.contains(input)) {
+                for (input, _) in class.iter() {
+                    if !reached.contains(input) {
+                        reached.push(*input);
+                        active = true;
+                    }
+                }
+            }
+        }
+    }
+    reached.len() == tables
+}
@@ -0,0 +1,441 @@
+//! Parsing of SQL text into syntax trees.
+
+/// A query, either a single select statement or a union of queries.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Query {
+    /// A single select statement.
+    Select(Select),
+    /// The union of two queries, retaining duplicates if `all` is set.
+    Union(Box<Query>, Box<Query>, bool),
+}
+
+/// A select statement.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Select {
+    /// Whether duplicate results should be removed.
+    pub distinct: bool,
+    /// Items to produce for each result.
+    pub items: Vec<SelectItem>,
+    /// Tables to join, with their aliases.
+    pub from: Vec<(String, Option<String>)>,
+    /// Conditions from `ON` and `WHERE` clauses.
+    pub conditions: Vec<Expr>,
+    /// Columns to group by.
+    pub group_by: Vec<ColumnRef>,
+}
+
+/// An item in the select list.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SelectItem {
+    /// All columns of all tables.
+    Wildcard,
+    /// A column, with an optional alias.
+    Column(ColumnRef, Option<String>),
+    /// An aggregate of a column, or of all tuples, with an optional alias.
+    Aggregate(Function, Option<ColumnRef>, Option<String>),
+}
+
+/// An aggregate function.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Function {
+    /// `COUNT`
+    Count,
+    /// `SUM`
+    Sum,
+    /// `MIN`
+    Min,
+    /// `MAX`
+    Max,
+    /// `AVG`
+    Avg,
+}
+
+/// A reference to a column, optionally qualified by a table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ColumnRef {
+    /// The table or alias qualifying the column.
+    pub table: Option<String>,
+    /// The name of the column.
+    pub name: String,
+}
+
+/// A boolean condition.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Expr {
+    /// A comparison between two operands.
+    Compare(Operand, Comparison, Operand),
+    /// All of the conditions hold.
+    And(Vec<Expr>),
+    /// Any of the conditions hold.
+    Or(Vec<Expr>),
+    /// The condition does not hold.
+    Not(Box<Expr>),
+}
+
+/// A comparison operator.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Comparison {
+    /// `<`
+    LessThan,
+    /// `<=`
+    LessEqual,
+    /// `>`
+    GreaterThan,
+    /// `>=`
+    GreaterEqual,
+    /// `=`
+    Equal,
+    /// `<>` or `!=`
+    NotEqual,
+}
+
+impl Comparison {
+    /// The comparison with its operands exchanged.
+    pub fn flip(self) -> Self {
+        match self {
+            Comparison::LessThan => Comparison::GreaterThan,
+            Comparison::LessEqual => Comparison::GreaterEqual,
+            Comparison::GreaterThan => Comparison::LessThan,
+            Comparison::GreaterEqual => Comparison::LessEqual,
+            other => other,
+        }
+    }
+}
+
+/// An operand of a comparison.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Operand {
+    /// The value of a column.
+    Column(ColumnRef),
+    /// A constant value.
+    Literal(Literal),
+}
+
+/// A constant value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Literal {
+    /// An unsigned integer.
+    Integer(usize),
+    /// A quoted string.
+    String(String),
+    /// `TRUE` or `FALSE`.
+    Bool(bool),
+}
+
+/// Words that cannot be used as aliases.
+const KEYWORDS: &[&str] = &[
+    "SELECT", "DISTINCT", "FROM", "WHERE", "GROUP", "BY", "JOIN", "INNER", "LEFT", "RIGHT", "FULL",
+    "OUTER", "CROSS", "ON", "AND", "OR", "NOT", "UNION", "ALL", "AS", "TRUE", "FALSE",
+];
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+    Word(String),
+    Integer(usize),
+    String(String),
+    Symbol(&'static str),
+}
+
+/// Splits `text` into tokens.
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(&next) = chars.peek() {
+        if next.is_whitespace() {
+            chars.next();
+        }
+        else if next.is_alphabetic() || next == '_' {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_alphanumeric() || c == '_' { word.push(c); chars.next(); }
+                else { break; }
+            }
+            tokens.push(Token::Word(word));
+        }
+        else if next.is_ascii_digit() {
+            let mut digits = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_ascii_digit() { digits.push(c); chars.next(); }
+                else { break; }
+            }
+            let value = digits.parse().map_err(|_| format!("Integer out of range: {}", digits))?;
+            tokens.push(Token::Integer(value));
+        }
+        else if next == '\'' {
+            chars.next();
+            let mut string = String::new();
+            loop {
+                match chars.next() {
+                    // A doubled quote stands for a single quote.
+                    Some('\'') if chars.peek() == Some(&'\'') => { chars.next(); string.push('\''); },
+                    Some('\'') => break,
+                    Some(c) => string.push(c),
+                    None => return Err("Unterminated string literal".to_string()),
+                }
+            }
+            tokens.push(Token::String(string));
+        }
+        else {
+            chars.next();
+            let symbol = match (next, chars.peek()) {
+                ('<', Some('=')) => { chars.next(); "<=" },
+                ('<', Some('>')) => { chars.next(); "<>" },
+                ('>', Some('=')) => { chars.next(); ">=" },
+                ('!', Some('=')) => { chars.next(); "!=" },
+                ('<', _) => "<",
+                ('>', _) => ">",
+                ('=', _) => "=",
+                (',', _) => ",",
+                ('.', _) => ".",
+                ('(', _) => "(",
+                (')', _) => ")",
+                ('*', _) => "*",
+                (';', _) => ";",
+                _ => return Err(format!("Unexpected character: {:?}", next)),
+            };
+            tokens.push(Token::Symbol(symbol));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Parses `text` as a query.
+pub fn parse(text: &str) -> Result<Query, String> {
+    let mut parser = Parser { tokens: tokenize(text)?, position: 0 };
+    let query = parser.query()?;
+    parser.symbol(";");
+    if let Some(token) = parser.peek() {
+        return Err(format!("Unexpected token after query: {:?}", token));
+    }
+    Ok(query)
+}
+
+/// A recursive descent parser over tokens.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    /// Consumes the keyword `word` if it is next.
+    fn keyword(&mut self, word: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(next)) if next.eq_ignore_ascii_case(word) => { self.position += 1; true },
+            _ => false,
+        }
+    }
+
+    fn expect_keyword(&mut self, word: &str) -> Result<(), String> {
+        if self.keyword(word) { Ok(()) }
+        else { Err(format!("Expected {}, found {:?}", word, self.peek())) }
+    }
+
+    /// Consumes the symbol `symbol` if it is next.
+    fn symbol(&mut self, symbol: &str) -> bool {
+        match self.peek() {
+            Some(Token::Symbol(next)) if next == &symbol => { self.position += 1; true },
+            _ => false,
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), String> {
+        if self.symbol(symbol) { Ok(()) }
+        else { Err(format!("Expected {:?}, found {:?}", symbol, self.peek())) }
+    }
+
+    /// Consumes an identifier, which may not be a keyword.
+    fn identifier(&mut self) -> Result<String, String> {
+        match self.peek().cloned() {
+            Some(Token::Word(word)) => {
+                if KEYWORDS.iter().any(|keyword| keyword.eq_ignore_ascii_case(&word)) {
+                    Err(format!("Expected identifier, found keyword {}", word))
+                }
+                else {
+                    self.position += 1;
+                    Ok(word)
+                }
+            },
+            other => Err(format!("Expected identifier, found {:?}", other)),
+        }
+    }
+
+    /// Consumes an optional alias, introduced by `AS` or a bare identifier.
+    fn alias(&mut self) -> Result<Option<String>, String> {
+        if self.keyword("AS") {
+            self.identifier().map(Some)
+        }
+        else {
+            Ok(self.identifier().ok())
+        }
+    }
+
+    fn query(&mut self) -> Result<Query, String> {
+        let mut query = Query::Select(self.select()?);
+        while self.keyword("UNION") {
+            let all = self.keyword("ALL");
+            let other = Query::Select(self.select()?);
+            query = Query::Union(Box::new(query), Box::new(other), all);
+        }
+        Ok(query)
+    }
+
+    fn select(&mut self) -> Result<Select, String> {
+
+        self.expect_keyword("SELECT")?;
+        let distinct = self.keyword("DISTINCT");
+
+        let mut items = Vec::new();
+        loop {
+            if self.symbol("*") {
+                items.push(SelectItem::Wildcard);
+            }
+            else if let Some(function) = self.function() {
+                self.expect_symbol("(")?;
+                let column = if function == Function::Count && self.symbol("*") { None } else { Some(self.column()?) };
+                self.expect_symbol(")")?;
+                let alias = self.alias()?;
+                items.push(SelectItem::Aggregate(function, column, alias));
+            }
+            else {
+                let column = self.column()?;
+                let alias = self.alias()?;
+                items.push(SelectItem::Column(column, alias));
+            }
+            if !self.symbol(",") { break; }
+        }
+
+        self.expect_keyword("FROM")?;
+        let mut from = vec![self.table()?];
+        let mut conditions = Vec::new();
+        loop {
+            if self.symbol(",") {
+                from.push(self.table()?);
+            }
+            else if self.keyword("JOIN") || (self.keyword("INNER") && { self.expect_keyword("JOIN")?; true }) {
+                from.push(self.table()?);
+                self.expect_keyword("ON")?;
+                conditions.push(self.expr()?);
+            }
+            else if self.keyword("CROSS") {
+                self.expect_keyword("JOIN")?;
+                from.push(self.table()?);
+            }
+            else if ["LEFT", "RIGHT", "FULL", "OUTER"].iter().any(|word| self.keyword(word)) {
+                return Err("Outer joins are not supported".to_string());
+            }
+            else {
+                break;
+            }
+        }
+
+        if self.keyword("WHERE") {
+            conditions.push(self.expr()?);
+        }
+
+        let mut group_by = Vec::new();
+        if self.keyword("GROUP") {
+            self.expect_keyword("BY")?;
+            loop {
+                group_by.push(self.column()?);
+                if !self.symbol(",") { break; }
+            }
+        }
+
+        Ok(Select { distinct, items, from, conditions, group_by })
+    }
+
+    /// Consumes the name of an aggregate function, if it is next and followed by `(`.
+    fn function(&mut self) -> Option<Function> {
+        let function = match (self.tokens.get(self.position), self.tokens.get(self.position + 1)) {
+            (Some(Token::Word(word)), Some(Token::Symbol("("))) => {
+                match word.to_uppercase().as_str() {
+                    "COUNT" => Function::Count,
+                    "SUM" => Function::Sum,
+                    "MIN" => Function::Min,
+                    "MAX" => Function::Max,
+                    "AVG" => Function::Avg,
+                    _ => return None,
+                }
+            },
+            _ => return None,
+        };
+        self.position += 1;
+        Some(function)
+    }
+
+    fn table(&mut self) -> Result<(String, Option<String>), String> {
+        let name = self.identifier()?;
+        let alias = self.alias()?;
+        Ok((name, alias))
+    }
+
+    fn column(&mut self) -> Result<ColumnRef, String> {
+        let name = self.identifier()?;
+        if self.symbol(".") {
+            Ok(ColumnRef { table: Some(name), name: self.identifier()? })
+        }
+        else {
+            Ok(ColumnRef { table: None, name })
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut terms = vec![self.conjunction()?];
+        while self.keyword("OR") {
+            terms.push(self.conjunction()?);
+        }
+        Ok(if terms.len() == 1 { terms.pop().unwrap() } else { Expr::Or(terms) })
+    }
+
+    fn conjunction(&mut self) -> Result<Expr, String> {
+        let mut terms = vec![self.negation()?];
+        while self.keyword("AND") {
+            terms.push(self.negation()?);
+        }
+        Ok(if terms.len() == 1 { terms.pop().unwrap() } else { Expr::And(terms) })
+    }
+
+    fn negation(&mut self) -> Result<Expr, String> {
+        if self.keyword("NOT") {
+            Ok(Expr::Not(Box::new(self.negation()?)))
+        }
+        else if self.symbol("(") {
+            let expr = self.expr()?;
+            self.expect_symbol(")")?;
+            Ok(expr)
+        }
+        else {
+            let left = self.operand()?;
+            let comparison = match self.peek() {
+                Some(Token::Symbol("<")) => Comparison::LessThan,
+                Some(Token::Symbol("<=")) => Comparison::LessEqual,
+                Some(Token::Symbol(">")) => Comparison::GreaterThan,
+                Some(Token::Symbol(">=")) => Comparison::GreaterEqual,
+                Some(Token::Symbol("=")) => Comparison::Equal,
+                Some(Token::Symbol("<>")) | Some(Token::Symbol("!=")) => Comparison::NotEqual,
+                other => return Err(format!("Expected comparison, found {:?}", other)),
+            };
+            self.position += 1;
+            let right = self.operand()?;
+            Ok(Expr::Compare(left, comparison, right))
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand, String> {
+        match self.peek().cloned() {
+            Some(Token::Integer(value)) => { self.position += 1; Ok(Operand::Literal(Literal::Integer(value))) },
+            Some(Token::String(value)) => { self.position += 1; Ok(Operand::Literal(Literal::String(value))) },
+            _ if self.keyword("TRUE") => Ok(Operand::Literal(Literal::Bool(true))),
+            _ if self.keyword("FALSE") => Ok(Operand::Literal(Literal::Bool(false))),
+            _ => self.column().map(Operand::Column),
+        }
+    }
+}
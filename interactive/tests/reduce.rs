@@ -0,0 +1,131 @@
+extern crate timely;
+extern crate differential_dataflow;
+extern crate interactive;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use timely::dataflow::operators::Capture;
+use timely::dataflow::operators::capture::Extract;
+
+use differential_dataflow::input::Input;
+use differential_dataflow::operators::arrange::ArrangeBySelf;
+
+use interactive::{Plan, TraceManager, Time, Diff};
+use interactive::plan::{Aggregate, Render};
+use interactive::concrete::Value;
+
+/// Renders `plan` over the input `Input`, and produces the accumulated results after each round of updates.
+fn results(plan: Plan<Value>, rounds: Vec<Vec<(Vec<Value>, Diff)>>) -> Vec<Vec<(Vec<Value>, Diff)>> {
+
+    let count = rounds.len();
+    let captured = timely::execute_directly(move |worker| {
+
+        let (mut input, trace) = worker.dataflow::<Time,_,_>(|scope| {
+            let (input, collection) = scope.new_collection::<Vec<Value>, Diff>();
+            (input, collection.arrange_by_self().trace)
+        });
+
+        let mut traces = TraceManager::new();
+        traces.set_unkeyed(&Plan::source("Input"), &trace);
+        let captured = worker.dataflow::<Time,_,_>(|scope| {
+            plan.render(scope, &mut HashMap::new(), &mut traces).inner.capture()
+        });
+        drop(traces);
+        drop(trace);
+
+        for (round, updates) in rounds.into_iter().enumerate() {
+            input.advance_to(Duration::from_secs(round as u64));
+            for (data, diff) in updates {
+                input.update(data, diff);
+            }
+        }
+        input.close();
+        while worker.step() { }
+
+        captured
+    });
+
+    let updates = captured.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    (0 .. count).map(|round| {
+        let mut results =
+        updates
+            .iter()
+            .filter(|(_, time, _)| time <= &Duration::from_secs(round as u64))
+            .map(|(data, _, diff)| (data.clone(), *diff))
+            .collect::<Vec<_>>();
+        differential_dataflow::consolidation::consolidate(&mut results);
+        results
+    }).collect()
+}
+
+fn row(values: &[usize]) -> Vec<Value> {
+    values.iter().map(|value| Value::Usize(*value)).collect()
+}
+
+#[test]
+fn aggregates() {
+
+    let plan =
+    Plan::source("Input")
+        .reduce(vec![0], vec![Aggregate::Count, Aggregate::Sum(1), Aggregate::Min(1), Aggregate::Max(1), Aggregate::Avg(1)]);
+
+    let rounds = vec![
+        vec![(row(&[1, 10]), 1), (row(&[1, 20]), 1), (row(&[2, 5]), 2)],
+        vec![(row(&[1, 20]), -1), (row(&[1, 40]), 1), (row(&[1, 15]), 1)],
+        vec![(row(&[2, 5]), -2), (row(&[1, 10]), -1)],
+    ];
+
+    assert_eq!(results(plan, rounds), vec![
+        vec![(row(&[1, 2, 30, 10, 20, 15]), 1), (row(&[2, 2, 10, 5, 5, 5]), 1)],
+        vec![(row(&[1, 3, 65, 10, 40, 21]), 1), (row(&[2, 2, 10, 5, 5, 5]), 1)],
+        vec![(row(&[1, 2, 55, 15, 40, 27]), 1)],
+    ]);
+}
+
+#[test]
+fn aggregates_of_keys_and_durations() {
+
+    let plan =
+    Plan::source("Input")
+        .reduce(vec![0], vec![Aggregate::Sum(0), Aggregate::Sum(1), Aggregate::Avg(1)]);
+
+    let row = |key: usize, millis: u64| vec![Value::Usize(key), Value::Duration(Duration::from_millis(millis))];
+    let rounds = vec![
+        vec![(row(1, 1500), 1), (row(1, 500), 1)],
+        vec![(row(1, 500), -1), (row(1, 1000), 2)],
+    ];
+
+    // durations are averaged in nanoseconds.
+    let result = |key: usize, sum: usize, total: u64, average: u64| vec![
+        Value::Usize(key),
+        Value::Usize(sum),
+        Value::Duration(Duration::from_millis(total)),
+        Value::Duration(Duration::from_nanos(average)),
+    ];
+    assert_eq!(results(plan, rounds), vec![
+        vec![(result(1, 2, 2000, 1_000_000_000), 1)],
+        vec![(result(1, 3, 3500, 1_166_666_666), 1)],
+    ]);
+}
+
+#[test]
+fn undefined_aggregates() {
+
+    // sums of values that are not numbers, or of groups without a positive count, produce no results.
+    let plan =
+    Plan::source("Input")
+        .reduce(vec![0], vec![Aggregate::Count, Aggregate::Sum(1)]);
+
+    let rounds = vec![
+        vec![(vec![Value::Usize(1), Value::String("one".to_string())], 1), (row(&[2, 3]), 1)],
+        vec![(row(&[2, 3]), -1), (row(&[2, 4]), -1)],
+        vec![(row(&[2, 4]), 1)],
+    ];
+
+    assert_eq!(results(plan, rounds), vec![
+        vec![(row(&[2, 1, 3]), 1)],
+        vec![],
+        vec![],
+    ]);
+}
@@ -0,0 +1,157 @@
+extern crate interactive;
+
+use interactive::Plan;
+use interactive::plan::{Aggregate, Predicate};
+use interactive::plan::filter::SecondArgument;
+use interactive::concrete::Value;
+use interactive::sql::{Catalog, Kind};
+
+fn catalog() -> Catalog {
+    let mut catalog = Catalog::new();
+    catalog.create_input::<Value>("Edges", &[("src", Kind::Number), ("dst", Kind::Number)], Vec::new());
+    catalog.create_input::<Value>("Names", &[("id", Kind::Number), ("name", Kind::Other)], Vec::new());
+    catalog
+}
+
+#[test]
+fn single_table() {
+
+    let catalog = catalog();
+
+    let plan = catalog.plan::<Value>("SELECT dst, src FROM Edges WHERE src < 5").unwrap();
+    let expected =
+    Plan::source("Edges")
+        .filter(Predicate::LessThan(0, SecondArgument::Constant(Value::Usize(5))))
+        .project(vec![1, 0]);
+    assert_eq!(plan, expected);
+
+    // comparisons with the column second are flipped.
+    let plan = catalog.plan::<Value>("SELECT DISTINCT * FROM Edges WHERE 5 > src").unwrap();
+    let expected =
+    Plan::source("Edges")
+        .filter(Predicate::LessThan(0, SecondArgument::Constant(Value::Usize(5))))
+        .project(vec![0, 1])
+        .distinct();
+    assert_eq!(plan, expected);
+}
+
+#[test]
+fn two_table_join() {
+
+    let catalog = catalog();
+
+    // join keys precede the remaining columns of each table.
+    let plan = catalog.plan::<Value>("SELECT * FROM Edges e1, Edges e2 WHERE e1.dst = e2.src AND e1.src <> e2.dst").unwrap();
+    let expected =
+    Plan::source("Edges")
+        .join(Plan::source("Edges"), vec![(1, 0)])
+        .filter(Predicate::NotEqual(1, SecondArgument::Position(2)))
+        .project(vec![1, 0, 0, 2]);
+    assert_eq!(plan, expected);
+
+    let plan = catalog.plan::<Value>("SELECT e1.src, e2.dst FROM Edges e1 INNER JOIN Edges e2 ON e1.dst = e2.src").unwrap();
+    let expected =
+    Plan::source("Edges")
+        .join(Plan::source("Edges"), vec![(1, 0)])
+        .project(vec![1, 2]);
+    assert_eq!(plan, expected);
+}
+
+#[test]
+fn multiway_join() {
+
+    let catalog = catalog();
+
+    let plan = catalog.plan::<Value>("
+        SELECT e1.src, e2.src, e3.dst
+        FROM Edges e1, Edges e2, Edges e3
+        WHERE e1.dst = e2.src AND e2.dst = e3.dst AND e1.src = e3.src
+    ").unwrap();
+    let expected =
+    Plan::multiway_join(
+        vec![Plan::source("Edges"), Plan::source("Edges"), Plan::source("Edges")],
+        vec![vec![(1, 0), (0, 1)], vec![(1, 1), (1, 2)], vec![(0, 0), (0, 2)]],
+        vec![(0, 0), (0, 1), (1, 2)],
+    )
+    .project(vec![0, 1, 2]);
+    assert_eq!(plan, expected);
+}
+
+#[test]
+fn group_by() {
+
+    let catalog = catalog();
+
+    let plan = catalog.plan::<Value>("SELECT MAX(dst), src FROM Edges GROUP BY src").unwrap();
+    let expected =
+    Plan::source("Edges")
+        .reduce(vec![0], vec![Aggregate::Max(1)])
+        .project(vec![1, 0]);
+    assert_eq!(plan, expected);
+
+    let plan = catalog.plan::<Value>("SELECT name, SUM(id), AVG(id) FROM Names GROUP BY name").unwrap();
+    let expected =
+    Plan::source("Names")
+        .reduce(vec![1], vec![Aggregate::Sum(0), Aggregate::Avg(0)])
+        .project(vec![0, 1, 2]);
+    assert_eq!(plan, expected);
+
+    // aggregates without `GROUP BY` form a single group.
+    let plan = catalog.plan::<Value>("SELECT COUNT(*), MIN(dst) FROM Edges").unwrap();
+    let expected =
+    Plan::source("Edges")
+        .reduce(vec![], vec![Aggregate::Count, Aggregate::Min(1)])
+        .project(vec![0, 1]);
+    assert_eq!(plan, expected);
+}
+
+#[test]
+fn union() {
+
+    let mut catalog = catalog();
+
+    let plan = catalog.plan::<Value>("SELECT src FROM Edges UNION SELECT dst FROM Edges").unwrap();
+    let expected =
+    Plan::source("Edges").project(vec![0])
+        .concat(Plan::source("Edges").project(vec![1]))
+        .distinct();
+    assert_eq!(plan, expected);
+
+    let plan = catalog.plan::<Value>("SELECT src FROM Edges UNION ALL SELECT dst FROM Edges").unwrap();
+    let expected =
+    Plan::source("Edges").project(vec![0])
+        .concat(Plan::source("Edges").project(vec![1]));
+    assert_eq!(plan, expected);
+
+    // rules register the columns of their first query, which are numbers if numbers in each query.
+    catalog.rule::<Value>("Nodes", "SELECT src AS node FROM Edges UNION SELECT dst FROM Edges").unwrap();
+    assert_eq!(catalog.columns("Nodes"), Some(&["node".to_string()][..]));
+    assert_eq!(catalog.kinds("Nodes"), Some(&[Kind::Number][..]));
+    catalog.rule::<Value>("Labels", "SELECT id FROM Names UNION SELECT name FROM Names").unwrap();
+    assert_eq!(catalog.kinds("Labels"), Some(&[Kind::Other][..]));
+
+    // aggregates of rules keep the kinds of their columns.
+    catalog.rule::<Value>("Counts", "SELECT name, COUNT(*), MAX(name) FROM Names GROUP BY name").unwrap();
+    assert_eq!(catalog.kinds("Counts"), Some(&[Kind::Other, Kind::Number, Kind::Other][..]));
+    assert_eq!(catalog.plan::<Value>("SELECT SUM(max) FROM Counts").unwrap_err(), "Sum requires a numeric column: max");
+}
+
+#[test]
+fn errors() {
+
+    let catalog = catalog();
+    let error = |sql: &str| catalog.plan::<Value>(sql).unwrap_err();
+
+    assert_eq!(error("SELECT src FROM Nodes"), "Unknown table: Nodes");
+    assert_eq!(error("SELECT node FROM Edges"), "Unknown column: node");
+    assert_eq!(error("SELECT SUM(name) FROM Names"), "Sum requires a numeric column: name");
+    assert_eq!(error("SELECT id, AVG(n.name) FROM Names n GROUP BY id"), "Avg requires a numeric column: n.name");
+    assert_eq!(error("SELECT e3.src FROM Edges e1, Edges e2"), "Unknown column: e3.src");
+    assert_eq!(error("SELECT src FROM Edges e1, Edges e2 WHERE e1.dst = e2.src"), "Ambiguous column: src");
+    assert_eq!(error("SELECT e.src FROM Edges e, Edges e"), "Duplicate table name: e");
+    assert_eq!(error("SELECT src, dst, COUNT(*) FROM Edges GROUP BY src"), "Column dst must appear in GROUP BY");
+    assert_eq!(error("SELECT src FROM Edges UNION SELECT src, dst FROM Edges"), "UNION of 1 and 2 columns");
+    assert_eq!(error("SELECT * FROM Edges e1, Edges e2, Edges e3 WHERE e1.dst = e2.src"), "Tables of multiway joins must be connected by equality constraints");
+    assert_eq!(error("SELECT * FROM Edges e1 LEFT JOIN Edges e2 ON e1.dst = e2.src"), "Outer joins are not supported");
+    assert_eq!(error("SELECT 'src' FROM Edges"), "Expected identifier, found Some(String(\"src\"))");
+}
@@ -148,6 +148,599 @@ mod present {
     }
 }
 
+pub use self::checked::{Checked, Saturating};
+mod checked {
+
+    use super::{Semigroup, Monoid, Abelian, Multiply};
+
+    /// A signed integer difference that panics on overflow.
+    ///
+    /// Accumulations that overflow the built-in integer types wrap silently in release builds, which
+    /// corrupts results in ways that are hard to trace. This wrapper checks each addition, negation
+    /// and multiplication, and panics with the operands if the result would overflow.
+    #[derive(Abomonation, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Clone, Default, Serialize, Deserialize, Hash)]
+    pub struct Checked<R>(pub R);
+
+    /// A signed integer difference that saturates at the bounds of its type on overflow.
+    ///
+    /// Saturated accumulations are incorrect, but they remain at the bounds of the type rather than
+    /// wrapping to arbitrary values, and `is_saturated` indicates that an accumulation may be corrupt.
+    #[derive(Abomonation, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Clone, Default, Serialize, Deserialize, Hash)]
+    pub struct Saturating<R>(pub R);
+
+    /// Implementations for wrapped built-in signed integers.
+    macro_rules! checked_implementation {
+        ($t:ty) => {
+            impl Semigroup for Checked<$t> {
+                #[inline] fn plus_equals(&mut self, rhs: &Self) {
+                    self.0 = match self.0.checked_add(rhs.0) {
+                        Some(sum) => sum,
+                        None => panic!("difference overflow: {} + {} exceeds the bounds of {}", self.0, rhs.0, stringify!($t)),
+                    };
+                }
+                #[inline] fn is_zero(&self) -> bool { self.0 == 0 }
+            }
+
+            impl Monoid for Checked<$t> {
+                #[inline] fn zero() -> Self { Checked(0) }
+            }
+
+            impl Abelian for Checked<$t> {
+                #[inline] fn negate(self) -> Self {
+                    match self.0.checked_neg() {
+                        Some(neg) => Checked(neg),
+                        None => panic!("difference overflow: -({}) exceeds the bounds of {}", self.0, stringify!($t)),
+                    }
+                }
+            }
+
+            impl Multiply<Self> for Checked<$t> {
+                type Output = Self;
+                fn multiply(self, rhs: &Self) -> Self {
+                    match self.0.checked_mul(rhs.0) {
+                        Some(product) => Checked(product),
+                        None => panic!("difference overflow: {} * {} exceeds the bounds of {}", self.0, rhs.0, stringify!($t)),
+                    }
+                }
+            }
+
+            impl From<i8> for Checked<$t> {
+                fn from(value: i8) -> Self { Checked(value.into()) }
+            }
+
+            impl Semigroup for Saturating<$t> {
+                #[inline] fn plus_equals(&mut self, rhs: &Self) { self.0 = self.0.saturating_add(rhs.0); }
+                #[inline] fn is_zero(&self) -> bool { self.0 == 0 }
+            }
+
+            impl Monoid for Saturating<$t> {
+                #[inline] fn zero() -> Self { Saturating(0) }
+            }
+
+            impl Abelian for Saturating<$t> {
+                #[inline] fn negate(self) -> Self { Saturating(self.0.saturating_neg()) }
+            }
+
+            impl Multiply<Self> for Saturating<$t> {
+                type Output = Self;
+                fn multiply(self, rhs: &Self) -> Self { Saturating(self.0.saturating_mul(rhs.0)) }
+            }
+
+            impl From<i8> for Saturating<$t> {
+                fn from(value: i8) -> Self { Saturating(value.into()) }
+            }
+
+            impl Saturating<$t> {
+                /// Indicates whether the accumulation has reached the bounds of its type, and may be corrupt.
+                pub fn is_saturated(&self) -> bool {
+                    self.0 == <$t>::max_value() || self.0 == <$t>::min_value()
+                }
+            }
+        };
+    }
+
+    checked_implementation!(i8);
+    checked_implementation!(i16);
+    checked_implementation!(i32);
+    checked_implementation!(i64);
+    checked_implementation!(i128);
+    checked_implementation!(isize);
+}
+
+pub use self::hyperloglog::HyperLogLog;
+mod hyperloglog {
+
+    use super::{Semigroup, Monoid, Multiply};
+
+    /// The number of bits of each hash that select a register.
+    const PRECISION: u32 = 12;
+    /// The number of registers, each of which records the greatest rank of the hashes it has seen.
+    const REGISTERS: usize = 1 << PRECISION;
+
+    /// A HyperLogLog sketch of a set of hashes, which estimates the number of distinct hashes.
+    ///
+    /// Addition unions the sketched sets, and the empty sketch is zero. Sketches cannot remove hashes,
+    /// and the type has no negation; it is appropriate for collections whose records are only added.
+    /// Multiplication by a positive unsigned integer leaves the sketch unchanged, as the union of a set
+    /// with itself is the set, and multiplication by zero produces the empty sketch. There is no
+    /// multiplication by signed integers, as sketches cannot be negated.
+    ///
+    /// The sketch uses 4096 registers, and its estimates have a standard error of about 1.6%. Only the
+    /// registers that have seen hashes are stored, so that sketches of few hashes are small.
+    #[derive(Abomonation, Ord, PartialOrd, Eq, PartialEq, Debug, Clone, Default, Serialize, Deserialize, Hash)]
+    pub struct HyperLogLog {
+        /// Pairs of register index and non-zero rank, ordered by index.
+        registers: Vec<(u16, u8)>,
+    }
+
+    impl HyperLogLog {
+        /// Allocates an empty sketch.
+        pub fn new() -> Self {
+            HyperLogLog { registers: Vec::new() }
+        }
+
+        /// Allocates a sketch of a single hash.
+        pub fn from_hash(hash: u64) -> Self {
+            // Hashes of small types may be poorly distributed in their high bits, so we mix them first.
+            let mut hash = hash;
+            hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d049bb133111eb);
+            hash ^= hash >> 31;
+
+            let index = (hash >> (64 - PRECISION)) as u16;
+            // The rank is the position of the first set bit among the remaining bits, at most `65 - PRECISION`.
+            let rank = ((hash << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() + 1;
+            HyperLogLog { registers: vec![(index, rank as u8)] }
+        }
+
+        /// Estimates the number of distinct hashes in the sketch.
+        pub fn estimate(&self) -> u64 {
+            let registers = REGISTERS as f64;
+            let zeros = REGISTERS - self.registers.len();
+            let mut sum = zeros as f64;
+            for &(_, rank) in self.registers.iter() {
+                sum += 2f64.powi(-(rank as i32));
+            }
+            let alpha = 0.7213 / (1.0 + 1.079 / registers);
+            let estimate = alpha * registers * registers / sum;
+            // Small cardinalities are more accurately estimated from the number of empty registers.
+            if estimate <= 2.5 * registers && zeros > 0 {
+                (registers * (registers / zeros as f64).ln()).round() as u64
+            }
+            else {
+                estimate.round() as u64
+            }
+        }
+    }
+
+    impl Semigroup for HyperLogLog {
+        fn plus_equals(&mut self, rhs: &Self) {
+            if rhs.registers.is_empty() { return; }
+            let mut merged = Vec::with_capacity(self.registers.len() + rhs.registers.len());
+            let mut index1 = 0;
+            let mut index2 = 0;
+            while index1 < self.registers.len() && index2 < rhs.registers.len() {
+                let (register1, rank1) = self.registers[index1];
+                let (register2, rank2) = rhs.registers[index2];
+                if register1 < register2 {
+                    merged.push((register1, rank1));
+                    index1 += 1;
+                }
+                else if register1 > register2 {
+                    merged.push((register2, rank2));
+                    index2 += 1;
+                }
+                else {
+                    merged.push((register1, ::std::cmp::max(rank1, rank2)));
+                    index1 += 1;
+                    index2 += 1;
+                }
+            }
+            merged.extend_from_slice(&self.registers[index1..]);
+            merged.extend_from_slice(&rhs.registers[index2..]);
+            self.registers = merged;
+        }
+        fn is_zero(&self) -> bool {
+            self.registers.is_empty()
+        }
+    }
+
+    impl Monoid for HyperLogLog {
+        fn zero() -> Self {
+            Self::new()
+        }
+    }
+
+    macro_rules! multiply_implementation {
+        ($t:ty) => {
+            impl Multiply<$t> for HyperLogLog {
+                type Output = Self;
+                fn multiply(self, rhs: &$t) -> Self {
+                    if *rhs == 0 { Self::new() } else { self }
+                }
+            }
+        };
+    }
+
+    multiply_implementation!(u8);
+    multiply_implementation!(u16);
+    multiply_implementation!(u32);
+    multiply_implementation!(u64);
+    multiply_implementation!(u128);
+    multiply_implementation!(usize);
+}
+
+pub use self::extremes::{Min, Max, Bounded};
+mod extremes {
+
+    use super::{Semigroup, Monoid, Multiply};
+    use ::Data;
+
+    /// A type with a least and a greatest value, which are the zeros of `Max` and `Min` respectively.
+    pub trait Bounded : Data {
+        /// The least value of the type.
+        fn least() -> Self;
+        /// The greatest value of the type.
+        fn greatest() -> Self;
+    }
+
+    /// Implementations for types with `min_value` and `max_value`.
+    macro_rules! bounded_implementation {
+        ($t:ty) => {
+            impl Bounded for $t {
+                #[inline] fn least() -> Self { <$t>::min_value() }
+                #[inline] fn greatest() -> Self { <$t>::max_value() }
+            }
+        };
+    }
+
+    bounded_implementation!(i8);
+    bounded_implementation!(i16);
+    bounded_implementation!(i32);
+    bounded_implementation!(i64);
+    bounded_implementation!(i128);
+    bounded_implementation!(isize);
+    bounded_implementation!(u8);
+    bounded_implementation!(u16);
+    bounded_implementation!(u32);
+    bounded_implementation!(u64);
+    bounded_implementation!(u128);
+    bounded_implementation!(usize);
+
+    impl Bounded for char {
+        #[inline] fn least() -> Self { '\0' }
+        #[inline] fn greatest() -> Self { ::std::char::MAX }
+    }
+
+    impl Bounded for bool {
+        #[inline] fn least() -> Self { false }
+        #[inline] fn greatest() -> Self { true }
+    }
+
+    impl Bounded for () {
+        #[inline] fn least() -> Self { }
+        #[inline] fn greatest() -> Self { }
+    }
+
+    /// A difference that accumulates the least of its values.
+    ///
+    /// Addition is idempotent, and there is no negation, so records with this difference cannot be
+    /// retracted. The zero is the greatest value of `T`, which is the identity of the least value.
+    /// Multiplication by a positive unsigned integer leaves the value unchanged, and by zero produces zero.
+    #[derive(Abomonation, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Clone, Serialize, Deserialize, Hash)]
+    pub struct Min<T>(pub T);
+
+    /// A difference that accumulates the greatest of its values.
+    ///
+    /// Addition is idempotent, and there is no negation, so records with this difference cannot be
+    /// retracted. The zero is the least value of `T`, which is the identity of the greatest value.
+    /// Multiplication by a positive unsigned integer leaves the value unchanged, and by zero produces zero.
+    #[derive(Abomonation, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Clone, Serialize, Deserialize, Hash)]
+    pub struct Max<T>(pub T);
+
+    impl<T: Bounded> Semigroup for Min<T> {
+        fn plus_equals(&mut self, rhs: &Self) {
+            if rhs.0 < self.0 { self.0 = rhs.0.clone(); }
+        }
+        fn is_zero(&self) -> bool { self.0 == T::greatest() }
+    }
+
+    impl<T: Bounded> Semigroup for Max<T> {
+        fn plus_equals(&mut self, rhs: &Self) {
+            if rhs.0 > self.0 { self.0 = rhs.0.clone(); }
+        }
+        fn is_zero(&self) -> bool { self.0 == T::least() }
+    }
+
+    impl<T: Bounded> Monoid for Min<T> {
+        fn zero() -> Self { Min(T::greatest()) }
+    }
+
+    impl<T: Bounded> Monoid for Max<T> {
+        fn zero() -> Self { Max(T::least()) }
+    }
+
+    /// Implementations of multiplication by unsigned integers.
+    macro_rules! multiply_implementation {
+        ($t:ty) => {
+            impl<T: Bounded> Multiply<$t> for Min<T> {
+                type Output = Self;
+                fn multiply(self, rhs: &$t) -> Self {
+                    if *rhs == 0 { Self::zero() } else { self }
+                }
+            }
+
+            impl<T: Bounded> Multiply<$t> for Max<T> {
+                type Output = Self;
+                fn multiply(self, rhs: &$t) -> Self {
+                    if *rhs == 0 { Self::zero() } else { self }
+                }
+            }
+        };
+    }
+
+    multiply_implementation!(u8);
+    multiply_implementation!(u16);
+    multiply_implementation!(u32);
+    multiply_implementation!(u64);
+    multiply_implementation!(u128);
+    multiply_implementation!(usize);
+}
+
+pub use self::float::Sum;
+mod float {
+
+    use std::cmp::Ordering;
+    use std::hash::{Hash, Hasher};
+
+    use super::{Semigroup, Monoid, Abelian, Multiply};
+
+    /// A floating point difference accumulated with compensated summation.
+    ///
+    /// Floating point addition is not associative, and differential dataflow adds differences in an
+    /// arbitrary order, so naive sums of floats may differ between executions and may fail to cancel.
+    /// This type tracks the error of its accumulation (using Neumaier's variant of Kahan summation),
+    /// which makes its `value` substantially less sensitive to the order of addition. It is zero when
+    /// its compensated value is zero, even if the partial sum and compensation are not.
+    ///
+    /// Floats have no total order, and this type orders values by `total_cmp` of their components.
+    #[derive(Abomonation, Copy, Debug, Clone, Default, Serialize, Deserialize)]
+    pub struct Sum<T> {
+        sum: T,
+        compensation: T,
+    }
+
+    /// Implementations for floating point types.
+    macro_rules! float_implementation {
+        ($t:ty) => {
+            impl Sum<$t> {
+                /// Allocates a sum of `value`.
+                pub fn new(value: $t) -> Self {
+                    Sum { sum: value, compensation: 0.0 }
+                }
+                /// The compensated value of the sum.
+                pub fn value(&self) -> $t {
+                    self.sum + self.compensation
+                }
+            }
+
+            impl From<$t> for Sum<$t> {
+                fn from(value: $t) -> Self { Sum::new(value) }
+            }
+
+            impl PartialEq for Sum<$t> {
+                fn eq(&self, other: &Self) -> bool { self.cmp(other) == Ordering::Equal }
+            }
+            impl Eq for Sum<$t> { }
+            impl PartialOrd for Sum<$t> {
+                fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+            }
+            impl Ord for Sum<$t> {
+                fn cmp(&self, other: &Self) -> Ordering {
+                    self.sum.total_cmp(&other.sum).then_with(|| self.compensation.total_cmp(&other.compensation))
+                }
+            }
+            impl Hash for Sum<$t> {
+                fn hash<H: Hasher>(&self, state: &mut H) {
+                    self.sum.to_bits().hash(state);
+                    self.compensation.to_bits().hash(state);
+                }
+            }
+
+            impl Semigroup for Sum<$t> {
+                fn plus_equals(&mut self, rhs: &Self) {
+                    let sum = self.sum + rhs.sum;
+                    // Accumulate the low-order bits lost by the addition.
+                    if self.sum.abs() >= rhs.sum.abs() {
+                        self.compensation += (self.sum - sum) + rhs.sum;
+                    }
+                    else {
+                        self.compensation += (rhs.sum - sum) + self.sum;
+                    }
+                    self.compensation += rhs.compensation;
+                    self.sum = sum;
+                }
+                fn is_zero(&self) -> bool { self.value() == 0.0 }
+            }
+
+            impl Monoid for Sum<$t> {
+                fn zero() -> Self { Sum::new(0.0) }
+            }
+
+            impl Abelian for Sum<$t> {
+                fn negate(self) -> Self {
+                    Sum { sum: -self.sum, compensation: -self.compensation }
+                }
+            }
+
+            impl Multiply<Self> for Sum<$t> {
+                type Output = Self;
+                fn multiply(self, rhs: &Self) -> Self { Sum::new(self.value() * rhs.value()) }
+            }
+        };
+    }
+
+    float_implementation!(f32);
+    float_implementation!(f64);
+}
+
+pub use self::map::Map;
+// Map implementations
+mod map {
+
+    use std::collections::BTreeMap;
+
+    use super::{Semigroup, Monoid, Abelian, Multiply};
+    use ::Data;
+
+    /// A map from keys to differences, which accumulates the differences of each key.
+    ///
+    /// The map is a list of keys and their non-zero differences, sorted by key. Unlike `BTreeMap`, it
+    /// implements `Abomonation`, and so can be exchanged between workers, for example by `count_total`
+    /// and `threshold_semigroup`.
+    #[derive(Abomonation, Ord, PartialOrd, Eq, PartialEq, Debug, Clone, Serialize, Deserialize, Hash)]
+    pub struct Map<K, R> {
+        updates: Vec<(K, R)>,
+    }
+
+    impl<K: Data, R: Semigroup> Map<K, R> {
+        /// Allocates an empty map.
+        pub fn new() -> Self {
+            Map { updates: Vec::new() }
+        }
+        /// Allocates a map from `key` to `diff`, which is empty if `diff` is zero.
+        pub fn singleton(key: K, diff: R) -> Self {
+            let mut updates = Vec::new();
+            if !diff.is_zero() { updates.push((key, diff)); }
+            Map { updates }
+        }
+        /// The difference of `key`, if it is not zero.
+        pub fn get(&self, key: &K) -> Option<&R> {
+            self.updates
+                .binary_search_by(|x| x.0.cmp(key))
+                .ok()
+                .map(|index| &self.updates[index].1)
+        }
+        /// Iterates over keys and their non-zero differences, in order of keys.
+        pub fn iter(&self) -> ::std::slice::Iter<(K, R)> {
+            self.updates.iter()
+        }
+        /// The number of keys with non-zero differences.
+        pub fn len(&self) -> usize {
+            self.updates.len()
+        }
+        /// Indicates whether all keys have zero differences.
+        pub fn is_empty(&self) -> bool {
+            self.updates.is_empty()
+        }
+    }
+
+    impl<K: Data, R: Semigroup> Default for Map<K, R> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<K: Data, R: Semigroup> ::std::iter::FromIterator<(K, R)> for Map<K, R> {
+        fn from_iter<I: IntoIterator<Item=(K, R)>>(iter: I) -> Self {
+            let mut updates = iter.into_iter().collect::<Vec<_>>();
+            ::consolidation::consolidate(&mut updates);
+            Map { updates }
+        }
+    }
+
+    impl<K: Data, R: Semigroup> Semigroup for Map<K, R> {
+        fn plus_equals(&mut self, rhs: &Self) {
+            if rhs.updates.is_empty() { return; }
+            let mut merged = Vec::with_capacity(self.updates.len() + rhs.updates.len());
+            let mut rhs_iter = rhs.updates.iter().peekable();
+            for (key, mut diff) in self.updates.drain(..) {
+                while rhs_iter.peek().map(|x| x.0 < key).unwrap_or(false) {
+                    merged.push(rhs_iter.next().unwrap().clone());
+                }
+                if rhs_iter.peek().map(|x| x.0 == key).unwrap_or(false) {
+                    diff.plus_equals(&rhs_iter.next().unwrap().1);
+                    if diff.is_zero() { continue; }
+                }
+                merged.push((key, diff));
+            }
+            merged.extend(rhs_iter.cloned());
+            self.updates = merged;
+        }
+        fn is_zero(&self) -> bool {
+            self.updates.is_empty()
+        }
+    }
+
+    impl<K: Data, R: Semigroup> Monoid for Map<K, R> {
+        fn zero() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<K: Data, R: Abelian> Abelian for Map<K, R> {
+        fn negate(self) -> Self {
+            let updates = self.updates.into_iter().map(|(key, diff)| (key, diff.negate())).collect();
+            Map { updates }
+        }
+    }
+
+    impl<K: Data, T, R: Multiply<T>> Multiply<T> for Map<K, R> where <R as Multiply<T>>::Output: Semigroup {
+        type Output = Map<K, <R as Multiply<T>>::Output>;
+        fn multiply(self, rhs: &T) -> Self::Output {
+            let updates = self.updates
+                .into_iter()
+                .map(|(key, diff)| (key, diff.multiply(rhs)))
+                .filter(|x| !x.1.is_zero())
+                .collect();
+            Map { updates }
+        }
+    }
+
+    /// Maps accumulate the differences of each key, and omit keys whose differences are zero.
+    ///
+    /// As `BTreeMap` does not implement `Abomonation`, maps cannot be exchanged between workers, and
+    /// are only usable as differences where updates are not exchanged; `Map` can be exchanged.
+    impl<K: Data, R: Semigroup> Semigroup for BTreeMap<K, R> {
+        fn plus_equals(&mut self, rhs: &Self) {
+            for (key, update) in rhs.iter() {
+                let zero = match self.get_mut(key) {
+                    Some(value) => { value.plus_equals(update); value.is_zero() },
+                    None => { self.insert(key.clone(), update.clone()); update.is_zero() },
+                };
+                if zero { self.remove(key); }
+            }
+        }
+        fn is_zero(&self) -> bool {
+            self.values().all(|x| x.is_zero())
+        }
+    }
+
+    impl<K: Data, R: Semigroup> Monoid for BTreeMap<K, R> {
+        fn zero() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<K: Data, R: Abelian> Abelian for BTreeMap<K, R> {
+        fn negate(mut self) -> Self {
+            for update in self.values_mut() {
+                *update = update.clone().negate();
+            }
+            self
+        }
+    }
+
+    impl<K: Ord, T, R: Multiply<T>> Multiply<T> for BTreeMap<K, R> {
+        type Output = BTreeMap<K, <R as Multiply<T>>::Output>;
+        fn multiply(self, rhs: &T) -> Self::Output {
+            self.into_iter()
+                .map(|(key, x)| (key, x.multiply(rhs)))
+                .collect()
+        }
+    }
+}
+
 // Pair implementations.
 mod tuples {
 
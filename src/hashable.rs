@@ -36,3 +36,49 @@ impl<T: ::std::hash::Hash> Hashable for T {
         h.finish()
     }
 }
+
+/// Types whose `Ord` implementation first orders by the output of `hashed`.
+///
+/// For hash-ordered types, `x.hashed().into() < y.hashed().into()` must imply `x < y`. This
+/// allows a hash table whose entries are laid out by hash value to also present them in order,
+/// as the hashed trie layer does.
+pub trait HashOrdered: Ord+Hashable { }
+
+/// A wrapper that caches the hash of its contents, and orders by that hash before the contents.
+///
+/// The wrapper is `HashOrdered`, and it avoids re-hashing its item for exchange and placement.
+#[derive(Clone, Debug, Eq, PartialEq, Abomonation, Serialize, Deserialize)]
+pub struct HashableWrapper<T> {
+    hash: u64,
+    /// The item, for reference.
+    pub item: T,
+}
+
+impl<T: Hashable> From<T> for HashableWrapper<T> {
+    fn from(item: T) -> HashableWrapper<T> {
+        HashableWrapper {
+            hash: item.hashed().into(),
+            item,
+        }
+    }
+}
+
+impl<T: Ord> PartialOrd for HashableWrapper<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<::std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> Ord for HashableWrapper<T> {
+    fn cmp(&self, other: &Self) -> ::std::cmp::Ordering {
+        (self.hash, &self.item).cmp(&(other.hash, &other.item))
+    }
+}
+
+impl<T> Hashable for HashableWrapper<T> {
+    type Output = u64;
+    #[inline(always)]
+    fn hashed(&self) -> u64 { self.hash }
+}
+
+impl<T: Ord> HashOrdered for HashableWrapper<T> { }
@@ -281,6 +281,21 @@ impl<T: Timestamp+Clone, D: Data, R: Semigroup> InputSession<T, D, R> {
         }
     }
 
+    /// Flushes the session as `flush` does, unless the worker's arrangements exceed their memory budget.
+    ///
+    /// Returns `true` if the session was flushed. If it was not, updates remain buffered and the caller
+    /// should step the worker, allowing arrangements to compact, before trying again. Without a configured
+    /// memory budget this method is equivalent to `flush`.
+    pub fn try_flush(&mut self) -> bool {
+        if ::operators::arrange::budget::over_budget() {
+            false
+        }
+        else {
+            self.flush();
+            true
+        }
+    }
+
     /// Advances the logical time for future records.
     ///
     /// Importantly, this method does **not** immediately inform timely dataflow of the change. This happens only when
@@ -91,6 +91,7 @@ pub trait ExchangeData : timely::ExchangeData + Ord + Debug { }
 impl<T: timely::ExchangeData + Ord + Debug> ExchangeData for T { }
 
 extern crate fnv;
+extern crate memmap;
 extern crate timely;
 
 #[macro_use]
@@ -122,7 +123,19 @@ pub struct Config {
     /// cause these operators to reschedule themselves as long as their arrangemnt has not
     /// reached a compact representation, and each scheduling quantum they will perform
     /// compaction work as if `effort` records had been added to the arrangement.
-    pub idle_merge_effort: Option<isize>
+    pub idle_merge_effort: Option<isize>,
+    /// A number of bytes each worker's arrangements should not exceed.
+    ///
+    /// The default value of `None` imposes no budget. Setting the value to `Some(bytes)` will
+    /// cause arrangement operators to report the sizes of their traces, and to apply additional
+    /// compaction work while the total exceeds `bytes`.
+    ///
+    /// The budget does not itself slow inputs. Backpressure is opt-in: an input session holds back
+    /// its buffered updates while the worker is over budget only when flushed with
+    /// `InputSession::try_flush`. Calls to `flush`, and updates that fill the session's buffer, send
+    /// updates regardless of the budget. Sizes are as reported by `BatchReader::heap_size`,
+    /// which for most batch types does not include allocations owned by keys and values.
+    pub memory_budget: Option<usize>,
 }
 
 impl Config {
@@ -131,6 +144,11 @@ impl Config {
         self.idle_merge_effort = effort;
         self
     }
+    /// Assign a number of bytes that each worker's arrangements should not exceed.
+    pub fn memory_budget(mut self, bytes: Option<usize>) -> Self {
+        self.memory_budget = bytes;
+        self
+    }
 }
 
 /// Introduces differential options to a timely configuration.
@@ -138,4 +156,7 @@ pub fn configure(config: &mut timely::WorkerConfig, options: &Config) {
     if let Some(effort) = options.idle_merge_effort {
         config.set("differential/idle_merge_effort".to_string(), effort);
     }
+    if let Some(bytes) = options.memory_budget {
+        config.set("differential/memory_budget".to_string(), bytes);
+    }
 }
@@ -27,6 +27,8 @@ pub enum DifferentialEvent {
     Drop(DropEvent),
     /// A merge failed to complete in time.
     MergeShortfall(MergeShortfall),
+    /// A worker's arrangements exceeded their memory budget.
+    Budget(BudgetEvent),
     /// Trace sharing event.
     TraceShare(TraceShare),
 }
@@ -72,6 +74,10 @@ pub struct MergeEvent {
 impl From<MergeEvent> for DifferentialEvent { fn from(e: MergeEvent) -> Self { DifferentialEvent::Merge(e) } }
 
 /// A merge failed to complete in time.
+///
+/// Spines report this event when their merge effort policy falls behind, in which case `scale` is
+/// the largest level with a merge in progress, and `shortfall` is the number of updates in merges
+/// that remain in progress.
 #[derive(Debug, Clone, Abomonation, Ord, PartialOrd, Eq, PartialEq)]
 pub struct MergeShortfall {
     /// Operator identifer.
@@ -84,6 +90,19 @@ pub struct MergeShortfall {
 
 impl From<MergeShortfall> for DifferentialEvent { fn from(e: MergeShortfall) -> Self { DifferentialEvent::MergeShortfall(e) } }
 
+/// An arrangement was scheduled while its worker exceeded its memory budget.
+#[derive(Debug, Clone, Abomonation, Ord, PartialOrd, Eq, PartialEq)]
+pub struct BudgetEvent {
+    /// Operator identifier.
+    pub operator: usize,
+    /// The number of bytes the arrangement reported.
+    pub bytes: usize,
+    /// The number of bytes by which the worker exceeds its budget.
+    pub excess: usize,
+}
+
+impl From<BudgetEvent> for DifferentialEvent { fn from(e: BudgetEvent) -> Self { DifferentialEvent::Budget(e) } }
+
 /// Either the start or end of a merge event.
 #[derive(Debug, Clone, Abomonation, Ord, PartialOrd, Eq, PartialEq)]
 pub struct TraceShare {
@@ -514,15 +514,91 @@ where
         Tr: Trace+TraceReader<Key=K,Val=V,Time=G::Timestamp,R=R>+'static,
         Tr::Batch: Batch<K, V, G::Timestamp, R>,
         Tr::Cursor: Cursor<K, V, G::Timestamp, R>,
+    {
+        self.arrange_core_from::<P, <Tr::Batch as Batch<K, V, G::Timestamp, R>>::Batcher, Tr>(pact, name, Vec::new())
+    }
+}
+
+impl<G, K, V, R> Collection<G, (K, V), R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+Hashable,
+    V: ExchangeData,
+    R: Semigroup+ExchangeData,
+{
+    /// As `arrange_core`, but combines updates at each worker before they are exchanged if `combine` is set.
+    ///
+    /// See `Combine::combine` for the trade-offs of combining updates.
+    pub fn arrange_core_with_combine<P, Tr>(&self, pact: P, name: &str, combine: bool) -> Arranged<G, TraceAgent<Tr>>
+    where
+        P: ParallelizationContract<G::Timestamp, ((K,V),G::Timestamp,R)>,
+        Tr: Trace+TraceReader<Key=K,Val=V,Time=G::Timestamp,R=R>+'static,
+        Tr::Batch: Batch<K, V, G::Timestamp, R>,
+        Tr::Cursor: Cursor<K, V, G::Timestamp, R>,
+    {
+        use operators::consolidate::Combine;
+        if combine {
+            self.combine_named(&format!("Combine: {}", name))
+                .arrange_core(pact, name)
+        }
+        else {
+            self.arrange_core(pact, name)
+        }
+    }
+
+    /// Arranges a stream of `(Key, Val)` updates by `Key`, using a specified batcher.
+    ///
+    /// This method allows the batcher, which sorts and consolidates received updates, to be chosen
+    /// for each arrangement rather than by the batch type. For example, a `RadixBatcher` may be
+    /// substantially faster for keys with integer projections.
+    pub fn arrange_with_batcher_named<Ba, Tr>(&self, name: &str) -> Arranged<G, TraceAgent<Tr>>
+    where
+        Ba: Batcher<K, V, G::Timestamp, R, Tr::Batch>+'static,
+        Tr: Trace+TraceReader<Key=K,Val=V,Time=G::Timestamp,R=R>+'static,
+        Tr::Batch: Batch<K, V, G::Timestamp, R>,
+        Tr::Cursor: Cursor<K, V, G::Timestamp, R>,
+    {
+        let exchange = Exchange::new(move |update: &((K,V),G::Timestamp,R)| (update.0).0.hashed().into());
+        self.arrange_core_with_batcher::<_, Ba, Tr>(exchange, name)
+    }
+
+    /// Arranges a stream of `(Key, Val)` updates by `Key`, using a specified batcher.
+    ///
+    /// This method is as `arrange_core`, but uses `Ba` rather than the batcher of `Tr::Batch`.
+    pub fn arrange_core_with_batcher<P, Ba, Tr>(&self, pact: P, name: &str) -> Arranged<G, TraceAgent<Tr>>
+    where
+        P: ParallelizationContract<G::Timestamp, ((K,V),G::Timestamp,R)>,
+        Ba: Batcher<K, V, G::Timestamp, R, Tr::Batch>+'static,
+        Tr: Trace+TraceReader<Key=K,Val=V,Time=G::Timestamp,R=R>+'static,
+        Tr::Batch: Batch<K, V, G::Timestamp, R>,
+        Tr::Cursor: Cursor<K, V, G::Timestamp, R>,
+    {
+        self.arrange_core_from::<P, Ba, Tr>(pact, name, Vec::new())
+    }
+
+    /// Arranges a stream of `(Key, Val)` updates by `Key`, starting from a sequence of initial batches.
+    ///
+    /// The initial batches must form a sequence whose lower bound is the minimum time, and they are
+    /// both installed in the trace and sent to downstream consumers. Updates in the collection must be
+    /// at times in advance of the upper bound of the last initial batch, and they are only arranged
+    /// once the input frontier has passed this bound.
+    pub(crate) fn arrange_core_from<P, Ba, Tr>(&self, pact: P, name: &str, mut initial: Vec<Tr::Batch>) -> Arranged<G, TraceAgent<Tr>>
+    where
+        P: ParallelizationContract<G::Timestamp, ((K,V),G::Timestamp,R)>,
+        Ba: Batcher<K, V, G::Timestamp, R, Tr::Batch>+'static,
+        Tr: Trace+TraceReader<Key=K,Val=V,Time=G::Timestamp,R=R>+'static,
+        Tr::Batch: Batch<K, V, G::Timestamp, R>,
+        Tr::Cursor: Cursor<K, V, G::Timestamp, R>,
     {
         // The `Arrange` operator is tasked with reacting to an advancing input
         // frontier by producing the sequence of batches whose lower and upper
         // bounds are those frontiers, containing updates at times greater or
         // equal to lower and not greater or equal to upper.
         //
-        // The operator uses its batch type's `Batcher`, which accepts update
-        // triples and responds to requests to "seal" batches (presented as new
-        // upper frontiers).
+        // The operator uses a `Batcher` (by default that of its batch type),
+        // which accepts update triples and responds to requests to "seal"
+        // batches (presented as new upper frontiers).
         //
         // Each sealed batch is presented to the trace, and if at all possible
         // transmitted along the outgoing channel. Empty batches may not have
@@ -537,7 +613,7 @@ where
 
             let reader = &mut reader;
 
-            self.inner.unary_frontier(pact, name, move |_capability, info| {
+            self.inner.unary_frontier(pact, name, move |capability, info| {
 
                 // Acquire a logger for arrange events.
                 let logger = {
@@ -547,7 +623,7 @@ where
                 };
 
                 // Where we will deposit received updates, and from which we extract batches.
-                let mut batcher = <Tr::Batch as Batch<K,V,G::Timestamp,R>>::Batcher::new();
+                let mut batcher = Ba::new();
 
                 // Capabilities for the lower envelope of updates in `batcher`.
                 let mut capabilities = Antichain::<Capability<G::Timestamp>>::new();
@@ -563,9 +639,33 @@ where
                     (None, None)
                 };
 
+                // Report the size of the trace against the worker's memory budget, if one is configured.
+                let operator = info.global_id;
+                let mut budget_report =
+                if let Some(limit) = self.inner.scope().config().get::<usize>("differential/memory_budget").cloned() {
+                    super::budget::set_limit(Some(limit));
+                    Some(super::budget::BudgetReport::new(operator))
+                }
+                else {
+                    None
+                };
+                let budget_logger = logger.clone();
+
                 let empty_trace = Tr::new(info.clone(), logger.clone(), activator);
                 let (reader_local, mut writer) = TraceAgent::new(empty_trace, info, logger);
 
+                // Install the initial batches, and advance the batcher to their upper bound.
+                let mut initial_upper = Antichain::from_elem(<G::Timestamp as Timestamp>::minimum());
+                for batch in initial.iter() {
+                    writer.insert(batch.clone(), None);
+                    initial_upper.clone_from(batch.upper());
+                }
+                if !initial.is_empty() {
+                    let _batch = batcher.seal(initial_upper.clone());
+                }
+                // Retain the initial capability only if there are initial batches to send.
+                let mut initial_capability = if initial.is_empty() { None } else { Some(capability) };
+
                 *reader = Some(reader_local);
 
                 // Initialize to the minimal input frontier.
@@ -573,6 +673,14 @@ where
 
                 move |input, output| {
 
+                    // Send the initial batches to downstream consumers, as a new listener to the trace would see them.
+                    if let Some(capability) = initial_capability.take() {
+                        let mut session = output.session(&capability);
+                        for batch in initial.drain(..) {
+                            session.give(batch);
+                        }
+                    }
+
                     // As we receive data, we need to (i) stash the data and (ii) keep *enough* capabilities.
                     // We don't have to keep all capabilities, but we need to be able to form output messages
                     // when we realize that time intervals are complete.
@@ -592,8 +700,11 @@ where
                     assert!(input.frontier().frontier().iter().all(|t1| input_frontier.iter().any(|t2: &G::Timestamp| t2.less_equal(t1))));
 
                     // Test to see if strict progress has occurred (any of the old frontier less equal
-                    // to the new frontier).
-                    let progress = input_frontier.iter().any(|t2| !input.frontier().less_equal(t2));
+                    // to the new frontier). We cannot make progress until the input frontier has passed
+                    // the upper bound of any initial batches.
+                    let progress =
+                    input_frontier.iter().any(|t2| !input.frontier().less_equal(t2)) &&
+                    input.frontier().frontier().iter().all(|t1| initial_upper.less_equal(t1));
 
                     if progress {
 
@@ -670,6 +781,24 @@ where
                     if let Some(mut fuel) = effort.clone() {
                         writer.exert(&mut fuel);
                     }
+
+                    if let Some(report) = budget_report.as_mut() {
+                        let bytes = writer.heap_size();
+                        report.update(bytes);
+                        if let Some(excess) = super::budget::excess() {
+                            // Merging may consolidate updates and release the allocations of merged batches,
+                            // so we apply effort as if we had received updates of the size of the excess.
+                            // Updates may be zero-sized, in which case each byte counts as an update.
+                            let update_size = ::std::cmp::max(1, ::std::mem::size_of::<((K,V),G::Timestamp,R)>());
+                            let mut fuel = ::std::cmp::max(1, excess / update_size) as isize;
+                            writer.exert(&mut fuel);
+                            budget_logger.as_ref().map(|l| l.log(::logging::BudgetEvent {
+                                operator,
+                                bytes,
+                                excess,
+                            }));
+                        }
+                    }
                 }
             })
         };
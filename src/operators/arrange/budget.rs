@@ -0,0 +1,81 @@
+//! Per-worker memory budgets for arrangements.
+//!
+//! When a memory budget is configured (see `Config::memory_budget`), each arrangement operator reports
+//! the heap size of its trace whenever it is scheduled. Reports are collected for each worker, and as
+//! timely dataflow workers each run on their own thread, they are held in thread-local state.
+//!
+//! Once the total of the reports exceeds the budget, arrangement operators apply additional merge effort,
+//! which can consolidate updates and release the allocations of merged batches, and log `BudgetEvent`s
+//! describing how far the worker is over budget. Inputs are not held back unless they opt in: input sessions
+//! can hold back their buffered updates using `InputSession::try_flush`, which declines to flush while the
+//! worker is over budget, whereas `InputSession::flush` always flushes.
+//!
+//! Sizes are as reported by `BatchReader::heap_size`, which for most batch types does not include the
+//! allocations owned by keys and values, such as the contents of `String` or `Vec` data. Arrangements of such
+//! data may exceed the budget by far more than reported; the columnar batches report these allocations.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+#[derive(Default)]
+struct Usage {
+    /// The configured budget, in bytes.
+    limit: Option<usize>,
+    /// Reported heap sizes, in bytes, indexed by operator identifier.
+    bytes: HashMap<usize, usize>,
+}
+
+thread_local! {
+    static USAGE: RefCell<Usage> = RefCell::new(Usage::default());
+}
+
+/// Sets the memory budget for arrangements of the current worker.
+pub fn set_limit(limit: Option<usize>) {
+    USAGE.with(|usage| usage.borrow_mut().limit = limit);
+}
+
+/// The memory budget for arrangements of the current worker, if any.
+pub fn limit() -> Option<usize> {
+    USAGE.with(|usage| usage.borrow().limit)
+}
+
+/// The total number of bytes reported by arrangements of the current worker.
+pub fn usage() -> usize {
+    USAGE.with(|usage| usage.borrow().bytes.values().sum())
+}
+
+/// The number of bytes by which the current worker exceeds its budget, if it does.
+pub fn excess() -> Option<usize> {
+    USAGE.with(|usage| {
+        let usage = usage.borrow();
+        let total: usize = usage.bytes.values().sum();
+        usage.limit.and_then(|limit| if total > limit { Some(total - limit) } else { None })
+    })
+}
+
+/// True iff the current worker exceeds its budget.
+pub fn over_budget() -> bool {
+    excess().is_some()
+}
+
+/// An arrangement's report of its heap size, which is withdrawn when dropped.
+pub struct BudgetReport {
+    operator: usize,
+}
+
+impl BudgetReport {
+    /// Allocates a new report for the arrangement operator with global identifier `operator`.
+    pub fn new(operator: usize) -> Self {
+        BudgetReport { operator }
+    }
+    /// Updates the number of bytes held by the arrangement.
+    pub fn update(&mut self, bytes: usize) {
+        USAGE.with(|usage| usage.borrow_mut().bytes.insert(self.operator, bytes));
+    }
+}
+
+impl Drop for BudgetReport {
+    fn drop(&mut self) {
+        USAGE.with(|usage| usage.borrow_mut().bytes.remove(&self.operator));
+    }
+}
@@ -0,0 +1,210 @@
+//! Support for writing arrangements to disk, and restoring them.
+//!
+//! A checkpoint records the batches of a trace, as enumerated by `map_batches`, along with
+//! each batch's lower, upper, and since frontiers. Each batch is written to its own file in
+//! the abomonation encoding, followed by a manifest recording the number of batches and the
+//! logical compaction frontier of the trace. The manifest is written last, and a directory
+//! without a manifest is not a valid checkpoint.
+//!
+//! Arrangements are partitioned among workers, and each worker should checkpoint its trace
+//! to a distinct directory (for example, one named by the worker index). Restoring requires
+//! the same number of workers, so that keys are routed to the worker holding their batches.
+//!
+//! # Example
+//!
+//! ```no_run
+//! extern crate timely;
+//! extern crate differential_dataflow;
+//!
+//! use differential_dataflow::input::Input;
+//! use differential_dataflow::operators::arrange::ArrangeByKey;
+//! use differential_dataflow::operators::arrange::checkpoint::{checkpoint, arrange_from_checkpoint};
+//! use differential_dataflow::trace::implementations::ord::OrdValSpine;
+//!
+//! fn main() -> std::io::Result<()> {
+//!     timely::execute_directly(|worker| -> std::io::Result<()> {
+//!
+//!         let directory = format!("checkpoint/worker-{}", worker.index());
+//!
+//!         // arrange some updates, and record the contents of the arrangement.
+//!         let (mut input, mut trace) = worker.dataflow::<u64,_,_>(|scope| {
+//!             let (input, updates) = scope.new_collection::<(u64, u64), isize>();
+//!             (input, updates.arrange_by_key().trace)
+//!         });
+//!         input.insert((0, 1));
+//!         input.close();
+//!         while worker.step() { }
+//!         checkpoint(&mut trace, &directory)?;
+//!
+//!         // ... later, in a new dataflow, continue from the recorded state.
+//!         worker.dataflow::<u64,_,_>(|scope| -> std::io::Result<()> {
+//!             let (_input, new_updates) = scope.new_collection::<(u64, u64), isize>();
+//!             let arranged = arrange_from_checkpoint::<_,_,_,_,OrdValSpine<_,_,_,_>,_>(&new_updates, &directory, "Restored")?;
+//!             arranged.as_collection(|key, val| (*key, *val)).inspect(|x| println!("{:?}", x));
+//!             Ok(())
+//!         })
+//!     })
+//! }
+//! ```
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use timely::dataflow::Scope;
+use timely::dataflow::channels::pact::Exchange;
+use timely::progress::Antichain;
+
+use abomonation::Abomonation;
+
+use ::{Collection, ExchangeData, Hashable};
+use ::difference::Semigroup;
+use lattice::Lattice;
+use trace::{Trace, TraceReader, Batch, BatchReader, Builder, Cursor};
+
+use super::TraceAgent;
+use super::arrangement::Arranged;
+
+/// The contents of a batch file: lower, upper, and since frontiers, and the updates themselves.
+type BatchRecord<K, V, T, R> = (Vec<T>, Vec<T>, Vec<T>, Vec<((K, V), T, R)>);
+
+/// The contents of the manifest: the number of batches and the logical compaction frontier.
+type ManifestRecord<T> = (u64, Vec<T>);
+
+fn batch_path(directory: &Path, index: usize) -> ::std::path::PathBuf {
+    directory.join(format!("batch-{}.abom", index))
+}
+
+fn manifest_path(directory: &Path) -> ::std::path::PathBuf {
+    directory.join("manifest.abom")
+}
+
+/// Writes `record` to the file at `path`.
+fn write_record<T: Abomonation>(path: &Path, record: &T) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    unsafe { abomonation::encode(record, &mut writer)?; }
+    writer.flush()?;
+    writer.get_ref().sync_all()
+}
+
+/// Reads a record of type `T` from the file at `path`.
+fn read_record<T: Abomonation+Clone>(path: &Path) -> io::Result<T> {
+    let mut bytes = fs::read(path)?;
+    unsafe { abomonation::decode::<T>(&mut bytes) }
+        .map(|(record, _remaining)| record.clone())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("failed to decode {:?}", path)))
+}
+
+/// Writes the batches of `trace` to `directory`, creating the directory if needed.
+///
+/// The checkpoint reflects the batches the trace presents at the moment of the call, which
+/// cover the times not greater or equal to its upper frontier. Any existing checkpoint in
+/// `directory` is overwritten, starting with its manifest.
+pub fn checkpoint<Tr, P>(trace: &mut Tr, directory: P) -> io::Result<()>
+where
+    Tr: TraceReader,
+    Tr::Key: Abomonation+Clone,
+    Tr::Val: Abomonation+Clone,
+    Tr::Time: Abomonation+Clone,
+    Tr::R: Abomonation+Clone,
+    P: AsRef<Path>,
+{
+    let directory = directory.as_ref();
+    fs::create_dir_all(directory)?;
+
+    // Invalidate any existing checkpoint before writing new batches.
+    if let Err(error) = fs::remove_file(manifest_path(directory)) {
+        if error.kind() != io::ErrorKind::NotFound {
+            return Err(error);
+        }
+    }
+
+    let mut count = 0;
+    let mut result = Ok(());
+    trace.map_batches(|batch| {
+        if result.is_ok() {
+            let description = batch.description();
+            let mut updates = Vec::with_capacity(batch.len());
+            let mut cursor = batch.cursor();
+            while cursor.key_valid(batch) {
+                while cursor.val_valid(batch) {
+                    let key = cursor.key(batch);
+                    let val = cursor.val(batch);
+                    cursor.map_times(batch, |time, diff| {
+                        updates.push(((key.clone(), val.clone()), time.clone(), diff.clone()));
+                    });
+                    cursor.step_val(batch);
+                }
+                cursor.step_key(batch);
+            }
+            let record: BatchRecord<Tr::Key, Tr::Val, Tr::Time, Tr::R> = (
+                description.lower().elements().to_vec(),
+                description.upper().elements().to_vec(),
+                description.since().elements().to_vec(),
+                updates,
+            );
+            result = write_record(&batch_path(directory, count), &record);
+            count += 1;
+        }
+    });
+    result?;
+
+    let manifest: ManifestRecord<Tr::Time> = (count as u64, trace.get_logical_compaction().to_vec());
+    write_record(&manifest_path(directory), &manifest)
+}
+
+/// Reads the batches of a checkpoint from `directory`, and the logical compaction frontier of its trace.
+///
+/// The batches are returned in the order they were written, which is the order in which they
+/// should be inserted into a trace.
+pub fn restore<K, V, T, R, B, P>(directory: P) -> io::Result<(Vec<B>, Antichain<T>)>
+where
+    K: Abomonation+Clone,
+    V: Abomonation+Clone,
+    T: Abomonation+Clone+Lattice+Ord,
+    R: Abomonation+Clone,
+    B: Batch<K, V, T, R>,
+    P: AsRef<Path>,
+{
+    let directory = directory.as_ref();
+    let (count, compaction): ManifestRecord<T> = read_record(&manifest_path(directory))?;
+
+    let mut batches = Vec::with_capacity(count as usize);
+    for index in 0 .. count as usize {
+        let (lower, upper, since, updates): BatchRecord<K, V, T, R> = read_record(&batch_path(directory, index))?;
+        let mut builder = B::Builder::with_capacity(updates.len());
+        for ((key, val), time, diff) in updates {
+            builder.push((key, val, time, diff));
+        }
+        batches.push(builder.done(Antichain::from(lower), Antichain::from(upper), Antichain::from(since)));
+    }
+
+    Ok((batches, Antichain::from(compaction)))
+}
+
+/// Arranges `collection` starting from the checkpoint in `directory`.
+///
+/// The resulting arrangement contains the batches of the checkpoint, followed by batches of updates
+/// from `collection`, whose times must be in advance of the upper frontier of the checkpoint. Its
+/// trace's logical compaction frontier is initially that of the checkpointed trace.
+pub fn arrange_from_checkpoint<G, K, V, R, Tr, P>(collection: &Collection<G, (K, V), R>, directory: P, name: &str) -> io::Result<Arranged<G, TraceAgent<Tr>>>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord+Abomonation,
+    K: ExchangeData+Hashable+Abomonation,
+    V: ExchangeData+Abomonation,
+    R: Semigroup+ExchangeData+Abomonation,
+    Tr: Trace+TraceReader<Key=K,Val=V,Time=G::Timestamp,R=R>+'static,
+    Tr::Batch: Batch<K, V, G::Timestamp, R>,
+    Tr::Cursor: Cursor<K, V, G::Timestamp, R>,
+    P: AsRef<Path>,
+{
+    let (batches, compaction) = restore::<K, V, G::Timestamp, R, Tr::Batch, _>(directory)?;
+
+    let exchange = Exchange::new(move |update: &((K,V),G::Timestamp,R)| (update.0).0.hashed().into());
+    let mut arranged = collection.arrange_core_from::<_, <Tr::Batch as Batch<K, V, G::Timestamp, R>>::Batcher, Tr>(exchange, name, batches);
+
+    arranged.trace.set_logical_compaction(compaction.borrow());
+
+    Ok(arranged)
+}
@@ -68,6 +68,8 @@ pub mod agent;
 pub mod arrangement;
 
 pub mod upsert;
+pub mod checkpoint;
+pub mod budget;
 
 pub use self::writer::TraceWriter;
 pub use self::agent::{TraceAgent, ShutdownButton};
@@ -7,7 +7,7 @@ use std::rc::{Rc, Weak};
 use std::cell::RefCell;
 
 use lattice::Lattice;
-use trace::{Trace, Batch, BatchReader};
+use trace::{Trace, TraceReader, Batch, BatchReader};
 use timely::progress::{Antichain, Timestamp};
 
 use trace::wrappers::rc::TraceBox;
@@ -58,6 +58,15 @@ where
         }
     }
 
+    /// Reports the number of bytes allocated by batches of the trace, if it still exists.
+    pub fn heap_size(&self) -> usize {
+        let mut bytes = 0;
+        if let Some(trace) = self.trace.upgrade() {
+            trace.borrow().trace.map_batches(|batch| batch.heap_size(&mut |_length, capacity| bytes += capacity));
+        }
+        bytes
+    }
+
     /// Advances the trace by `batch`.
     ///
     /// The `hint` argument is either `None` in the case of an empty batch,
@@ -0,0 +1,187 @@
+//! Match records to the version of a key current as of their timestamp.
+//!
+//! The `as_of_join` operators act on records `((key, ts), val)` whose keys include a timestamp, for
+//! example events, and an arrangement of versions `((key, ts), val)` of some other data, for example
+//! the rows of a dimension table valid from `ts`. Each record is matched with the version of its key
+//! with the greatest timestamp not greater than its own, so that records are enriched with the data
+//! as it was at their timestamp.
+//!
+//! The operators are maintained incrementally as either input changes. The versions of each key are
+//! first organized into intervals of timestamps, which is proportional in cost to the number of
+//! versions of the key, and records are then matched with the interval containing their timestamp.
+//! Each record seeks directly to the interval with the greatest start not after its timestamp, and
+//! changes to intervals are applied once the records before them are known, so that each record is
+//! matched against a single current interval. A new version of a key re-reads the records of the key
+//! with timestamps at least its timestamp, so the operators are best suited to versions that change
+//! less often than the records they enrich. The operators require totally ordered times.
+
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use timely::order::TotalOrder;
+use timely::dataflow::Scope;
+use timely::dataflow::channels::pact::Exchange;
+
+use ::{Data, ExchangeData, Collection, Hashable};
+use ::difference::{Semigroup, Multiply};
+use lattice::Lattice;
+use operators::Reduce;
+use operators::arrange::{Arranged, Arrange, ArrangeByKey};
+use operators::band::range_join;
+use operators::join::JoinCore;
+use trace::{BatchReader, Cursor, TraceReader};
+use trace::implementations::ord::OrdValSpine as DefaultValTrace;
+
+/// Timestamps within keys, ordered from the greatest to the least, and preceded by `Descending(None)`.
+#[derive(Abomonation, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct Descending<Ts>(Option<Ts>);
+
+impl<Ts: Ord> PartialOrd for Descending<Ts> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+impl<Ts: Ord> Ord for Descending<Ts> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (&self.0, &other.0) {
+            (&None, &None) => Ordering::Equal,
+            (&None, &Some(_)) => Ordering::Less,
+            (&Some(_), &None) => Ordering::Greater,
+            (&Some(ref ts1), &Some(ref ts2)) => ts2.cmp(ts1),
+        }
+    }
+}
+
+/// Extension trait for the `as_of_join` differential dataflow method.
+pub trait AsOfJoin<G: Scope, K: Data, Ts: Data, V: Data, R: Semigroup> where G::Timestamp: Lattice+TotalOrder+Ord {
+    /// Matches each record `((key, ts), val)` with the version of `key` in `other` with the greatest timestamp not greater than `ts`.
+    ///
+    /// Records are yielded as `((key, ts), (val, val2))` with their frequencies multiplied, where `val2`
+    /// is a value of the matched version. Records without a version at or before their timestamp are
+    /// not matched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate timely;
+    /// extern crate differential_dataflow;
+    ///
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::arrange::ArrangeByKey;
+    /// use differential_dataflow::operators::asof::AsOfJoin;
+    ///
+    /// fn main() {
+    ///     ::timely::example(|scope| {
+    ///
+    ///         // trades and rates of currencies `0` and `1` at various times.
+    ///         let trades = scope.new_collection_from(vec![((0, 5u64), 100), ((0, 12), 200), ((1, 3), 50)]).1;
+    ///         let rates = scope.new_collection_from(vec![((0, 0u64), 110), ((0, 10), 120), ((1, 4), 130)]).1;
+    ///         let z = scope.new_collection_from(vec![((0, 5), (100, 110)), ((0, 12), (200, 120))]).1;
+    ///
+    ///         // each trade with the rate of its currency at the time of the trade.
+    ///         trades.as_of_join(&rates.arrange_by_key())
+    ///               .assert_eq(&z);
+    ///     });
+    /// }
+    /// ```
+    fn as_of_join<Tr2>(&self, other: &Arranged<G, Tr2>) -> Collection<G, ((K, Ts), (V, Tr2::Val)), <R as Multiply<Tr2::R>>::Output>
+    where
+        Tr2: TraceReader<Key=(K, Ts), Time=G::Timestamp>+Clone+'static,
+        Tr2::Val: Data,
+        Tr2::R: ExchangeData+Semigroup,
+        Tr2::Batch: BatchReader<(K, Ts), Tr2::Val, G::Timestamp, Tr2::R>+'static,
+        Tr2::Cursor: Cursor<(K, Ts), Tr2::Val, G::Timestamp, Tr2::R>+'static,
+        R: Multiply<Tr2::R>,
+        <R as Multiply<Tr2::R>>::Output: Semigroup,
+    {
+        self.as_of_join_core(other, |key, val1, _key2, val2| Some((key.clone(), (val1.clone(), val2.clone()))))
+    }
+
+    /// Matches each record with the version of its key in `other` current as of its timestamp, and applies `result` to the pair.
+    ///
+    /// The function `result` is applied to the key and value of the record, and the key and value of
+    /// the matched version.
+    fn as_of_join_core<Tr2, I, L>(&self, other: &Arranged<G, Tr2>, result: L) -> Collection<G, I::Item, <R as Multiply<Tr2::R>>::Output>
+    where
+        Tr2: TraceReader<Key=(K, Ts), Time=G::Timestamp>+Clone+'static,
+        Tr2::Val: Data,
+        Tr2::R: ExchangeData+Semigroup,
+        Tr2::Batch: BatchReader<(K, Ts), Tr2::Val, G::Timestamp, Tr2::R>+'static,
+        Tr2::Cursor: Cursor<(K, Ts), Tr2::Val, G::Timestamp, Tr2::R>+'static,
+        R: Multiply<Tr2::R>,
+        <R as Multiply<Tr2::R>>::Output: Semigroup,
+        I: IntoIterator,
+        I::Item: Data,
+        L: FnMut(&(K, Ts), &V, &(K, Ts), &Tr2::Val)->I+'static;
+}
+
+impl<G, K, Ts, V, R> AsOfJoin<G, K, Ts, V, R> for Collection<G, ((K, Ts), V), R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+TotalOrder+Ord+Debug,
+    K: ExchangeData+Hash,
+    Ts: ExchangeData+Hash,
+    V: ExchangeData,
+    R: ExchangeData+Semigroup+Multiply<isize, Output=R>,
+{
+    fn as_of_join_core<Tr2, I, L>(&self, other: &Arranged<G, Tr2>, mut result: L) -> Collection<G, I::Item, <R as Multiply<Tr2::R>>::Output>
+    where
+        Tr2: TraceReader<Key=(K, Ts), Time=G::Timestamp>+Clone+'static,
+        Tr2::Val: Data,
+        Tr2::R: ExchangeData+Semigroup,
+        Tr2::Batch: BatchReader<(K, Ts), Tr2::Val, G::Timestamp, Tr2::R>+'static,
+        Tr2::Cursor: Cursor<(K, Ts), Tr2::Val, G::Timestamp, Tr2::R>+'static,
+        R: Multiply<Tr2::R>,
+        <R as Multiply<Tr2::R>>::Output: Semigroup,
+        I: IntoIterator,
+        I::Item: Data,
+        L: FnMut(&(K, Ts), &V, &(K, Ts), &Tr2::Val)->I+'static,
+    {
+        // Timestamps are ordered from greatest to least within each key, so that records seek to the
+        // interval with the greatest start not after their timestamp. Records and intervals are
+        // partitioned by `key` alone, so that they meet at the same worker.
+        let intervals = other
+            .as_collection(|key, _val| key.clone())
+            .reduce_named("AsOfVersions", |_key, input, output| {
+                for index in 0 .. input.len() {
+                    let end = input.get(index + 1).map(|&(ts, _)| ts.clone());
+                    output.push(((input[index].0.clone(), end), 1isize));
+                }
+            })
+            .map(|(key, (start, end))| ((key, Descending(Some(start))), end))
+            .arrange_core::<_, DefaultValTrace<_, _, _, isize>>(
+                Exchange::new(|update: &(((K, Descending<Ts>), Option<Ts>), G::Timestamp, isize)| (((update.0).0).0).hashed().into()),
+                "Arrange: AsOfVersions",
+            );
+
+        let records = self
+            .map(|((key, ts), val)| ((key, Descending(Some(ts))), val))
+            .arrange_core::<_, DefaultValTrace<_, _, _, R>>(
+                Exchange::new(|update: &(((K, Descending<Ts>), V), G::Timestamp, R)| (((update.0).0).0).hashed().into()),
+                "Arrange: AsOfJoin",
+            );
+
+        // Records match the first interval of their key with updates starting at or before their
+        // timestamp, as the current intervals of a key are disjoint, and intervals match the records of
+        // their key with timestamps at least their start, from which those records before the end of
+        // the interval are retained.
+        range_join(
+            &records,
+            &intervals,
+            |record| record.clone(),
+            |record, start| start.0 == record.0,
+            |&(ref key, _)| (key.clone(), Descending(None)),
+            |start, record| record <= start,
+            true,
+            |&(ref key, ref ts), val, &(_, ref start), end| {
+                let ts = ts.0.as_ref().expect("records have timestamps");
+                let start = start.0.as_ref().expect("intervals have starts");
+                if end.as_ref().map(|end| ts < end).unwrap_or(true) {
+                    Some(((key.clone(), start.clone()), ((key.clone(), ts.clone()), val.clone())))
+                }
+                else { None }
+            },
+        )
+        .arrange_by_key_named("Arrange: AsOfMatches")
+        .join_core(other, move |key2, &(ref key1, ref val1), val2| result(key1, val1, key2, val2))
+    }
+}
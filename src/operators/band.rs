@@ -0,0 +1,511 @@
+//! Match pairs of records whose keys are within a band of one another.
+//!
+//! The `band_join` operators match records `(key1, val1)` and `(key2, val2)` whenever `key2` lies in
+//! the interval `[key1 - lo, key1 + hi]`, for example readings within some seconds of an event. As
+//! matched keys need not be equal, they are not co-located by hashing. Instead, the first input is
+//! broadcast to and arranged at every worker, and each worker matches it against its part of the
+//! second input, whose arrangement is walked in key order with `seek_key`. The first input should be
+//! the smaller of the two.
+//!
+//! Like `join_core`, the operator responds to batches from either input by joining them against the
+//! accepted contents of the other input's trace, and so maintains its output incrementally as either
+//! input changes.
+
+use std::fmt::Debug;
+use std::collections::VecDeque;
+
+use timely::order::PartialOrder;
+use timely::progress::Timestamp;
+use timely::progress::frontier::Antichain;
+use timely::dataflow::Scope;
+use timely::dataflow::operators::generic::{Operator, OutputHandle};
+use timely::dataflow::operators::{Broadcast, Capability};
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::channels::pushers::tee::Tee;
+
+use ::{Data, ExchangeData, Collection, AsCollection};
+use ::difference::{Semigroup, Multiply};
+use lattice::Lattice;
+use operators::arrange::{Arranged, Arrange, ArrangeByKey};
+use operators::join::JoinThinker;
+use trace::{BatchReader, Cursor, TraceReader};
+use trace::implementations::ord::OrdValSpine as DefaultValTrace;
+
+/// Keys that can bound a band of keys.
+pub trait BandKey : Ord+Clone {
+    /// The key `width` less than `self`, or the least key if there is no such key.
+    fn band_sub(&self, width: &Self) -> Self;
+    /// The key `width` greater than `self`, or the greatest key if there is no such key.
+    fn band_add(&self, width: &Self) -> Self;
+}
+
+/// Implementation for built-in integers, which saturate at their bounds.
+macro_rules! band_key_implementation {
+    ($t:ty) => {
+        impl BandKey for $t {
+            #[inline] fn band_sub(&self, width: &Self) -> Self { self.saturating_sub(*width) }
+            #[inline] fn band_add(&self, width: &Self) -> Self { self.saturating_add(*width) }
+        }
+    };
+}
+
+band_key_implementation!(u8);
+band_key_implementation!(u16);
+band_key_implementation!(u32);
+band_key_implementation!(u64);
+band_key_implementation!(u128);
+band_key_implementation!(usize);
+band_key_implementation!(i8);
+band_key_implementation!(i16);
+band_key_implementation!(i32);
+band_key_implementation!(i64);
+band_key_implementation!(i128);
+band_key_implementation!(isize);
+
+/// Band join implementations for `(key,val)` data.
+pub trait BandJoin<G: Scope, K: Data, V: Data, R: Semigroup> {
+    /// Matches pairs `(key1,val1)` and `(key2,val2)` where `key2` is at least `key1 - lo` and at most `key1 + hi`.
+    ///
+    /// The input collection is broadcast to all workers, and should be the smaller of the two inputs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate timely;
+    /// extern crate differential_dataflow;
+    ///
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::band::BandJoin;
+    ///
+    /// fn main() {
+    ///     ::timely::example(|scope| {
+    ///
+    ///         let events = scope.new_collection_from(vec![(10u64, 'a'), (20, 'b')]).1;
+    ///         let readings = scope.new_collection_from(vec![(6u64, 0), (12, 1), (26, 2)]).1;
+    ///         let z = scope.new_collection_from(vec![((10, 'a'), (6, 0)), ((10, 'a'), (12, 1))]).1;
+    ///
+    ///         // match readings from five before to five after each event.
+    ///         events.band_join(&readings, 5, 5)
+    ///               .assert_eq(&z);
+    ///     });
+    /// }
+    /// ```
+    fn band_join<V2, R2>(&self, other: &Collection<G, (K, V2), R2>, lo: K, hi: K) -> Collection<G, ((K, V), (K, V2)), <R as Multiply<R2>>::Output>
+    where
+        K: ExchangeData,
+        V2: ExchangeData,
+        R2: ExchangeData+Semigroup,
+        R: Multiply<R2>,
+        <R as Multiply<R2>>::Output: Semigroup;
+
+    /// Matches records of the input with records of an arranged collection whose keys are at least `key - lo` and at most `key + hi`.
+    ///
+    /// The function `result` is applied to each matched pair of keys and values, and the input collection
+    /// is broadcast to all workers.
+    fn band_join_core<Tr2, I, L>(&self, other: &Arranged<G, Tr2>, lo: K, hi: K, result: L) -> Collection<G, I::Item, <R as Multiply<Tr2::R>>::Output>
+    where
+        Tr2: TraceReader<Key=K, Time=G::Timestamp>+Clone+'static,
+        Tr2::Val: Ord+Clone+Debug+'static,
+        Tr2::R: Semigroup,
+        Tr2::Batch: BatchReader<K, Tr2::Val, G::Timestamp, Tr2::R>+'static,
+        Tr2::Cursor: Cursor<K, Tr2::Val, G::Timestamp, Tr2::R>+'static,
+        R: Multiply<Tr2::R>,
+        <R as Multiply<Tr2::R>>::Output: Semigroup,
+        I: IntoIterator,
+        I::Item: Data,
+        L: FnMut(&K, &V, &K, &Tr2::Val)->I+'static;
+}
+
+impl<G, K, V, R> BandJoin<G, K, V, R> for Collection<G, (K, V), R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord+Debug,
+    K: ExchangeData+BandKey,
+    V: ExchangeData,
+    R: ExchangeData+Semigroup,
+{
+    fn band_join<V2, R2>(&self, other: &Collection<G, (K, V2), R2>, lo: K, hi: K) -> Collection<G, ((K, V), (K, V2)), <R as Multiply<R2>>::Output>
+    where
+        K: ExchangeData,
+        V2: ExchangeData,
+        R2: ExchangeData+Semigroup,
+        R: Multiply<R2>,
+        <R as Multiply<R2>>::Output: Semigroup,
+    {
+        self.band_join_core(&other.arrange_by_key(), lo, hi, |k1,v1,k2,v2| Some(((k1.clone(), v1.clone()), (k2.clone(), v2.clone()))))
+    }
+
+    fn band_join_core<Tr2, I, L>(&self, other: &Arranged<G, Tr2>, lo: K, hi: K, result: L) -> Collection<G, I::Item, <R as Multiply<Tr2::R>>::Output>
+    where
+        Tr2: TraceReader<Key=K, Time=G::Timestamp>+Clone+'static,
+        Tr2::Val: Ord+Clone+Debug+'static,
+        Tr2::R: Semigroup,
+        Tr2::Batch: BatchReader<K, Tr2::Val, G::Timestamp, Tr2::R>+'static,
+        Tr2::Cursor: Cursor<K, Tr2::Val, G::Timestamp, Tr2::R>+'static,
+        R: Multiply<Tr2::R>,
+        <R as Multiply<Tr2::R>>::Output: Semigroup,
+        I: IntoIterator,
+        I::Item: Data,
+        L: FnMut(&K, &V, &K, &Tr2::Val)->I+'static,
+    {
+        // Each worker requires all records of the first input, as matched keys need not be co-located.
+        let arranged = self.inner
+            .broadcast()
+            .as_collection()
+            .arrange_core::<_, DefaultValTrace<K, V, G::Timestamp, R>>(Pipeline, "Arrange: BandJoin");
+
+        // Keys `key1` of the first input match keys of the second input in `[key1 - lo, key1 + hi]`, and
+        // keys `key2` of the second input match keys of the first input in `[key2 - hi, key2 + lo]`.
+        let (lo1, hi1, lo2, hi2) = (lo.clone(), hi.clone(), lo, hi);
+        range_join(
+            &arranged,
+            other,
+            move |key1| key1.band_sub(&lo1),
+            move |key1, key2| key2 <= &key1.band_add(&hi1),
+            move |key2| key2.band_sub(&hi2),
+            move |key2, key1| key1 <= &key2.band_add(&lo2),
+            false,
+            result,
+        )
+    }
+}
+
+/// Matches the records of two arranged traces whose keys fall in ranges determined by one another.
+///
+/// Each key `key1` of the first input is matched with the keys `key2` of the second input that are at
+/// least `lower1(key1)` and for which `within1(key1, key2)` holds, and `lower2` and `within2` must
+/// describe the same pairs from the perspective of the second input. The predicates should hold for a
+/// prefix of the keys from each lower bound, as the search for matching keys ends at the first key for
+/// which they do not hold.
+///
+/// The structure mirrors that of `join_core`, with batches of each input joined against the accepted
+/// contents of the other input's trace, but each key of a batch is matched against a range of keys.
+/// The inputs must be partitioned so that matching keys are present at the same worker.
+///
+/// If `nearest` is set, each key of a batch of the first input is matched only with the first key of
+/// its range that has updates, and the search ends there. Batches of the second input are then only
+/// accepted once the frontier of the first input has passed them, so that for totally ordered times the
+/// updates of the second input's trace are all at or before the times of batches of the first input,
+/// and keys with no updates are those whose updates accumulate to zero.
+pub(crate) fn range_join<G, K, Tr1, Tr2, B1, W1, B2, W2, I, L>(
+    arranged1: &Arranged<G, Tr1>,
+    arranged2: &Arranged<G, Tr2>,
+    mut lower1: B1,
+    mut within1: W1,
+    mut lower2: B2,
+    mut within2: W2,
+    nearest: bool,
+    mut result: L,
+) -> Collection<G, I::Item, <Tr1::R as Multiply<Tr2::R>>::Output>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord+Debug,
+    K: Ord+Debug+'static,
+    Tr1: TraceReader<Key=K, Time=G::Timestamp>+Clone+'static,
+    Tr1::Val: Ord+Clone+Debug+'static,
+    Tr1::R: Semigroup,
+    Tr1::Batch: BatchReader<K, Tr1::Val, G::Timestamp, Tr1::R>+'static,
+    Tr1::Cursor: Cursor<K, Tr1::Val, G::Timestamp, Tr1::R>+'static,
+    Tr2: TraceReader<Key=K, Time=G::Timestamp>+Clone+'static,
+    Tr2::Val: Ord+Clone+Debug+'static,
+    Tr2::R: Semigroup,
+    Tr2::Batch: BatchReader<K, Tr2::Val, G::Timestamp, Tr2::R>+'static,
+    Tr2::Cursor: Cursor<K, Tr2::Val, G::Timestamp, Tr2::R>+'static,
+    Tr1::R: Multiply<Tr2::R>,
+    <Tr1::R as Multiply<Tr2::R>>::Output: Semigroup,
+    I: IntoIterator,
+    I::Item: Data,
+    B1: FnMut(&K)->K+'static,
+    W1: FnMut(&K, &K)->bool+'static,
+    B2: FnMut(&K)->K+'static,
+    W2: FnMut(&K, &K)->bool+'static,
+    L: FnMut(&K, &Tr1::Val, &K, &Tr2::Val)->I+'static,
+{
+    let mut trace1 = arranged1.trace.clone();
+    let mut trace2 = arranged2.trace.clone();
+
+    arranged1.stream.binary_frontier(&arranged2.stream, Pipeline, Pipeline, "RangeJoin", move |capability, info| {
+
+        // Acquire an activator to reschedule the operator when it has unfinished work.
+        use timely::scheduling::Activator;
+        let activations = arranged1.stream.scope().activations().clone();
+        let activator = Activator::new(&info.address[..], activations);
+
+        // Acknowledged frontier for each input, as in `join_core`.
+        let mut acknowledged1 = Antichain::from_elem(<G::Timestamp>::minimum());
+        let mut acknowledged2 = Antichain::from_elem(<G::Timestamp>::minimum());
+
+        // deferred work of batches from each input.
+        let mut todo1 = VecDeque::new();
+        let mut todo2 = VecDeque::new();
+
+        // Unload initial batches, joining those of `trace2` against all of `trace1`.
+        trace1.map_batches(|batch1| { acknowledged1.clone_from(batch1.upper()); });
+        assert!(PartialOrder::less_equal(&trace1.get_physical_compaction(), &acknowledged1.borrow()));
+
+        let mut batch2_cursors = Vec::new();
+        trace2.map_batches(|batch2| {
+            acknowledged2.clone_from(batch2.upper());
+            batch2_cursors.push((batch2.cursor(), batch2.clone()));
+        });
+        assert!(PartialOrder::less_equal(&trace2.get_physical_compaction(), &acknowledged2.borrow()));
+
+        for (batch2_cursor, batch2) in batch2_cursors.into_iter() {
+            let (trace1_cursor, trace1_storage) = trace1.cursor_through(acknowledged1.borrow()).unwrap();
+            todo2.push_back(RangeDeferred::new(trace1_cursor, trace1_storage, batch2_cursor, batch2.clone(), capability.clone()));
+        }
+
+        // Droppable handles to shared trace data structures.
+        let mut trace1_option = Some(trace1);
+        let mut trace2_option = Some(trace2);
+
+        // Swappable buffers for input extraction.
+        let mut input1_buffer = Vec::new();
+        let mut input2_buffer = Vec::new();
+
+        // Batches of the second input not yet accepted, with their capabilities.
+        let mut pending2 = VecDeque::new();
+
+        move |input1, input2, output| {
+
+            // 1. Consuming input, as in `join_core`.
+
+            input1.for_each(|capability, data| {
+                if let Some(ref mut trace2) = trace2_option {
+                    let capability = capability.retain();
+                    data.swap(&mut input1_buffer);
+                    for batch1 in input1_buffer.drain(..) {
+                        if PartialOrder::less_equal(&acknowledged1, &batch1.lower()) {
+                            if !batch1.is_empty() {
+                                let (trace2_cursor, trace2_storage) = trace2.cursor_through(acknowledged2.borrow()).unwrap();
+                                let batch1_cursor = batch1.cursor();
+                                todo1.push_back(RangeDeferred::new(trace2_cursor, trace2_storage, batch1_cursor, batch1.clone(), capability.clone()));
+                            }
+                            debug_assert!(PartialOrder::less_equal(&acknowledged1, batch1.upper()));
+                            acknowledged1.clone_from(batch1.upper());
+                        }
+                    }
+                }
+                else { panic!("`trace2_option` dropped before `input1` emptied!"); }
+            });
+
+            input2.for_each(|capability, data| {
+                let capability = capability.retain();
+                data.swap(&mut input2_buffer);
+                for batch2 in input2_buffer.drain(..) {
+                    pending2.push_back((capability.clone(), batch2));
+                }
+            });
+
+            // Accept batches of the second input, once the first input has passed them if `nearest` is set.
+            let frontier1 = input1.frontier().frontier();
+            let ready = |batch2: &Tr2::Batch| !nearest || PartialOrder::less_equal(&batch2.upper().borrow(), &frontier1);
+            while pending2.front().map(|&(_, ref batch2): &(Capability<G::Timestamp>, Tr2::Batch)| ready(batch2)).unwrap_or(false) {
+                let (capability, batch2) = pending2.pop_front().unwrap();
+                if let Some(ref mut trace1) = trace1_option {
+                    if PartialOrder::less_equal(&acknowledged2, &batch2.lower()) {
+                        if !batch2.is_empty() {
+                            let (trace1_cursor, trace1_storage) = trace1.cursor_through(acknowledged1.borrow()).unwrap();
+                            let batch2_cursor = batch2.cursor();
+                            todo2.push_back(RangeDeferred::new(trace1_cursor, trace1_storage, batch2_cursor, batch2.clone(), capability));
+                        }
+                        debug_assert!(PartialOrder::less_equal(&acknowledged2, batch2.upper()));
+                        acknowledged2.clone_from(batch2.upper());
+                    }
+                }
+                else { panic!("`trace1_option` dropped before `input2` emptied!"); }
+            }
+
+            // Advance acknowledged frontiers through any empty regions that we may not receive as batches.
+            if let Some(trace1) = trace1_option.as_mut() {
+                trace1.advance_upper(&mut acknowledged1);
+            }
+            if let Some(trace2) = trace2_option.as_mut() {
+                trace2.advance_upper(&mut acknowledged2);
+            }
+
+            // 2. Join computation.
+
+            let mut fuel = 1_000_000;
+            while !todo1.is_empty() && fuel > 0 {
+                todo1.front_mut().unwrap().work(
+                    output,
+                    |key1| lower1(key1),
+                    |key1, key2| within1(key1, key2),
+                    nearest,
+                    |k2,v2,k1,v1| result(k1,v1,k2,v2),
+                    |r2,r1| (r1.clone()).multiply(r2),
+                    &mut fuel
+                );
+                if !todo1.front().unwrap().work_remains() { todo1.pop_front(); }
+            }
+
+            let mut fuel = 1_000_000;
+            while !todo2.is_empty() && fuel > 0 {
+                todo2.front_mut().unwrap().work(
+                    output,
+                    |key2| lower2(key2),
+                    |key2, key1| within2(key2, key1),
+                    false,
+                    |k1,v1,k2,v2| result(k1,v1,k2,v2),
+                    |r1,r2| (r1.clone()).multiply(r2),
+                    &mut fuel
+                );
+                if !todo2.front().unwrap().work_remains() { todo2.pop_front(); }
+            }
+
+            // Re-activate operator if work remains.
+            if !todo1.is_empty() || !todo2.is_empty() {
+                activator.activate();
+            }
+
+            // 3. Trace maintenance, as in `join_core`.
+
+            if let Some(trace1) = trace1_option.as_mut() {
+                if input2.frontier().is_empty() && pending2.is_empty() { trace1_option = None; }
+                else {
+                    // Batches of the second input not yet accepted may still be joined with `trace1`.
+                    let mut frontier2 = Antichain::new();
+                    for time in input2.frontier().frontier().iter() { frontier2.insert(time.clone()); }
+                    for (capability, _) in pending2.iter() { frontier2.insert(capability.time().clone()); }
+                    trace1.set_logical_compaction(frontier2.borrow());
+                    trace1.set_physical_compaction(acknowledged1.borrow());
+                }
+            }
+
+            if let Some(trace2) = trace2_option.as_mut() {
+                if input1.frontier().is_empty() { trace2_option = None;}
+                else {
+                    trace2.set_logical_compaction(input1.frontier().frontier());
+                    trace2.set_physical_compaction(acknowledged2.borrow());
+                }
+            }
+        }
+    })
+    .as_collection()
+}
+
+/// Deferred range join computation.
+///
+/// As `Deferred` in `join`, but each key of the batch is matched against a range of keys of the trace.
+struct RangeDeferred<K, V1, V2, T, R1, R2, R3, C1, C2, D>
+where
+    V1: Ord+Clone,
+    V2: Ord+Clone,
+    T: Timestamp+Lattice+Ord+Debug,
+    R1: Semigroup,
+    R2: Semigroup,
+    R3: Semigroup,
+    C1: Cursor<K, V1, T, R1>,
+    C2: Cursor<K, V2, T, R2>,
+    D: Ord+Clone+Data,
+{
+    phant: ::std::marker::PhantomData<(K, V1, V2, R1, R2)>,
+    trace: C1,
+    trace_storage: C1::Storage,
+    batch: C2,
+    batch_storage: C2::Storage,
+    capability: Capability<T>,
+    done: bool,
+    temp: Vec<((D, T), R3)>,
+}
+
+impl<K, V1, V2, T, R1, R2, R3, C1, C2, D> RangeDeferred<K, V1, V2, T, R1, R2, R3, C1, C2, D>
+where
+    K: Ord+Debug,
+    V1: Ord+Clone+Debug,
+    V2: Ord+Clone+Debug,
+    T: Timestamp+Lattice+Ord+Debug,
+    R1: Semigroup,
+    R2: Semigroup,
+    R3: Semigroup,
+    C1: Cursor<K, V1, T, R1>,
+    C2: Cursor<K, V2, T, R2>,
+    D: Ord+Clone+Data,
+{
+    fn new(trace: C1, trace_storage: C1::Storage, batch: C2, batch_storage: C2::Storage, capability: Capability<T>) -> Self {
+        RangeDeferred {
+            phant: ::std::marker::PhantomData,
+            trace,
+            trace_storage,
+            batch,
+            batch_storage,
+            capability,
+            done: false,
+            temp: Vec::new(),
+        }
+    }
+
+    fn work_remains(&self) -> bool {
+        !self.done
+    }
+
+    /// Process batch keys until at least `limit` output tuples produced, or the work is exhausted.
+    ///
+    /// The function `lower` indicates the least trace key matching a batch key, and `within` whether a
+    /// trace key at least this bound matches the batch key. If `nearest` is set, the search ends at the
+    /// first matching trace key with updates.
+    #[inline(never)]
+    fn work<B, W, L, M, I>(&mut self, output: &mut OutputHandle<T, (D, T, R3), Tee<T, (D, T, R3)>>, mut lower: B, mut within: W, nearest: bool, mut logic: L, mut mult: M, fuel: &mut usize)
+    where I: IntoIterator<Item=D>, B: FnMut(&K)->K, W: FnMut(&K, &K)->bool, L: FnMut(&K, &V1, &K, &V2)->I, M: FnMut(&R1,&R2)->R3 {
+
+        let meet = self.capability.time();
+
+        let mut effort = 0;
+        let mut session = output.session(&self.capability);
+
+        let trace_storage = &self.trace_storage;
+        let batch_storage = &self.batch_storage;
+
+        let trace = &mut self.trace;
+        let batch = &mut self.batch;
+
+        let temp = &mut self.temp;
+        let mut thinker = JoinThinker::new();
+
+        while batch.key_valid(batch_storage) && effort < *fuel {
+
+            let least = lower(batch.key(batch_storage));
+
+            // Ranges of successive batch keys may overlap, in which case we must revisit trace keys.
+            if trace.get_key(trace_storage).map(|key| key > &least).unwrap_or(true) {
+                trace.rewind_keys(trace_storage);
+            }
+            trace.seek_key(trace_storage, &least);
+
+            thinker.history2.edits.load(batch, batch_storage, |time| time.clone());
+
+            while trace.get_key(trace_storage).map(|key| within(batch.key(batch_storage), key)).unwrap_or(false) {
+
+                thinker.history1.edits.load(trace, trace_storage, |time| time.join(&meet));
+
+                // populate `temp` with the results in the best way we know how.
+                thinker.think(|v1,v2,t,r1,r2|
+                    for result in logic(trace.key(trace_storage), v1, batch.key(batch_storage), v2) {
+                        temp.push(((result, t.clone()), mult(r1, r2)));
+                    }
+                );
+
+                let updates = !thinker.history1.edits.values.is_empty();
+                thinker.history1.clear();
+                trace.step_key(trace_storage);
+                if nearest && updates { break; }
+            }
+
+            crate::consolidation::consolidate(temp);
+
+            effort += temp.len();
+            for ((d, t), r) in temp.drain(..) {
+                session.give((d, t, r));
+            }
+
+            thinker.history2.clear();
+            batch.step_key(batch_storage);
+        }
+
+        self.done = !batch.key_valid(batch_storage);
+
+        if effort > *fuel { *fuel = 0; }
+        else              { *fuel -= effort; }
+    }
+}
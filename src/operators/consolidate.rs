@@ -8,7 +8,7 @@
 
 use timely::dataflow::Scope;
 
-use ::{Collection, ExchangeData, Hashable};
+use ::{Collection, Data, ExchangeData, Hashable};
 use ::difference::Semigroup;
 use operators::arrange::arrangement::Arrange;
 
@@ -123,3 +123,163 @@ where
             .as_collection()
     }
 }
+
+/// The number of held updates, after consolidation, above which `combine` sends all updates it holds.
+const COMBINE_LIMIT: usize = 1 << 16;
+
+/// The number of received updates above which `combine` consolidates them, if it holds fewer updates.
+const COMBINE_BATCH: usize = 1 << 10;
+
+/// An extension method for combining updates at each worker before they are exchanged.
+pub trait Combine : Sized {
+    /// Aggregates the weights of equal records at each worker.
+    ///
+    /// Like `consolidate_stream`, this method does not exchange data and does not ensure that at most
+    /// one copy of each `(data, time)` pair exists in the results. Unlike `consolidate_stream`, updates
+    /// are collapsed across messages, as each update is held until its time is complete, or until the
+    /// worker holds too many distinct updates. Placed before an exchange, for example that of an
+    /// arrangement, it reduces the number of updates sent between workers when many updates cancel
+    /// or accumulate, at the cost of holding updates until their times are complete.
+    ///
+    /// The `count`, `count_total`, `threshold` and `threshold_semigroup` methods of collections, and the
+    /// methods based on them, combine their inputs before arranging them. Their `_with_combine` variants
+    /// indicate whether to do so, and `arrange_core_with_combine` does the same for arrangements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate timely;
+    /// extern crate differential_dataflow;
+    ///
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Reduce;
+    /// use differential_dataflow::operators::consolidate::Combine;
+    ///
+    /// fn main() {
+    ///     ::timely::example(|scope| {
+    ///
+    ///         // combine the many updates to each key before they are exchanged.
+    ///         scope.new_collection_from(1 .. 1000u32).1
+    ///              .map(|x| (x % 10, x))
+    ///              .combine()
+    ///              .reduce(|_key, input, output| output.push((input.len(), 1)));
+    ///     });
+    /// }
+    /// ```
+    fn combine(&self) -> Self {
+        self.combine_named("Combine")
+    }
+
+    /// As `combine` but with the ability to name the operator.
+    fn combine_named(&self, name: &str) -> Self;
+}
+
+impl<G: Scope, D, R> Combine for Collection<G, D, R>
+where
+    D: Data,
+    R: Semigroup,
+    G::Timestamp: ::lattice::Lattice+Ord,
+ {
+    fn combine_named(&self, name: &str) -> Self {
+
+        use timely::dataflow::channels::pact::Pipeline;
+        use timely::dataflow::operators::{Operator, Capability};
+        use timely::progress::frontier::Antichain;
+        use collection::AsCollection;
+
+        self.inner
+            .unary_frontier(Pipeline, name, |_cap, _info| {
+
+                let mut buffer = Vec::new();
+                // Received updates, not yet consolidated.
+                let mut received = Vec::new();
+                // Consolidated updates held until their times are complete.
+                let mut stash = Vec::new();
+                // Capabilities for the lower envelope of times in `received` and `stash`.
+                let mut capabilities = Antichain::<Capability<G::Timestamp>>::new();
+
+                move |input, output| {
+
+                    input.for_each(|capability, data| {
+                        capabilities.insert(capability.retain());
+                        data.swap(&mut buffer);
+                        received.append(&mut buffer);
+                    });
+
+                    let frontier = input.frontier();
+                    let complete = capabilities.elements().iter().any(|c| !frontier.less_equal(c.time()));
+
+                    // Received updates are consolidated and merged into `stash` once they are as many as
+                    // the updates of `stash`, so that each update is consolidated a logarithmic number of times.
+                    if complete || received.len() > ::std::cmp::max(stash.len(), COMBINE_BATCH) {
+                        crate::consolidation::consolidate_updates(&mut received);
+                        stash = merge_updates(::std::mem::replace(&mut stash, Vec::new()), &mut received);
+                    }
+
+                    // If many updates remain after consolidation we send them all, as they do not
+                    // collapse enough to warrant holding them.
+                    let flush = stash.len() > COMBINE_LIMIT;
+
+                    if complete || flush {
+
+                        let mut sends = vec![Vec::new(); capabilities.elements().len()];
+                        let mut retained = Vec::new();
+                        for (data, time, diff) in stash.drain(..) {
+                            if flush || !frontier.less_equal(&time) {
+                                let index = capabilities.elements().iter().position(|c| c.time().less_equal(&time)).expect("failed to find capability");
+                                sends[index].push((data, time, diff));
+                            }
+                            else {
+                                retained.push((data, time, diff));
+                            }
+                        }
+                        stash = retained;
+
+                        for (index, mut updates) in sends.into_iter().enumerate() {
+                            if !updates.is_empty() {
+                                output.session(&capabilities.elements()[index]).give_vec(&mut updates);
+                            }
+                        }
+
+                        // Downgrade capabilities to the lower envelope of the times of held updates.
+                        let mut times = Antichain::new();
+                        for &(_, ref time, _) in stash.iter().chain(received.iter()) {
+                            times.insert(time.clone());
+                        }
+                        let mut new_capabilities = Antichain::new();
+                        for time in times.elements().iter() {
+                            if let Some(capability) = capabilities.elements().iter().find(|c| c.time().less_equal(time)) {
+                                new_capabilities.insert(capability.delayed(time));
+                            }
+                            else {
+                                panic!("failed to find capability");
+                            }
+                        }
+                        capabilities = new_capabilities;
+                    }
+                }
+            })
+            .as_collection()
+    }
+}
+
+/// Merges the consolidated updates of `updates1` and `updates2`, accumulating equal `(data, time)` pairs.
+///
+/// Both inputs must be sorted and consolidated, as is the result, and `updates2` is left empty.
+fn merge_updates<D: Ord, T: Ord, R: Semigroup>(updates1: Vec<(D, T, R)>, updates2: &mut Vec<(D, T, R)>) -> Vec<(D, T, R)> {
+    if updates2.is_empty() { return updates1; }
+    let mut merged = Vec::with_capacity(updates1.len() + updates2.len());
+    let mut updates2 = updates2.drain(..).peekable();
+    for (data, time, mut diff) in updates1.into_iter() {
+        while updates2.peek().map(|x| (&x.0, &x.1) < (&data, &time)).unwrap_or(false) {
+            merged.push(updates2.next().unwrap());
+        }
+        if updates2.peek().map(|x| (&x.0, &x.1) == (&data, &time)).unwrap_or(false) {
+            diff.plus_equals(&updates2.next().unwrap().2);
+            if diff.is_zero() { continue; }
+        }
+        merged.push((data, time, diff));
+    }
+    merged.extend(updates2);
+    merged
+}
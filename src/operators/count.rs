@@ -15,15 +15,16 @@
 
 use timely::order::TotalOrder;
 use timely::dataflow::*;
-use timely::dataflow::operators::Operator;
+use timely::dataflow::operators::{Operator, Map};
 use timely::dataflow::channels::pact::Pipeline;
 
 use lattice::Lattice;
-use ::{ExchangeData, Collection};
-use ::difference::Semigroup;
+use ::{Data, ExchangeData, Collection};
+use ::difference::{Semigroup, HyperLogLog, Present};
 use hashable::Hashable;
 use collection::AsCollection;
 use operators::arrange::{Arranged, ArrangeBySelf};
+use operators::consolidate::Combine;
 use trace::{BatchReader, Cursor, TraceReader};
 
 /// Extension trait for the `count` differential dataflow method.
@@ -49,12 +50,25 @@ pub trait CountTotal<G: Scope, K: ExchangeData, R: Semigroup> where G::Timestamp
     /// }
     /// ```
     fn count_total(&self) -> Collection<G, (K, R), isize>;
+
+    /// A `count_total` that combines updates at each worker before they are exchanged only if `combine` is set.
+    ///
+    /// Collections combine their updates in `count_total`. Arranged collections have already been
+    /// exchanged, and ignore `combine`.
+    fn count_total_with_combine(&self, _combine: bool) -> Collection<G, (K, R), isize> {
+        self.count_total()
+    }
 }
 
 impl<G: Scope, K: ExchangeData+Hashable, R: ExchangeData+Semigroup> CountTotal<G, K, R> for Collection<G, K, R>
 where G::Timestamp: TotalOrder+Lattice+Ord {
     fn count_total(&self) -> Collection<G, (K, R), isize> {
-        self.arrange_by_self_named("Arrange: CountTotal")
+        self.count_total_with_combine(true)
+    }
+    fn count_total_with_combine(&self, combine: bool) -> Collection<G, (K, R), isize> {
+        let collection = if combine { self.combine_named("Combine: CountTotal") } else { self.clone() };
+        collection
+            .arrange_by_self_named("Arrange: CountTotal")
             .count_total()
     }
 }
@@ -131,3 +145,53 @@ where
         .as_collection()
     }
 }
+
+/// Extension trait for the `count_distinct_approx` differential dataflow method.
+pub trait CountDistinctApprox<G: Scope, K: ExchangeData> where G::Timestamp: TotalOrder+Lattice+Ord {
+    /// Estimates the number of distinct values of each key.
+    ///
+    /// Each value is replaced by a `HyperLogLog` sketch of its hash, and the sketches are accumulated
+    /// by `count_total`, so that only the keys are arranged rather than each distinct value. The result
+    /// reports the estimate for each key, with a standard error of about 1.6%.
+    ///
+    /// Sketches cannot remove values, and the method is only for collections whose records are
+    /// inserted and never retracted, which the `Present` difference type ensures.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate timely;
+    /// extern crate differential_dataflow;
+    ///
+    /// use timely::dataflow::operators::ToStream;
+    /// use differential_dataflow::AsCollection;
+    /// use differential_dataflow::difference::Present;
+    /// use differential_dataflow::operators::count::CountDistinctApprox;
+    ///
+    /// fn main() {
+    ///     ::timely::example(|scope| {
+    ///         // estimate the number of distinct values of each key
+    ///         (1 .. 10).map(|x| ((x / 3, x), Default::default(), Present))
+    ///                  .to_stream(scope)
+    ///                  .as_collection()
+    ///                  .count_distinct_approx();
+    ///     });
+    /// }
+    /// ```
+    fn count_distinct_approx(&self) -> Collection<G, (K, u64), isize>;
+}
+
+impl<G: Scope, K, V> CountDistinctApprox<G, K> for Collection<G, (K, V), Present>
+where
+    G::Timestamp: TotalOrder+Lattice+Ord,
+    K: ExchangeData+Hashable,
+    V: Data+Hashable,
+{
+    fn count_distinct_approx(&self) -> Collection<G, (K, u64), isize> {
+        self.inner
+            .map(|((key, val), time, Present)| (key, time, HyperLogLog::from_hash(val.hashed().into())))
+            .as_collection()
+            .count_total()
+            .map(|(key, sketch)| (key, sketch.estimate()))
+    }
+}
@@ -19,7 +19,9 @@ use ::{Data, ExchangeData, Collection, AsCollection};
 use ::difference::{Semigroup, Abelian, Multiply};
 use lattice::Lattice;
 use operators::arrange::{Arranged, ArrangeByKey, ArrangeBySelf};
+use operators::reduce::ReduceCore;
 use trace::{BatchReader, Cursor};
+use trace::implementations::ord::OrdKeySpine as DefaultKeyTrace;
 use operators::ValueHistory;
 
 use trace::TraceReader;
@@ -151,6 +153,98 @@ pub trait Join<G: Scope, K: Data, V: Data, R: Semigroup> {
     /// ```
     fn antijoin<R2>(&self, other: &Collection<G, K, R2>) -> Collection<G, (K, V), R>
     where K: ExchangeData, R2: ExchangeData+Semigroup, R: Multiply<R2, Output = R>, R: Abelian;
+
+    /// Matches pairs `(key,val1)` and `(key,val2)`, and also yields `(key,val1)` whose key is absent from `other`.
+    ///
+    /// Matched pairs are yielded as `(key, (val1, Some(val2)))` with their frequencies multiplied, and records
+    /// of `self` whose key has no values in `other` are yielded as `(key, (val1, None))` with their frequencies.
+    /// The result reuses the arrangement of `self`, if it is arranged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate timely;
+    /// extern crate differential_dataflow;
+    ///
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Join;
+    ///
+    /// fn main() {
+    ///     ::timely::example(|scope| {
+    ///
+    ///         let x = scope.new_collection_from(vec![(0, 1), (1, 3)]).1;
+    ///         let y = scope.new_collection_from(vec![(0, 'a'), (2, 'b')]).1;
+    ///         let z = scope.new_collection_from(vec![(0, (1, Some('a'))), (1, (3, None))]).1;
+    ///
+    ///         x.left_join(&y)
+    ///          .assert_eq(&z);
+    ///     });
+    /// }
+    /// ```
+    fn left_join<V2>(&self, other: &Collection<G, (K,V2), R>) -> Collection<G, (K, (V, Option<V2>)), R>
+    where K: ExchangeData, V2: ExchangeData, R: ExchangeData+Abelian+Multiply<Output=R>+From<i8>;
+
+    /// Matches pairs `(key,val1)` and `(key,val2)`, and also yields `(key,val2)` whose key is absent from `self`.
+    ///
+    /// Matched pairs are yielded as `(key, (Some(val1), val2))` with their frequencies multiplied, and records
+    /// of `other` whose key has no values in `self` are yielded as `(key, (None, val2))` with their frequencies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate timely;
+    /// extern crate differential_dataflow;
+    ///
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Join;
+    ///
+    /// fn main() {
+    ///     ::timely::example(|scope| {
+    ///
+    ///         let x = scope.new_collection_from(vec![(0, 1), (1, 3)]).1;
+    ///         let y = scope.new_collection_from(vec![(0, 'a'), (2, 'b')]).1;
+    ///         let z = scope.new_collection_from(vec![(0, (Some(1), 'a')), (2, (None, 'b'))]).1;
+    ///
+    ///         x.right_join(&y)
+    ///          .assert_eq(&z);
+    ///     });
+    /// }
+    /// ```
+    fn right_join<V2>(&self, other: &Collection<G, (K,V2), R>) -> Collection<G, (K, (Option<V>, V2)), R>
+    where K: ExchangeData, V2: ExchangeData, R: ExchangeData+Abelian+Multiply<Output=R>+From<i8>;
+
+    /// Matches pairs `(key,val1)` and `(key,val2)`, and also yields records of either input whose key is absent from the other.
+    ///
+    /// Matched pairs are yielded as `(key, (Some(val1), Some(val2)))` with their frequencies multiplied, and
+    /// unmatched records as `(key, (Some(val1), None))` or `(key, (None, Some(val2)))` with their frequencies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate timely;
+    /// extern crate differential_dataflow;
+    ///
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Join;
+    ///
+    /// fn main() {
+    ///     ::timely::example(|scope| {
+    ///
+    ///         let x = scope.new_collection_from(vec![(0, 1), (1, 3)]).1;
+    ///         let y = scope.new_collection_from(vec![(0, 'a'), (2, 'b')]).1;
+    ///         let z = scope.new_collection_from(vec![
+    ///             (0, (Some(1), Some('a'))),
+    ///             (1, (Some(3), None)),
+    ///             (2, (None, Some('b'))),
+    ///         ]).1;
+    ///
+    ///         x.full_outer_join(&y)
+    ///          .assert_eq(&z);
+    ///     });
+    /// }
+    /// ```
+    fn full_outer_join<V2>(&self, other: &Collection<G, (K,V2), R>) -> Collection<G, (K, (Option<V>, Option<V2>)), R>
+    where K: ExchangeData, V2: ExchangeData, R: ExchangeData+Abelian+Multiply<Output=R>+From<i8>;
 }
 
 impl<G, K, V, R> Join<G, K, V, R> for Collection<G, (K, V), R>
@@ -179,6 +273,21 @@ where
     where R: Multiply<R2, Output=R>, R: Abelian {
         self.concat(&self.semijoin(other).negate())
     }
+
+    fn left_join<V2: ExchangeData>(&self, other: &Collection<G, (K, V2), R>) -> Collection<G, (K, (V, Option<V2>)), R>
+    where R: Abelian+Multiply<Output=R>+From<i8> {
+        self.arrange_by_key().left_join(other)
+    }
+
+    fn right_join<V2: ExchangeData>(&self, other: &Collection<G, (K, V2), R>) -> Collection<G, (K, (Option<V>, V2)), R>
+    where R: Abelian+Multiply<Output=R>+From<i8> {
+        self.arrange_by_key().right_join(other)
+    }
+
+    fn full_outer_join<V2: ExchangeData>(&self, other: &Collection<G, (K, V2), R>) -> Collection<G, (K, (Option<V>, Option<V2>)), R>
+    where R: Abelian+Multiply<Output=R>+From<i8> {
+        self.arrange_by_key().full_outer_join(other)
+    }
 }
 
 impl<G, Tr> Join<G, Tr::Key, Tr::Val, Tr::R> for Arranged<G, Tr>
@@ -209,6 +318,53 @@ where
         self.as_collection(|k,v| (k.clone(), v.clone()))
             .concat(&self.semijoin(other).negate())
     }
+
+    fn left_join<V2: ExchangeData>(&self, other: &Collection<G, (Tr::Key, V2), Tr::R>) -> Collection<G, (Tr::Key, (Tr::Val, Option<V2>)), Tr::R>
+    where Tr::Key: ExchangeData, Tr::R: ExchangeData+Abelian+Multiply<Output=Tr::R>+From<i8> {
+        let arranged2 = other.arrange_by_key();
+        self.join_core(&arranged2, |k,v1,v2| Some((k.clone(), (v1.clone(), Some(v2.clone())))))
+            .concat(&unmatched(self, &arranged2).map(|(k,v1)| (k, (v1, None))))
+    }
+
+    fn right_join<V2: ExchangeData>(&self, other: &Collection<G, (Tr::Key, V2), Tr::R>) -> Collection<G, (Tr::Key, (Option<Tr::Val>, V2)), Tr::R>
+    where Tr::Key: ExchangeData, Tr::R: ExchangeData+Abelian+Multiply<Output=Tr::R>+From<i8> {
+        let arranged2 = other.arrange_by_key();
+        self.join_core(&arranged2, |k,v1,v2| Some((k.clone(), (Some(v1.clone()), v2.clone()))))
+            .concat(&unmatched(&arranged2, self).map(|(k,v2)| (k, (None, v2))))
+    }
+
+    fn full_outer_join<V2: ExchangeData>(&self, other: &Collection<G, (Tr::Key, V2), Tr::R>) -> Collection<G, (Tr::Key, (Option<Tr::Val>, Option<V2>)), Tr::R>
+    where Tr::Key: ExchangeData, Tr::R: ExchangeData+Abelian+Multiply<Output=Tr::R>+From<i8> {
+        let arranged2 = other.arrange_by_key();
+        self.join_core(&arranged2, |k,v1,v2| Some((k.clone(), (Some(v1.clone()), Some(v2.clone())))))
+            .concat(&unmatched(self, &arranged2).map(|(k,v1)| (k, (Some(v1), None))))
+            .concat(&unmatched(&arranged2, self).map(|(k,v2)| (k, (None, Some(v2)))))
+    }
+}
+
+/// Records of `arranged1` whose keys have no values in `arranged2`.
+///
+/// The keys present in `arranged2` are determined from its arrangement, and the records of `arranged1`
+/// with those keys are subtracted from its contents, reusing its arrangement.
+fn unmatched<G, K, V1, V2, R, Tr1, Tr2>(arranged1: &Arranged<G, Tr1>, arranged2: &Arranged<G, Tr2>) -> Collection<G, (K, V1), R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: Data,
+    V1: Data,
+    V2: Data,
+    R: Abelian+Multiply<Output=R>+From<i8>,
+    Tr1: TraceReader<Key=K, Val=V1, Time=G::Timestamp, R=R>+Clone+'static,
+    Tr1::Batch: BatchReader<K, V1, G::Timestamp, R>+'static,
+    Tr1::Cursor: Cursor<K, V1, G::Timestamp, R>+'static,
+    Tr2: TraceReader<Key=K, Val=V2, Time=G::Timestamp, R=R>+Clone+'static,
+    Tr2::Batch: BatchReader<K, V2, G::Timestamp, R>+'static,
+    Tr2::Cursor: Cursor<K, V2, G::Timestamp, R>+'static,
+{
+    let present = arranged2.reduce_abelian::<_,DefaultKeyTrace<_,_,_>>("OuterJoinKeys", |_k, _s, t| t.push(((), R::from(1i8))));
+    arranged1
+        .as_collection(|k,v| (k.clone(), v.clone()))
+        .concat(&arranged1.join_core(&present, |k,v,_| Some((k.clone(), v.clone()))).negate())
 }
 
 /// Matches the elements of two arranged traces.
@@ -679,7 +835,7 @@ where
     }
 }
 
-struct JoinThinker<'a, V1: Ord+Clone+'a, V2: Ord+Clone+'a, T: Lattice+Ord+Clone, R1: Semigroup, R2: Semigroup> {
+pub(super) struct JoinThinker<'a, V1: Ord+Clone+'a, V2: Ord+Clone+'a, T: Lattice+Ord+Clone, R1: Semigroup, R2: Semigroup> {
     pub history1: ValueHistory<'a, V1, T, R1>,
     pub history2: ValueHistory<'a, V2, T, R2>,
 }
@@ -687,14 +843,14 @@ struct JoinThinker<'a, V1: Ord+Clone+'a, V2: Ord+Clone+'a, T: Lattice+Ord+Clone,
 impl<'a, V1: Ord+Clone, V2: Ord+Clone, T: Lattice+Ord+Clone, R1: Semigroup, R2: Semigroup> JoinThinker<'a, V1, V2, T, R1, R2>
 where V1: Debug, V2: Debug, T: Debug
 {
-    fn new() -> Self {
+    pub(super) fn new() -> Self {
         JoinThinker {
             history1: ValueHistory::new(),
             history2: ValueHistory::new(),
         }
     }
 
-    fn think<F: FnMut(&V1,&V2,T,&R1,&R2)>(&mut self, mut results: F) {
+    pub(super) fn think<F: FnMut(&V1,&V2,T,&R1,&R2)>(&mut self, mut results: F) {
 
         // for reasonably sized edits, do the dead-simple thing.
         if self.history1.edits.len() < 10 || self.history2.edits.len() < 10 {
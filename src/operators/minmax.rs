@@ -0,0 +1,132 @@
+//! Determines the least or greatest value of each key, by a function of the values.
+//!
+//! The `min_by_key` and `max_by_key` operators act on `(key, val)` data, and for each key retain the
+//! value whose image under a supplied function is least or greatest. Like `top_k`, they reduce the
+//! values of each key through a hierarchy of buckets, determined by the hashes of the values, so
+//! that a change to a value (including the retraction of the current extreme value) re-reads only a
+//! bounded number of records at each level, rather than all of the values of its key.
+//!
+//! The results are arranged by key, as the results of `reduce_abelian` are, and can be used wherever
+//! those results can.
+
+use timely::dataflow::Scope;
+
+use ::{Data, ExchangeData, Collection, Hashable};
+use lattice::Lattice;
+use operators::arrange::{Arranged, ArrangeByKey, TraceAgent};
+use operators::reduce::ReduceCore;
+use operators::topk::{hierarchy, first_records};
+use trace::{BatchReader, Cursor, TraceReader};
+use trace::implementations::ord::OrdValSpine as DefaultValTrace;
+
+/// Extension trait for the `min_by_key` and `max_by_key` differential dataflow methods.
+pub trait MinMax<G: Scope, K: Data, V: Data> where G::Timestamp: Lattice+Ord {
+    /// Retains for each key the value with the least image under `func`.
+    ///
+    /// Among values with equal images the least value is retained. The retained value has multiplicity
+    /// one, and values with non-positive multiplicities are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate timely;
+    /// extern crate differential_dataflow;
+    ///
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::MinMax;
+    ///
+    /// fn main() {
+    ///     ::timely::example(|scope| {
+    ///
+    ///         let x = scope.new_collection_from(1 .. 10).1.map(|x| (x % 3, x));
+    ///         let y = scope.new_collection_from(vec![(0, 6), (1, 4), (2, 5)]).1;
+    ///
+    ///         // retain the value nearest to five for each key.
+    ///         x.min_by_key(|x| (x - 5i32).abs())
+    ///          .as_collection(|k,v| (*k, *v))
+    ///          .assert_eq(&y);
+    ///     });
+    /// }
+    /// ```
+    fn min_by_key<O, L>(&self, func: L) -> Arranged<G, TraceAgent<DefaultValTrace<K, V, G::Timestamp, isize>>>
+    where O: Ord, L: Fn(&V)->O+Clone+'static;
+
+    /// Retains for each key the value with the greatest image under `func`.
+    ///
+    /// Among values with equal images the least value is retained. The retained value has multiplicity
+    /// one, and values with non-positive multiplicities are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate timely;
+    /// extern crate differential_dataflow;
+    ///
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::MinMax;
+    ///
+    /// fn main() {
+    ///     ::timely::example(|scope| {
+    ///
+    ///         let x = scope.new_collection_from(1 .. 10).1.map(|x| (x % 3, x));
+    ///         let y = scope.new_collection_from(vec![(0, 9), (1, 1), (2, 2)]).1;
+    ///
+    ///         // retain the value furthest from five for each key.
+    ///         x.max_by_key(|x| (x - 5i32).abs())
+    ///          .as_collection(|k,v| (*k, *v))
+    ///          .assert_eq(&y);
+    ///     });
+    /// }
+    /// ```
+    fn max_by_key<O, L>(&self, func: L) -> Arranged<G, TraceAgent<DefaultValTrace<K, V, G::Timestamp, isize>>>
+    where O: Ord, L: Fn(&V)->O+Clone+'static;
+}
+
+impl<G, K, V> MinMax<G, K, V> for Collection<G, (K, V), isize>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+Hashable,
+    V: ExchangeData+Hashable,
+{
+    fn min_by_key<O, L>(&self, func: L) -> Arranged<G, TraceAgent<DefaultValTrace<K, V, G::Timestamp, isize>>>
+    where O: Ord, L: Fn(&V)->O+Clone+'static {
+        let order = move |x: &V, y: &V| func(x).cmp(&func(y));
+        hierarchy(self, order.clone(), 1)
+            .arrange_by_key_named("Arrange: MinByKey")
+            .reduce_abelian("MinByKey", first_records(order, 0, 1))
+    }
+
+    fn max_by_key<O, L>(&self, func: L) -> Arranged<G, TraceAgent<DefaultValTrace<K, V, G::Timestamp, isize>>>
+    where O: Ord, L: Fn(&V)->O+Clone+'static {
+        let order = move |x: &V, y: &V| func(y).cmp(&func(x));
+        hierarchy(self, order.clone(), 1)
+            .arrange_by_key_named("Arrange: MaxByKey")
+            .reduce_abelian("MaxByKey", first_records(order, 0, 1))
+    }
+}
+
+impl<G, T1> MinMax<G, T1::Key, T1::Val> for Arranged<G, T1>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    T1: TraceReader<Time=G::Timestamp, R=isize>+Clone+'static,
+    T1::Key: ExchangeData+Hashable,
+    T1::Val: ExchangeData+Hashable,
+    T1::Batch: BatchReader<T1::Key, T1::Val, G::Timestamp, isize>,
+    T1::Cursor: Cursor<T1::Key, T1::Val, G::Timestamp, isize>,
+{
+    fn min_by_key<O, L>(&self, func: L) -> Arranged<G, TraceAgent<DefaultValTrace<T1::Key, T1::Val, G::Timestamp, isize>>>
+    where O: Ord, L: Fn(&T1::Val)->O+Clone+'static {
+        // The buckets of the hierarchy are not the keys of the arrangement, which cannot be re-used.
+        self.as_collection(|k,v| (k.clone(), v.clone()))
+            .min_by_key(func)
+    }
+
+    fn max_by_key<O, L>(&self, func: L) -> Arranged<G, TraceAgent<DefaultValTrace<T1::Key, T1::Val, G::Timestamp, isize>>>
+    where O: Ord, L: Fn(&T1::Val)->O+Clone+'static {
+        // The buckets of the hierarchy are not the keys of the arrangement, which cannot be re-used.
+        self.as_collection(|k,v| (k.clone(), v.clone()))
+            .max_by_key(func)
+    }
+}
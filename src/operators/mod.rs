@@ -10,6 +10,12 @@ pub use self::iterate::Iterate;
 pub use self::join::{Join, JoinCore};
 pub use self::count::CountTotal;
 pub use self::threshold::ThresholdTotal;
+pub use self::temporal::TemporalFilter;
+pub use self::topk::TopK;
+pub use self::minmax::MinMax;
+pub use self::band::BandJoin;
+pub use self::asof::AsOfJoin;
+pub use self::skew::SkewJoin;
 
 pub mod arrange;
 pub mod reduce;
@@ -18,6 +24,13 @@ pub mod iterate;
 pub mod join;
 pub mod count;
 pub mod threshold;
+pub mod temporal;
+pub mod topk;
+pub mod minmax;
+pub mod windows;
+pub mod band;
+pub mod asof;
+pub mod skew;
 
 use ::difference::Semigroup;
 use lattice::Lattice;
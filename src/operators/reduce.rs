@@ -18,6 +18,7 @@ use timely::dataflow::channels::pact::Pipeline;
 use timely::dataflow::operators::Capability;
 
 use operators::arrange::{Arranged, ArrangeByKey, ArrangeBySelf, TraceAgent};
+use operators::consolidate::Combine;
 use lattice::Lattice;
 use trace::{Batch, BatchReader, Cursor, Trace, Builder};
 use trace::cursor::CursorList;
@@ -133,6 +134,14 @@ pub trait Threshold<G: Scope, K: Data, R1: Semigroup> where G::Timestamp: Lattic
     /// A `threshold` with the ability to name the operator.
     fn threshold_named<R2: Abelian, F: FnMut(&K, &R1)->R2+'static>(&self, name: &str, thresh: F) -> Collection<G, K, R2>;
 
+    /// A `threshold_named` that combines updates at each worker before they are exchanged only if `combine` is set.
+    ///
+    /// Collections combine their updates in `threshold_named`. Arranged collections have already been
+    /// exchanged, and ignore `combine`.
+    fn threshold_named_with_combine<R2: Abelian, F: FnMut(&K, &R1)->R2+'static>(&self, name: &str, thresh: F, _combine: bool) -> Collection<G, K, R2> {
+        self.threshold_named(name, thresh)
+    }
+
     /// Reduces the collection to one occurrence of each distinct element.
     ///
     /// # Examples
@@ -170,7 +179,12 @@ pub trait Threshold<G: Scope, K: Data, R1: Semigroup> where G::Timestamp: Lattic
 impl<G: Scope, K: ExchangeData+Hashable, R1: ExchangeData+Semigroup> Threshold<G, K, R1> for Collection<G, K, R1>
 where G::Timestamp: Lattice+Ord {
     fn threshold_named<R2: Abelian, F: FnMut(&K,&R1)->R2+'static>(&self, name: &str, thresh: F) -> Collection<G, K, R2> {
-        self.arrange_by_self_named(&format!("Arrange: {}", name))
+        self.threshold_named_with_combine(name, thresh, true)
+    }
+    fn threshold_named_with_combine<R2: Abelian, F: FnMut(&K,&R1)->R2+'static>(&self, name: &str, thresh: F, combine: bool) -> Collection<G, K, R2> {
+        let collection = if combine { self.combine_named(&format!("Combine: {}", name)) } else { self.clone() };
+        collection
+            .arrange_by_self_named(&format!("Arrange: {}", name))
             .threshold_named(name, thresh)
     }
 }
@@ -211,6 +225,14 @@ pub trait Count<G: Scope, K: Data, R: Semigroup> where G::Timestamp: Lattice+Ord
     /// }
     /// ```
     fn count(&self) -> Collection<G, (K, R), isize>;
+
+    /// A `count` that combines updates at each worker before they are exchanged only if `combine` is set.
+    ///
+    /// Collections combine their updates in `count`. Arranged collections have already been exchanged,
+    /// and ignore `combine`.
+    fn count_with_combine(&self, _combine: bool) -> Collection<G, (K, R), isize> {
+        self.count()
+    }
 }
 
 impl<G: Scope, K: ExchangeData+Hashable, R: ExchangeData+Semigroup> Count<G, K, R> for Collection<G, K, R>
@@ -218,7 +240,12 @@ where
     G::Timestamp: Lattice+Ord,
 {
     fn count(&self) -> Collection<G, (K, R), isize> {
-        self.arrange_by_self_named("Arrange: Count")
+        self.count_with_combine(true)
+    }
+    fn count_with_combine(&self, combine: bool) -> Collection<G, (K, R), isize> {
+        let collection = if combine { self.combine_named("Combine: Count") } else { self.clone() };
+        collection
+            .arrange_by_self_named("Arrange: Count")
             .count()
     }
 }
@@ -0,0 +1,143 @@
+//! Match pairs of records based on a key, spreading the work of heavily used keys across workers.
+//!
+//! The `join` operators exchange both inputs by the hash of their keys, so that all records with the
+//! same key are matched at the same worker. If one key has many records, that worker must produce all
+//! of the key's matches, while other workers may have little to do. The `skew_join` operators instead
+//! identify the keys with many records in the larger input, and for those keys alone partition the
+//! records of the larger input by their values and replicate the records of the smaller input at every
+//! worker. Records of other keys are matched as in `join`, and the output is the same.
+//!
+//! Keys become and cease to be heavy as the number of their records changes, and their records are
+//! then moved between the two strategies. The heavy keys, and the records of heavy keys in the smaller
+//! input, are held at each worker, and so the threshold should be large enough that few keys are heavy.
+//! The records of the larger input are arranged where they are introduced, to divide them between the
+//! strategies without first moving them to the workers responsible for their keys.
+
+use timely::dataflow::Scope;
+use timely::dataflow::operators::Broadcast;
+use timely::dataflow::channels::pact::{Exchange, Pipeline};
+
+use ::{Data, ExchangeData, Collection, AsCollection, Hashable};
+use ::difference::{Semigroup, Abelian, Multiply};
+use lattice::Lattice;
+use operators::{JoinCore, Count};
+use operators::arrange::{Arrange, ArrangeByKey};
+use trace::implementations::ord::OrdValSpine as DefaultValTrace;
+use trace::implementations::ord::OrdKeySpine as DefaultKeyTrace;
+
+/// Extension trait for the `skew_join` differential dataflow methods.
+pub trait SkewJoin<G: Scope, K: Data, V: Data, R: Semigroup> where G::Timestamp: Lattice+Ord {
+    /// Matches pairs `(key,val1)` and `(key,val2)` based on `key`, spreading keys with at least `threshold` records in `self` across workers.
+    ///
+    /// The result is the same as that of `join`. The input `self` should be the larger input, as the
+    /// records of `other` with heavy keys are replicated at every worker.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate timely;
+    /// extern crate differential_dataflow;
+    ///
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::skew::SkewJoin;
+    ///
+    /// fn main() {
+    ///     ::timely::example(|scope| {
+    ///
+    ///         let x = scope.new_collection_from((0 .. 10).map(|x| (x % 2, x))).1;
+    ///         let y = scope.new_collection_from(vec![(0, 'a'), (1, 'b')]).1;
+    ///         let z = scope.new_collection_from((0 .. 10).map(|x| (x % 2, (x, if x % 2 == 0 { 'a' } else { 'b' })))).1;
+    ///
+    ///         // keys with at least five records are heavy.
+    ///         x.skew_join(&y, 5)
+    ///          .assert_eq(&z);
+    ///     });
+    /// }
+    /// ```
+    fn skew_join<V2, R2>(&self, other: &Collection<G, (K, V2), R2>, threshold: R) -> Collection<G, (K, (V, V2)), <R as Multiply<R2>>::Output>
+    where
+        V2: ExchangeData,
+        R2: ExchangeData+Abelian+Multiply<isize, Output=R2>,
+        R: Multiply<R2>,
+        <R as Multiply<R2>>::Output: Semigroup,
+    {
+        self.skew_join_map(other, threshold, |k,v1,v2| (k.clone(), (v1.clone(), v2.clone())))
+    }
+
+    /// Matches pairs `(key,val1)` and `(key,val2)` based on `key` and then applies a function, spreading keys with at least `threshold` records in `self` across workers.
+    ///
+    /// The result is the same as that of `join_map`.
+    fn skew_join_map<V2, R2, D, L>(&self, other: &Collection<G, (K, V2), R2>, threshold: R, logic: L) -> Collection<G, D, <R as Multiply<R2>>::Output>
+    where
+        V2: ExchangeData,
+        R2: ExchangeData+Abelian+Multiply<isize, Output=R2>,
+        R: Multiply<R2>,
+        <R as Multiply<R2>>::Output: Semigroup,
+        D: Data,
+        L: FnMut(&K, &V, &V2)->D+Clone+'static;
+}
+
+impl<G, K, V, R> SkewJoin<G, K, V, R> for Collection<G, (K, V), R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+Hashable,
+    V: ExchangeData+Hashable,
+    R: ExchangeData+Abelian+Multiply<isize, Output=R>,
+{
+    fn skew_join_map<V2, R2, D, L>(&self, other: &Collection<G, (K, V2), R2>, threshold: R, logic: L) -> Collection<G, D, <R as Multiply<R2>>::Output>
+    where
+        V2: ExchangeData,
+        R2: ExchangeData+Abelian+Multiply<isize, Output=R2>,
+        R: Multiply<R2>,
+        <R as Multiply<R2>>::Output: Semigroup,
+        D: Data,
+        L: FnMut(&K, &V, &V2)->D+Clone+'static,
+    {
+        // Keys with at least `threshold` records in `self`, arranged at every worker.
+        let heavy = self
+            .map(|(key, _val)| key)
+            .count()
+            .filter(move |&(_, ref count)| count >= &threshold)
+            .map(|(key, _count)| key)
+            .inner
+            .broadcast()
+            .as_collection()
+            .arrange_core::<_, DefaultKeyTrace<K, G::Timestamp, isize>>(Pipeline, "Arrange: SkewJoinHeavyKeys");
+
+        // Records of `self` are divided between the strategies where they are introduced, so that the
+        // records of heavy keys are never gathered at one worker.
+        let local1 = self.arrange_core::<_, DefaultValTrace<K, V, G::Timestamp, R>>(Pipeline, "Arrange: SkewJoinLocal");
+        let heavy1 = local1.join_core(&heavy, |k,v,_| Some((k.clone(), v.clone())));
+
+        // Records of other keys are matched at the worker responsible for their key, with all records of
+        // `other`. As `other` is arranged by key, its records of heavy keys are found where they are.
+        let light1 = local1
+            .as_collection(|k,v| (k.clone(), v.clone()))
+            .concat(&heavy1.negate())
+            .arrange_core::<_, DefaultValTrace<K, V, G::Timestamp, R>>(
+                Exchange::new(|update: &((K, V), G::Timestamp, R)| (update.0).0.hashed().into()),
+                "Arrange: SkewJoinLight",
+            );
+        let arranged2 = other.arrange_by_key();
+        let heavy2 = arranged2.join_core(&heavy, |k,v,_| Some((k.clone(), v.clone())));
+        let mut light_logic = logic.clone();
+        let light = light1.join_core(&arranged2, move |k,v1,v2| Some(light_logic(k,v1,v2)));
+
+        // Records of heavy keys in `self` are partitioned by value, and matched with all records of
+        // heavy keys in `other`, which are present at every worker.
+        let heavy1 = heavy1.arrange_core::<_, DefaultValTrace<K, V, G::Timestamp, R>>(
+            Exchange::new(|update: &((K, V), G::Timestamp, R)| (update.0).1.hashed().into()),
+            "Arrange: SkewJoinHeavy",
+        );
+        let heavy2 = heavy2
+            .inner
+            .broadcast()
+            .as_collection()
+            .arrange_core::<_, DefaultValTrace<K, V2, G::Timestamp, R2>>(Pipeline, "Arrange: SkewJoinBroadcast");
+        let mut heavy_logic = logic;
+        let heavy = heavy1.join_core(&heavy2, move |k,v1,v2| Some(heavy_logic(k,v1,v2)));
+
+        light.concat(&heavy)
+    }
+}
@@ -0,0 +1,163 @@
+//! Restricts records to intervals of validity.
+//!
+//! Records are often valid only for an interval of time, for example those with `valid_from` and
+//! `valid_to` fields, or those that should only be considered for some period after they occur. The
+//! `temporal_filter` operator presents each record from the lower bound of its interval, and retracts
+//! it at the upper bound of its interval.
+//!
+//! Updates at future times are held back by the operator, along with capabilities for their times,
+//! and released once the input frontier indicates that their times are complete. This avoids burdening
+//! downstream operators with updates they cannot yet act on.
+
+use std::collections::BTreeMap;
+use std::collections::Bound::{Excluded, Unbounded};
+
+use timely::dataflow::Scope;
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Operator, Capability};
+use timely::order::PartialOrder;
+use timely::progress::Timestamp;
+
+use ::{Data, Collection, AsCollection};
+use ::difference::Abelian;
+use lattice::Lattice;
+use consolidation::consolidate;
+
+/// Extension trait for the `temporal_filter` differential dataflow method.
+pub trait TemporalFilter<G: Scope, D: Data, R: Abelian> where G::Timestamp: Lattice+Ord {
+    /// Presents each record only between the lower and upper times of its interval of validity.
+    ///
+    /// The function `logic` maps each record to a pair `(lower, upper)` of times. A record introduced at
+    /// time `time` is presented at time `time.join(&lower)` and retracted at `time.join(&lower).join(&upper)`,
+    /// so that it is present in the collection at those times greater or equal to both `time` and `lower`,
+    /// but not greater or equal to `upper`. Records whose intervals are empty are never presented.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate timely;
+    /// extern crate differential_dataflow;
+    ///
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::TemporalFilter;
+    ///
+    /// fn main() {
+    ///     ::timely::example(|scope| {
+    ///         // present each `(valid_from, valid_to)` record while it is valid.
+    ///         scope.new_collection_from(vec![(0u64, 5u64), (3, 7), (4, 4)]).1
+    ///              .temporal_filter(|&(from, to)| (from, to));
+    ///     });
+    /// }
+    /// ```
+    fn temporal_filter<L>(&self, logic: L) -> Collection<G, D, R>
+    where L: FnMut(&D)->(G::Timestamp, G::Timestamp)+'static;
+}
+
+impl<G, D, R> TemporalFilter<G, D, R> for Collection<G, D, R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    D: Data,
+    R: Abelian,
+{
+    fn temporal_filter<L>(&self, mut logic: L) -> Collection<G, D, R>
+    where L: FnMut(&D)->(G::Timestamp, G::Timestamp)+'static {
+
+        let mut buffer = Vec::new();
+        let mut ready = Vec::new();
+
+        self.inner.unary_frontier(Pipeline, "TemporalFilter", move |_,_| {
+
+            // Updates at times not yet complete, in chains of totally ordered times.
+            let mut chains = Vec::<Chain<G::Timestamp, D, R>>::new();
+
+            move |input, output| {
+
+                input.for_each(|capability, data| {
+                    data.swap(&mut buffer);
+                    let mut session = output.session(&capability);
+                    for (data, time, diff) in buffer.drain(..) {
+                        let (lower, upper) = logic(&data);
+                        let lower = time.join(&lower);
+                        let upper = lower.join(&upper);
+                        // Intervals of validity may be empty, in which case the record is never presented.
+                        if lower != upper {
+                            stash(&mut chains, upper, (data.clone(), diff.clone().negate()), |time| capability.delayed(time));
+                            if lower == time {
+                                session.give((data, time, diff));
+                            }
+                            else {
+                                stash(&mut chains, lower, (data, diff), |time| capability.delayed(time));
+                            }
+                        }
+                    }
+                });
+
+                // Release the updates of each chain whose times are no longer in advance of the input frontier.
+                let frontier = input.frontier().frontier();
+                for chain in chains.iter_mut() {
+                    loop {
+                        let time = match chain.updates.keys().next() {
+                            Some(time) if !frontier.less_equal(time) => time.clone(),
+                            _ => break,
+                        };
+                        let mut updates = chain.updates.remove(&time).expect("time just found");
+                        consolidate(&mut updates);
+                        ready.extend(updates.into_iter().map(|(data, diff)| (data, time.clone(), diff)));
+                    }
+                    if !ready.is_empty() {
+                        output.session(&chain.capability).give_iterator(ready.drain(..));
+                    }
+                    if let Some(time) = chain.updates.keys().next() {
+                        chain.capability.downgrade(time);
+                    }
+                }
+                chains.retain(|chain| !chain.updates.is_empty());
+            }
+        })
+        .as_collection()
+    }
+}
+
+/// Pending updates whose times are totally ordered, and a capability for the least of them.
+///
+/// The updates of a chain whose times are complete form a prefix of the chain, as times greater than a
+/// time in advance of the input frontier are also in advance of it. The times of the chain are ordered
+/// by `Ord`, which is assumed to extend their partial order.
+struct Chain<T: Timestamp, D, R> {
+    capability: Capability<T>,
+    updates: BTreeMap<T, Vec<(D, R)>>,
+}
+
+impl<T: Timestamp+Ord, D, R> Chain<T, D, R> {
+    /// Indicates whether `time` is comparable with each time of the chain.
+    ///
+    /// As the times of the chain are totally ordered, it suffices to compare `time` with its neighbors.
+    fn accepts(&self, time: &T) -> bool {
+        let below = self.updates.range(..=time).next_back().map(|(prior, _)| prior.less_equal(time)).unwrap_or(true);
+        let above = self.updates.range((Excluded(time), Unbounded)).next().map(|(next, _)| time.less_equal(next)).unwrap_or(true);
+        below && above
+    }
+}
+
+/// Adds an update at `time` to the first chain that accepts it, or to a new chain.
+///
+/// The function `delayed` produces a capability for `time`, used if no capability held covers it.
+fn stash<T, D, R, F>(chains: &mut Vec<Chain<T, D, R>>, time: T, update: (D, R), delayed: F)
+where
+    T: Timestamp+Ord,
+    F: Fn(&T)->Capability<T>,
+{
+    if let Some(chain) = chains.iter_mut().find(|chain| chain.accepts(&time)) {
+        if !chain.capability.time().less_equal(&time) {
+            chain.capability = delayed(&time);
+        }
+        chain.updates.entry(time).or_insert_with(Vec::new).push(update);
+    }
+    else {
+        let capability = delayed(&time);
+        let mut updates = BTreeMap::new();
+        updates.insert(time, vec![update]);
+        chains.push(Chain { capability, updates });
+    }
+}
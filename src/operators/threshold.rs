@@ -14,6 +14,7 @@ use ::difference::{Semigroup, Abelian};
 use hashable::Hashable;
 use collection::AsCollection;
 use operators::arrange::{Arranged, ArrangeBySelf};
+use operators::consolidate::Combine;
 use trace::{BatchReader, Cursor, TraceReader};
 
 /// Extension trait for the `distinct` differential dataflow method.
@@ -24,6 +25,17 @@ pub trait ThresholdTotal<G: Scope, K: ExchangeData, R: ExchangeData+Semigroup> w
         R2: Semigroup,
         F: FnMut(&K,&R,Option<&R>)->Option<R2>+'static,
         ;
+    /// A `threshold_semigroup` that combines updates at each worker before they are exchanged only if `combine` is set.
+    ///
+    /// Collections combine their updates in `threshold_semigroup`. Arranged collections have already
+    /// been exchanged, and ignore `combine`.
+    fn threshold_semigroup_with_combine<R2, F>(&self, thresh: F, _combine: bool) -> Collection<G, K, R2>
+    where
+        R2: Semigroup,
+        F: FnMut(&K,&R,Option<&R>)->Option<R2>+'static,
+    {
+        self.threshold_semigroup(thresh)
+    }
     /// Reduces the collection to one occurrence of each distinct element.
     ///
     /// # Examples
@@ -97,7 +109,16 @@ where G::Timestamp: TotalOrder+Lattice+Ord {
         R2: Semigroup,
         F: FnMut(&K,&R,Option<&R>)->Option<R2>+'static,
     {
-        self.arrange_by_self_named("Arrange: ThresholdTotal")
+        self.threshold_semigroup_with_combine(thresh, true)
+    }
+    fn threshold_semigroup_with_combine<R2, F>(&self, thresh: F, combine: bool) -> Collection<G, K, R2>
+    where
+        R2: Semigroup,
+        F: FnMut(&K,&R,Option<&R>)->Option<R2>+'static,
+    {
+        let collection = if combine { self.combine_named("Combine: ThresholdTotal") } else { self.clone() };
+        collection
+            .arrange_by_self_named("Arrange: ThresholdTotal")
             .threshold_semigroup(thresh)
     }
 }
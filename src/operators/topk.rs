@@ -0,0 +1,153 @@
+//! Retains the first records of each key, according to an ordering of their values.
+//!
+//! The `top_k` operator acts on `(key, val)` data, and for each key retains those records that
+//! come first when ordered by a supplied function, after skipping an optional number of records.
+//! Multiplicities count as records, so that a value with multiplicity three occupies three places.
+//!
+//! Rather than reducing each key in one step, which would re-read all of the values of a key on
+//! each change, the operator builds a hierarchy of reductions over buckets of the values of each
+//! key, determined by the low-order bits of the hashes of the values. Each reduction retains only enough
+//! records for the result, and a change to a value only re-reads its bucket in each level of the
+//! hierarchy, each of which holds a bounded number of records from the level below.
+
+use std::cmp::Ordering;
+
+use timely::dataflow::Scope;
+
+use ::{Data, ExchangeData, Collection, Hashable};
+use lattice::Lattice;
+use operators::Reduce;
+use operators::arrange::Arranged;
+use trace::{BatchReader, Cursor, TraceReader};
+
+/// The number of bits of the hashes of values that distinguish buckets at each level of the hierarchy.
+///
+/// The first level has buckets for the low 24 bits of hashes, and each further level removes eight
+/// of these bits, so that each bucket holds the retained records of at most 256 buckets of
+/// the previous level.
+const LEVELS: [u64; 3] = [24, 16, 8];
+
+/// Extension trait for the `top_k` differential dataflow method.
+pub trait TopK<G: Scope, K: Data, V: Data> where G::Timestamp: Lattice+Ord {
+    /// Retains for each key the first `limit` records after the first `offset`, as ordered by `order`.
+    ///
+    /// Records that `order` considers equal are ordered by their values. Each record is counted as
+    /// many times as its multiplicity, and records with non-positive multiplicities are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate timely;
+    /// extern crate differential_dataflow;
+    ///
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::TopK;
+    ///
+    /// fn main() {
+    ///     ::timely::example(|scope| {
+    ///
+    ///         let x = scope.new_collection_from(1 .. 10).1.map(|x| (x % 3, x));
+    ///         let y = scope.new_collection_from(vec![(0, 6), (1, 4), (2, 5)]).1;
+    ///
+    ///         // retain the second largest value for each key.
+    ///         x.top_k(|a, b| b.cmp(a), 1, Some(1))
+    ///          .assert_eq(&y);
+    ///     });
+    /// }
+    /// ```
+    fn top_k<F>(&self, order: F, limit: usize, offset: Option<usize>) -> Collection<G, (K, V), isize>
+    where F: Fn(&V, &V)->Ordering+Clone+'static;
+}
+
+impl<G, K, V> TopK<G, K, V> for Collection<G, (K, V), isize>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+Hashable,
+    V: ExchangeData+Hashable,
+{
+    fn top_k<F>(&self, order: F, limit: usize, offset: Option<usize>) -> Collection<G, (K, V), isize>
+    where F: Fn(&V, &V)->Ordering+Clone+'static {
+
+        // Each intermediate reduction must retain the records that are skipped, as well as those retained.
+        let offset = offset.unwrap_or(0);
+        hierarchy(self, order.clone(), offset.saturating_add(limit))
+            .reduce_named("TopK", first_records(order, offset, limit))
+    }
+}
+
+impl<G, T1> TopK<G, T1::Key, T1::Val> for Arranged<G, T1>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    T1: TraceReader<Time=G::Timestamp, R=isize>+Clone+'static,
+    T1::Key: ExchangeData+Hashable,
+    T1::Val: ExchangeData+Hashable,
+    T1::Batch: BatchReader<T1::Key, T1::Val, G::Timestamp, isize>,
+    T1::Cursor: Cursor<T1::Key, T1::Val, G::Timestamp, isize>,
+{
+    fn top_k<F>(&self, order: F, limit: usize, offset: Option<usize>) -> Collection<G, (T1::Key, T1::Val), isize>
+    where F: Fn(&T1::Val, &T1::Val)->Ordering+Clone+'static {
+        // The buckets of the hierarchy are not the keys of the arrangement, which cannot be re-used.
+        self.as_collection(|k,v| (k.clone(), v.clone()))
+            .top_k(order, limit, offset)
+    }
+}
+
+/// Reduces buckets of the values of each key through each level of the hierarchy.
+///
+/// Each reduction retains the first `retain` records of its bucket, as ordered by `order`, and the
+/// result contains at least the first `retain` records of each key.
+pub(crate) fn hierarchy<G, K, V, F>(collection: &Collection<G, (K, V), isize>, order: F, retain: usize) -> Collection<G, (K, V), isize>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+Hashable,
+    V: ExchangeData+Hashable,
+    F: Fn(&V, &V)->Ordering+Clone+'static,
+{
+    let mut buckets =
+    collection
+        .map(|(key, val)| {
+            let hash: u64 = val.hashed().into();
+            ((key, hash), val)
+        });
+
+    for &bits in LEVELS.iter() {
+        buckets =
+        buckets
+            .map(move |((key, hash), val)| ((key, hash & ((1 << bits) - 1)), val))
+            .reduce_named("HierarchyBucket", first_records(order.clone(), 0, retain));
+    }
+
+    buckets.map(|((key, _hash), val)| (key, val))
+}
+
+/// Reduction logic that retains the first `limit` records after the first `offset`, as ordered by `order`.
+///
+/// The input values are presented in sorted order, and the stable sort by `order` preserves this order
+/// among records that `order` considers equal.
+pub(crate) fn first_records<K, V, F>(order: F, offset: usize, limit: usize) -> impl FnMut(&K, &[(&V, isize)], &mut Vec<(V, isize)>)
+where
+    V: Clone,
+    F: Fn(&V, &V)->Ordering,
+{
+    move |_key, input, output| {
+
+        let mut records = input.iter().filter(|&&(_, count)| count > 0).map(|&(val, count)| (val, count as usize)).collect::<Vec<_>>();
+        records.sort_by(|x, y| order(x.0, y.0));
+
+        let mut offset = offset;
+        let mut limit = limit;
+        for &(val, count) in records.iter() {
+            if limit == 0 { break; }
+            let skipped = ::std::cmp::min(offset, count);
+            offset -= skipped;
+            let taken = ::std::cmp::min(limit, count - skipped);
+            if taken > 0 {
+                output.push((val.clone(), taken as isize));
+                limit -= taken;
+            }
+        }
+    }
+}
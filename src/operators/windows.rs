@@ -0,0 +1,278 @@
+//! Assigns records to windows of time, retiring the windows as time advances.
+//!
+//! Window operators treat each update to a collection as an event at the time of the update, and
+//! present the update paired with each window containing that time. The pair is presented from the
+//! time of the update until the end of its window, at which point it is retracted. Consequently,
+//! computations on the windowed records, for example counts by window, reflect the windows that
+//! are open at each time, and the state downstream operators maintain for a window is released
+//! once the input frontier passes the end of the window.
+//!
+//! As updates are events, the retraction of a record at some time is presented as a negative record
+//! in the windows containing the time of the retraction, rather than in the windows that contained
+//! the record. Window operators are most natural for collections whose records are never retracted.
+//!
+//! Tumbling and hopping windows are determined by the time of each update alone. Session windows
+//! group updates by key, and a session extends for as long as updates for the key occur within a
+//! given gap of one another. A session's end is not known until it has closed, and sessions are
+//! identified by their key and the time at which they started.
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::{Add, Sub, Rem};
+
+use timely::order::TotalOrder;
+use timely::dataflow::Scope;
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::{Operator, Capability, Map};
+use timely::progress::Timestamp;
+
+use ::{Data, ExchangeData, Collection, AsCollection, Hashable};
+use ::difference::Abelian;
+use lattice::Lattice;
+use operators::temporal::TemporalFilter;
+
+/// Timestamps that windows can be formed from.
+///
+/// Windows require totally ordered timestamps whose least element is zero, and that support addition,
+/// subtraction and remainders, such as the unsigned integer types.
+pub trait WindowTime : Timestamp+Lattice+TotalOrder+Ord+Copy+Add<Output=Self>+Sub<Output=Self>+Rem<Output=Self> {
+    /// Adds `other` to `self`, producing the greatest time rather than overflowing.
+    ///
+    /// Windows and sessions whose ends would overflow instead end at the greatest time.
+    fn saturating_add(self, other: Self) -> Self;
+}
+
+macro_rules! implement_window_time {
+    ($($index_type:ty,)*) => (
+        $(
+            impl WindowTime for $index_type {
+                #[inline] fn saturating_add(self, other: Self) -> Self { <$index_type>::saturating_add(self, other) }
+            }
+        )*
+    )
+}
+
+implement_window_time!(usize, u128, u64, u32, u16, u8,);
+
+/// An interval of time, including `start` and excluding `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Abomonation, Serialize, Deserialize)]
+pub struct Window<T> {
+    /// The first time in the window.
+    pub start: T,
+    /// The first time after the window.
+    pub end: T,
+}
+
+impl<T: WindowTime> Window<T> {
+    /// Indicates whether `time` is in the window.
+    pub fn contains(&self, time: &T) -> bool {
+        &self.start <= time && time < &self.end
+    }
+}
+
+/// Extension trait for the `tumbling` and `hopping` differential dataflow methods.
+pub trait Windows<G: Scope, D: Data, R: Abelian> where G::Timestamp: WindowTime {
+    /// Pairs each update with the window of length `width` containing its time.
+    ///
+    /// Windows start at multiples of `width`, and each time is in exactly one window.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate timely;
+    /// extern crate differential_dataflow;
+    ///
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Count;
+    /// use differential_dataflow::operators::windows::Windows;
+    ///
+    /// fn main() {
+    ///     ::timely::example(|scope| {
+    ///         // count the records of each window of ten time units.
+    ///         scope.new_collection_from(1 .. 10u32).1
+    ///              .tumbling(10u64)
+    ///              .map(|(window, _data)| window)
+    ///              .count();
+    ///     });
+    /// }
+    /// ```
+    fn tumbling(&self, width: G::Timestamp) -> Collection<G, (Window<G::Timestamp>, D), R> {
+        self.hopping(width, width)
+    }
+
+    /// Pairs each update with each window of length `width` containing its time, for windows starting at multiples of `hop`.
+    ///
+    /// If `hop` is less than `width` windows overlap, and each time is in several windows. If `hop` is
+    /// greater than `width`, some times are in no windows, and updates at these times are discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate timely;
+    /// extern crate differential_dataflow;
+    ///
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Count;
+    /// use differential_dataflow::operators::windows::Windows;
+    ///
+    /// fn main() {
+    ///     ::timely::example(|scope| {
+    ///         // count the records of the last hour, each minute.
+    ///         scope.new_collection_from(1 .. 10u32).1
+    ///              .hopping(3600u64, 60)
+    ///              .map(|(window, _data)| window)
+    ///              .count();
+    ///     });
+    /// }
+    /// ```
+    fn hopping(&self, width: G::Timestamp, hop: G::Timestamp) -> Collection<G, (Window<G::Timestamp>, D), R>;
+}
+
+impl<G, D, R> Windows<G, D, R> for Collection<G, D, R>
+where
+    G: Scope,
+    G::Timestamp: WindowTime,
+    D: Data,
+    R: Abelian,
+{
+    fn hopping(&self, width: G::Timestamp, hop: G::Timestamp) -> Collection<G, (Window<G::Timestamp>, D), R> {
+
+        let minimum = <G::Timestamp as Timestamp>::minimum();
+        assert!(width > minimum && hop > minimum, "windows must have positive width and hop");
+
+        self.inner
+            .flat_map(move |(data, time, diff)| {
+                // Windows start at multiples of `hop`, and the latest window starts at or before `time`.
+                let mut start = time - time % hop;
+                let mut windows = Vec::new();
+                while time < start.saturating_add(width) {
+                    windows.push(((Window { start, end: start.saturating_add(width) }, data.clone()), time, diff.clone()));
+                    if start < hop { break; }
+                    start = start - hop;
+                }
+                windows
+            })
+            .as_collection()
+            .temporal_filter(|&(ref window, _)| (window.start, window.end))
+    }
+}
+
+/// Extension trait for the `sessions` differential dataflow method.
+pub trait Sessions<G: Scope, K: Data, V: Data, R: Abelian> where G::Timestamp: WindowTime {
+    /// Pairs each update with the session of its key containing its time.
+    ///
+    /// A session for a key starts with an update for the key not in an open session, and remains open
+    /// until `gap` has passed without further updates for the key. Records are presented as
+    /// `((key, start), val)` where `start` is the time at which the session started, once the input
+    /// frontier has passed the time of their update, and are retracted when the session closes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate timely;
+    /// extern crate differential_dataflow;
+    ///
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Count;
+    /// use differential_dataflow::operators::windows::Sessions;
+    ///
+    /// fn main() {
+    ///     ::timely::example(|scope| {
+    ///         // count the events of each open session of each user.
+    ///         scope.new_collection_from(vec![(0u32, 0u32), (1, 1)]).1
+    ///              .sessions(30u64)
+    ///              .map(|(session, _event)| session)
+    ///              .count();
+    ///     });
+    /// }
+    /// ```
+    fn sessions(&self, gap: G::Timestamp) -> Collection<G, ((K, G::Timestamp), V), R>;
+}
+
+impl<G, K, V, R> Sessions<G, K, V, R> for Collection<G, (K, V), R>
+where
+    G: Scope,
+    G::Timestamp: WindowTime,
+    K: ExchangeData+Hashable+::std::hash::Hash,
+    V: ExchangeData,
+    R: ExchangeData+Abelian,
+{
+    fn sessions(&self, gap: G::Timestamp) -> Collection<G, ((K, G::Timestamp), V), R> {
+
+        assert!(gap > <G::Timestamp as Timestamp>::minimum(), "sessions must have a positive gap");
+
+        let exchange = Exchange::new(move |update: &((K,V),G::Timestamp,R)| (update.0).0.hashed().into());
+        let mut buffer = Vec::new();
+
+        self.inner.unary_frontier(exchange, "Sessions", move |_,_| {
+
+            // Updates whose times are not yet complete, which may yet be preceded by other updates.
+            let mut pending = BTreeMap::<G::Timestamp, Vec<((K, V), R)>>::new();
+            // For each key with an open session, its start, its end, and the records presented in it.
+            let mut sessions = HashMap::<K, (G::Timestamp, G::Timestamp, Vec<(V, R)>)>::new();
+            // As times are totally ordered, one capability covers both pending updates and open sessions.
+            let mut capability: Option<Capability<G::Timestamp>> = None;
+
+            move |input, output| {
+
+                input.for_each(|cap, data| {
+                    data.swap(&mut buffer);
+                    for (data, time, diff) in buffer.drain(..) {
+                        pending.entry(time).or_insert_with(Vec::new).push((data, diff));
+                    }
+                    if capability.as_ref().map(|c| cap.time() < c.time()).unwrap_or(true) {
+                        capability = Some(cap.retain());
+                    }
+                });
+
+                if let Some(cap) = capability.as_mut() {
+
+                    let frontier = input.frontier().frontier();
+                    let mut session = output.session(cap);
+
+                    // Process updates whose times are complete, in order of their times.
+                    loop {
+                        let time = match pending.keys().next() {
+                            Some(time) if !frontier.less_equal(time) => *time,
+                            _ => break,
+                        };
+                        for ((key, val), diff) in pending.remove(&time).expect("time just found") {
+                            let open = sessions.get(&key).map(|&(_, end, _)| time < end).unwrap_or(false);
+                            if open {
+                                let state = sessions.get_mut(&key).expect("session just found");
+                                state.1 = ::std::cmp::max(state.1, time.saturating_add(gap));
+                                state.2.push((val.clone(), diff.clone()));
+                                session.give((((key, state.0), val), time, diff));
+                            }
+                            else {
+                                let state = (time, time.saturating_add(gap), vec![(val.clone(), diff.clone())]);
+                                if let Some((start, end, records)) = sessions.insert(key.clone(), state) {
+                                    for (val, diff) in records {
+                                        session.give((((key.clone(), start), val), end, diff.negate()));
+                                    }
+                                }
+                                session.give((((key, time), val), time, diff));
+                            }
+                        }
+                    }
+
+                    // Retract the records of sessions that can no longer be extended.
+                    let closed = sessions.iter().filter(|&(_, &(_, end, _))| frontier.iter().all(|time| end <= *time)).map(|(key, _)| key.clone()).collect::<Vec<_>>();
+                    for key in closed {
+                        let (start, end, records) = sessions.remove(&key).expect("session just found");
+                        for (val, diff) in records {
+                            session.give((((key.clone(), start), val), end, diff.negate()));
+                        }
+                    }
+                }
+
+                // Retain a capability for the least time of pending updates and ends of open sessions.
+                let least = pending.keys().next().cloned().into_iter().chain(sessions.values().map(|state| state.1)).min();
+                match least {
+                    Some(time) => { capability.as_mut().map(|cap| cap.downgrade(&time)); },
+                    None => { capability = None; },
+                }
+            }
+        })
+        .as_collection()
+    }
+}
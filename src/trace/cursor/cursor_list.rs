@@ -6,23 +6,30 @@ use super::Cursor;
 ///
 /// The `CursorList` tracks the indices of cursors with the minimum key, and the the indices of cursors with
 /// the minimum key and minimum value. It performs no clever management of these sets otherwise.
+///
+/// When seeking a key, cursors that report they cannot contain the key (through `may_contain`) are not
+/// sought, but are marked as pending. If some other cursor has the key, pending cursors are known to be
+/// beyond it and can be ignored until the list moves past the key. Otherwise, they are sought at once.
 #[derive(Debug)]
 pub struct CursorList<K, V, T, R, C: Cursor<K, V, T, R>> {
     _phantom: ::std::marker::PhantomData<(K, V, T, R)>,
     cursors: Vec<C>,
     min_key: Vec<usize>,
     min_val: Vec<usize>,
+    pending: Vec<bool>,
 }
 
 impl<K, V, T, R, C: Cursor<K, V, T, R>> CursorList<K, V, T, R, C> where K: Ord, V: Ord {
     /// Creates a new cursor list from pre-existing cursors.
     pub fn new(cursors: Vec<C>, storage: &[C::Storage]) -> Self {
 
+        let pending = vec![false; cursors.len()];
         let mut result = CursorList {
             _phantom: ::std::marker::PhantomData,
             cursors,
             min_key: Vec::new(),
             min_val: Vec::new(),
+            pending,
         };
 
         result.minimize_keys(storage);
@@ -45,6 +52,7 @@ impl<K, V, T, R, C: Cursor<K, V, T, R>> CursorList<K, V, T, R, C> where K: Ord,
         // Determine the index of the cursor with minimum key.
         let mut min_key_opt: Option<&K> = None;
         for (index, cursor) in self.cursors.iter().enumerate() {
+            if self.pending[index] { continue; }
             let key = cursor.get_key(&storage[index]);
             if key.is_some() {
                 if min_key_opt.is_none() || key.lt(&min_key_opt) {
@@ -60,6 +68,16 @@ impl<K, V, T, R, C: Cursor<K, V, T, R>> CursorList<K, V, T, R, C> where K: Ord,
         self.minimize_vals(storage);
     }
 
+    // Seeks pending cursors to `key`, the key most recently sought.
+    fn resolve_pending(&mut self, storage: &[C::Storage], key: &K) {
+        for index in 0 .. self.cursors.len() {
+            if self.pending[index] {
+                self.cursors[index].seek_key(&storage[index], key);
+                self.pending[index] = false;
+            }
+        }
+    }
+
     // Initialize min_val with the indices of minimum key cursors with the minimum value.
     //
     // This method scans the current values of cursor with minimum keys, and tracks the
@@ -124,6 +142,11 @@ where
     // key methods
     #[inline]
     fn step_key(&mut self, storage: &Self::Storage) {
+        // Pending cursors are only retained while positioned at their sought key.
+        if self.pending.iter().any(|&p| p) {
+            let key = self.cursors[self.min_key[0]].key(&storage[self.min_key[0]]);
+            self.resolve_pending(storage, key);
+        }
         for &index in self.min_key.iter() {
             self.cursors[index].step_key(&storage[index]);
         }
@@ -132,9 +155,24 @@ where
     #[inline]
     fn seek_key(&mut self, storage: &Self::Storage, key: &K) {
         for index in 0 .. self.cursors.len() {
-            self.cursors[index].seek_key(&storage[index], key);
+            if self.cursors[index].may_contain(&storage[index], key) {
+                self.cursors[index].seek_key(&storage[index], key);
+                self.pending[index] = false;
+            }
+            else {
+                self.pending[index] = true;
+            }
         }
         self.minimize_keys(storage);
+        // Unless positioned at `key`, pending cursors may hold lesser keys than those found.
+        if self.pending.iter().any(|&p| p) && self.get_key(storage) != Some(key) {
+            self.resolve_pending(storage, key);
+            self.minimize_keys(storage);
+        }
+    }
+    #[inline]
+    fn may_contain(&self, storage: &Self::Storage, key: &K) -> bool {
+        (0 .. self.cursors.len()).any(|index| self.cursors[index].may_contain(&storage[index], key))
     }
 
     // value methods
@@ -158,6 +196,7 @@ where
     fn rewind_keys(&mut self, storage: &Self::Storage) {
         for index in 0 .. self.cursors.len() {
             self.cursors[index].rewind_keys(&storage[index]);
+            self.pending[index] = false;
         }
         self.minimize_keys(storage);
     }
@@ -48,6 +48,11 @@ pub trait Cursor<K, V, T, R> {
     fn step_key(&mut self, storage: &Self::Storage);
     /// Advances the cursor to the specified key.
     fn seek_key(&mut self, storage: &Self::Storage, key: &K);
+    /// False only if the cursor's storage certainly contains no updates for `key`.
+    ///
+    /// Cursors over summarized batches can rule out keys without seeking, which allows
+    /// combinations of cursors to avoid seeking in cursors that cannot contain a key.
+    fn may_contain(&self, _storage: &Self::Storage, _key: &K) -> bool { true }
 
     /// Advances the cursor to the next value.
     fn step_val(&mut self, storage: &Self::Storage);
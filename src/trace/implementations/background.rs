@@ -0,0 +1,274 @@
+//! Batches whose large merges are performed by a pool of helper threads.
+//!
+//! A `BackgroundBatch<B>` wraps a batch of type `B` in an `Arc`, and is the thread-safe counterpart
+//! of `Rc<B>`. Its merger hands merges of sufficiently many updates to a shared pool of helper threads,
+//! rather than performing the work on the worker thread as fuel is applied. Until the helper thread
+//! completes the merge, the merger consumes all fuel offered to it and reports that it is incomplete;
+//! once complete, the next application of fuel installs the result.
+//!
+//! The merger only waits for a helper thread when offered at least as much fuel as the merge has input
+//! updates, which the spine does when it must complete a merge immediately. In other cases, the worker
+//! thread continues with other work, avoiding the latency of large merges.
+//!
+//! The pool is shared by all workers in the process, and is started on first use with the number of
+//! threads indicated by `set_merge_threads` (by default, one).
+
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Sender, Receiver, TryRecvError};
+
+use timely::progress::{Antichain, frontier::AntichainRef};
+
+use trace::{Batch, BatchReader, Batcher, Builder, Merger, Cursor, Description};
+
+use super::spine_fueled::Spine;
+use super::ord::{OrdValBatch, OrdKeyBatch};
+
+/// A trace implementation using a spine of ordered lists, merged by helper threads.
+pub type OrdValSpineBackground<K, V, T, R> = Spine<K, V, T, R, BackgroundBatch<OrdValBatch<K, V, T, R>>>;
+
+/// A trace implementation for empty values using a spine of ordered lists, merged by helper threads.
+pub type OrdKeySpineBackground<K, T, R> = Spine<K, (), T, R, BackgroundBatch<OrdKeyBatch<K, T, R>>>;
+
+/// The number of helper threads to start, read when the pool is first used.
+static MERGE_THREADS: AtomicUsize = AtomicUsize::new(1);
+/// Merges with fewer input updates than this are performed on the worker thread.
+static MERGE_THRESHOLD: AtomicUsize = AtomicUsize::new(1 << 16);
+
+/// Sets the number of helper threads, which only has an effect before the first background merge.
+pub fn set_merge_threads(threads: usize) {
+    MERGE_THREADS.store(::std::cmp::max(threads, 1), Ordering::SeqCst);
+}
+
+/// Sets the number of input updates at which merges are handed to helper threads.
+pub fn set_merge_threshold(updates: usize) {
+    MERGE_THRESHOLD.store(updates, Ordering::SeqCst);
+}
+
+type Job = Box<dyn FnOnce()+Send+'static>;
+
+/// The queue of merges for the helper threads, started on first use.
+static MERGE_POOL: Mutex<Option<Sender<Job>>> = Mutex::new(None);
+
+/// Hands `job` to the helper threads, starting them if necessary.
+fn spawn(job: Job) {
+    let mut pool = MERGE_POOL.lock().expect("merge pool poisoned");
+    let sender = pool.get_or_insert_with(|| {
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for index in 0 .. MERGE_THREADS.load(Ordering::SeqCst) {
+            let receiver = receiver.clone();
+            ::std::thread::Builder::new()
+                .name(format!("differential-merge-{}", index))
+                .spawn(move || {
+                    loop {
+                        // The lock is released before the job runs.
+                        let job = receiver.lock().expect("merge pool poisoned").recv();
+                        match job {
+                            Ok(job) => job(),
+                            Err(_) => break,
+                        }
+                    }
+                })
+                .expect("failed to start merge thread");
+        }
+        sender
+    });
+    sender.send(job).expect("merge threads have exited");
+}
+
+/// A batch shared by reference counting, whose large merges are performed by helper threads.
+pub struct BackgroundBatch<B> {
+    batch: Arc<B>,
+}
+
+impl<B> BackgroundBatch<B> {
+    /// Wraps a batch.
+    pub fn new(batch: B) -> Self {
+        BackgroundBatch { batch: Arc::new(batch) }
+    }
+}
+
+impl<B> Clone for BackgroundBatch<B> {
+    fn clone(&self) -> Self {
+        BackgroundBatch { batch: self.batch.clone() }
+    }
+}
+
+impl<B> Deref for BackgroundBatch<B> {
+    type Target = B;
+    fn deref(&self) -> &B { &self.batch }
+}
+
+impl<K, V, T, R, B: BatchReader<K,V,T,R>> BatchReader<K,V,T,R> for BackgroundBatch<B> {
+
+    /// The type used to enumerate the batch's contents.
+    type Cursor = BackgroundBatchCursor<K, V, T, R, B>;
+    /// Acquires a cursor to the batch's contents.
+    fn cursor(&self) -> Self::Cursor {
+        BackgroundBatchCursor::new((&**self).cursor())
+    }
+
+    /// The number of updates in the batch.
+    fn len(&self) -> usize { (&**self).len() }
+    /// Describes the times of the updates in the batch.
+    fn description(&self) -> &Description<T> { (&**self).description() }
+    /// Reports the heap allocations of the batch.
+    fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) { (&**self).heap_size(callback) }
+}
+
+/// Wrapper to provide cursor to nested scope.
+pub struct BackgroundBatchCursor<K, V, T, R, B: BatchReader<K, V, T, R>> {
+    phantom: ::std::marker::PhantomData<(K, V, T, R)>,
+    cursor: B::Cursor,
+}
+
+impl<K, V, T, R, B: BatchReader<K, V, T, R>> BackgroundBatchCursor<K, V, T, R, B> {
+    fn new(cursor: B::Cursor) -> Self {
+        BackgroundBatchCursor {
+            cursor,
+            phantom: ::std::marker::PhantomData,
+        }
+    }
+}
+
+impl<K, V, T, R, B: BatchReader<K, V, T, R>> Cursor<K, V, T, R> for BackgroundBatchCursor<K, V, T, R, B> {
+
+    type Storage = BackgroundBatch<B>;
+
+    #[inline] fn key_valid(&self, storage: &Self::Storage) -> bool { self.cursor.key_valid(storage) }
+    #[inline] fn val_valid(&self, storage: &Self::Storage) -> bool { self.cursor.val_valid(storage) }
+
+    #[inline] fn key<'a>(&self, storage: &'a Self::Storage) -> &'a K { self.cursor.key(storage) }
+    #[inline] fn val<'a>(&self, storage: &'a Self::Storage) -> &'a V { self.cursor.val(storage) }
+
+    #[inline]
+    fn map_times<L: FnMut(&T, &R)>(&mut self, storage: &Self::Storage, logic: L) {
+        self.cursor.map_times(storage, logic)
+    }
+
+    #[inline] fn step_key(&mut self, storage: &Self::Storage) { self.cursor.step_key(storage) }
+    #[inline] fn seek_key(&mut self, storage: &Self::Storage, key: &K) { self.cursor.seek_key(storage, key) }
+    #[inline] fn may_contain(&self, storage: &Self::Storage, key: &K) -> bool { self.cursor.may_contain(storage, key) }
+
+    #[inline] fn step_val(&mut self, storage: &Self::Storage) { self.cursor.step_val(storage) }
+    #[inline] fn seek_val(&mut self, storage: &Self::Storage, val: &V) { self.cursor.seek_val(storage, val) }
+
+    #[inline] fn rewind_keys(&mut self, storage: &Self::Storage) { self.cursor.rewind_keys(storage) }
+    #[inline] fn rewind_vals(&mut self, storage: &Self::Storage) { self.cursor.rewind_vals(storage) }
+}
+
+/// An immutable collection of updates.
+impl<K, V, T, R, B> Batch<K, V, T, R> for BackgroundBatch<B>
+where
+    B: Batch<K, V, T, R>+Send+Sync+'static,
+    T: Clone+Send+'static,
+{
+    type Batcher = BackgroundBatcher<K, V, T, R, B>;
+    type Builder = BackgroundBuilder<K, V, T, R, B>;
+    type Merger = BackgroundMerger<K, V, T, R, B>;
+}
+
+/// Wrapper type for batching background-merged batches.
+pub struct BackgroundBatcher<K, V, T, R, B: Batch<K,V,T,R>> { batcher: B::Batcher }
+
+/// Functionality for collecting and batching updates.
+impl<K, V, T, R, B> Batcher<K, V, T, R, BackgroundBatch<B>> for BackgroundBatcher<K, V, T, R, B>
+where
+    B: Batch<K, V, T, R>+Send+Sync+'static,
+    T: Clone+Send+'static,
+{
+    fn new() -> Self { BackgroundBatcher { batcher: <B::Batcher as Batcher<K,V,T,R,B>>::new() } }
+    fn push_batch(&mut self, batch: &mut Vec<((K, V), T, R)>) { self.batcher.push_batch(batch) }
+    fn seal(&mut self, upper: Antichain<T>) -> BackgroundBatch<B> { BackgroundBatch::new(self.batcher.seal(upper)) }
+    fn frontier(&mut self) -> AntichainRef<T> { self.batcher.frontier() }
+}
+
+/// Wrapper type for building background-merged batches.
+pub struct BackgroundBuilder<K, V, T, R, B: Batch<K,V,T,R>> { builder: B::Builder }
+
+/// Functionality for building batches from ordered update sequences.
+impl<K, V, T, R, B> Builder<K, V, T, R, BackgroundBatch<B>> for BackgroundBuilder<K, V, T, R, B>
+where
+    B: Batch<K, V, T, R>+Send+Sync+'static,
+    T: Clone+Send+'static,
+{
+    fn new() -> Self { BackgroundBuilder { builder: <B::Builder as Builder<K,V,T,R,B>>::new() } }
+    fn with_capacity(cap: usize) -> Self { BackgroundBuilder { builder: <B::Builder as Builder<K,V,T,R,B>>::with_capacity(cap) } }
+    fn push(&mut self, element: (K, V, T, R)) { self.builder.push(element) }
+    fn done(self, lower: Antichain<T>, upper: Antichain<T>, since: Antichain<T>) -> BackgroundBatch<B> {
+        BackgroundBatch::new(self.builder.done(lower, upper, since))
+    }
+}
+
+/// Wrapper type for merging background-merged batches.
+pub struct BackgroundMerger<K, V, T, R, B: Batch<K,V,T,R>> {
+    state: MergeWork<K, V, T, R, B>,
+}
+
+enum MergeWork<K, V, T, R, B: Batch<K,V,T,R>> {
+    /// A merge performed on the worker thread.
+    Inline(B::Merger),
+    /// A merge performed by a helper thread, with the number of input updates and the result once received.
+    Remote(usize, Receiver<B>, Option<B>),
+}
+
+/// Represents a merge in progress.
+impl<K, V, T, R, B> Merger<K, V, T, R, BackgroundBatch<B>> for BackgroundMerger<K, V, T, R, B>
+where
+    B: Batch<K, V, T, R>+Send+Sync+'static,
+    T: Clone+Send+'static,
+{
+    fn new(source1: &BackgroundBatch<B>, source2: &BackgroundBatch<B>, compaction_frontier: Option<AntichainRef<T>>) -> Self {
+        let length = source1.len() + source2.len();
+        let state =
+        if length < MERGE_THRESHOLD.load(Ordering::SeqCst) {
+            MergeWork::Inline(B::begin_merge(source1, source2, compaction_frontier))
+        }
+        else {
+            let batch1 = source1.batch.clone();
+            let batch2 = source2.batch.clone();
+            let frontier = compaction_frontier.map(|frontier| frontier.to_owned());
+            let (sender, receiver) = channel();
+            spawn(Box::new(move || {
+                let mut merger = B::begin_merge(&batch1, &batch2, frontier.as_ref().map(|f| f.borrow()));
+                let mut fuel = isize::max_value();
+                merger.work(&batch1, &batch2, &mut fuel);
+                // The receiver may have been dropped, if the trace no longer requires the merge.
+                let _ = sender.send(merger.done());
+            }));
+            MergeWork::Remote(length, receiver, None)
+        };
+        BackgroundMerger { state }
+    }
+    fn work(&mut self, source1: &BackgroundBatch<B>, source2: &BackgroundBatch<B>, fuel: &mut isize) {
+        match &mut self.state {
+            MergeWork::Inline(merger) => merger.work(source1, source2, fuel),
+            MergeWork::Remote(length, receiver, result) => {
+                if result.is_none() {
+                    // Wait for the result only if the merge would have completed on the worker thread.
+                    if *fuel >= *length as isize {
+                        *result = Some(receiver.recv().expect("merge thread failed"));
+                    }
+                    else {
+                        match receiver.try_recv() {
+                            Ok(batch) => { *result = Some(batch); },
+                            Err(TryRecvError::Empty) => { *fuel = 0; },
+                            Err(TryRecvError::Disconnected) => { panic!("merge thread failed"); },
+                        }
+                    }
+                }
+            }
+        }
+    }
+    fn done(self) -> BackgroundBatch<B> {
+        match self.state {
+            MergeWork::Inline(merger) => BackgroundBatch::new(merger.done()),
+            MergeWork::Remote(_, receiver, result) => {
+                let batch = result.unwrap_or_else(|| receiver.recv().expect("merge thread failed"));
+                BackgroundBatch::new(batch)
+            }
+        }
+    }
+}
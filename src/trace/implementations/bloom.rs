@@ -0,0 +1,68 @@
+//! Summaries of the keys of a batch, used to avoid seeking in batches that cannot contain a key.
+//!
+//! Batches built by `OrdValBuilder` and `OrdValMerger` record the least and greatest of their keys,
+//! and a `KeyFilter` built from all of their keys. The default filter, `()`, records nothing, and a
+//! `BloomFilter` records a Bloom filter of the hashes of the keys. Cursors report these summaries
+//! through `Cursor::may_contain`, which `CursorList` uses to avoid seeking in batches whose summaries
+//! exclude a key.
+
+use ::Hashable;
+
+/// A summary of a set of keys, which can rule out the presence of a key.
+pub trait KeyFilter<K> {
+    /// Summarizes `keys`, which are sorted and distinct.
+    fn build(keys: &[K]) -> Self;
+    /// False only if `key` is certainly not among the summarized keys.
+    fn may_contain(&self, key: &K) -> bool;
+    /// The number of bytes allocated by the summary.
+    fn heap_size(&self) -> usize { 0 }
+}
+
+/// The trivial summary, which rules out no keys.
+impl<K> KeyFilter<K> for () {
+    fn build(_keys: &[K]) -> Self { }
+    fn may_contain(&self, _key: &K) -> bool { true }
+}
+
+/// The number of bits maintained for each key, yielding a false positive rate of about one percent.
+const BITS_PER_KEY: usize = 10;
+/// The number of bits set for each key.
+const HASHES: u64 = 7;
+
+/// A Bloom filter of the hashes of a set of keys.
+#[derive(Debug, Clone, Abomonation)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+}
+
+impl BloomFilter {
+    /// Indicates the bits for `hash` among `words` words, derived by double hashing.
+    #[inline]
+    fn positions(words: usize, hash: u64) -> impl Iterator<Item=usize> {
+        let total = (words * 64) as u64;
+        let step = hash.rotate_left(32) | 1;
+        (0 .. HASHES).map(move |index| (hash.wrapping_add(index.wrapping_mul(step)) % total) as usize)
+    }
+}
+
+impl<K: Hashable> KeyFilter<K> for BloomFilter {
+    fn build(keys: &[K]) -> Self {
+        let words = (keys.len() * BITS_PER_KEY + 63) / 64;
+        let mut filter = BloomFilter { bits: vec![0; ::std::cmp::max(words, 1)] };
+        for key in keys.iter() {
+            let hash: u64 = key.hashed().into();
+            for position in BloomFilter::positions(filter.bits.len(), hash) {
+                filter.bits[position / 64] |= 1 << (position % 64);
+            }
+        }
+        filter
+    }
+    #[inline]
+    fn may_contain(&self, key: &K) -> bool {
+        let hash: u64 = key.hashed().into();
+        BloomFilter::positions(self.bits.len(), hash).all(|position| self.bits[position / 64] & (1 << (position % 64)) != 0)
+    }
+    fn heap_size(&self) -> usize {
+        self.bits.capacity() * ::std::mem::size_of::<u64>()
+    }
+}
@@ -0,0 +1,815 @@
+//! Trace and batch implementations based on columnar, region-allocated storage.
+//!
+//! The types and type aliases in this module start with either
+//!
+//! * `ColVal`: Collections whose data have the form `(key, val)` where `key` is ordered.
+//! * `ColKey`: Collections whose data have the form `key` where `key` is ordered.
+//!
+//! These batches organize their data like `OrdValBatch` and `OrdKeyBatch`, sorted by key, then val,
+//! then time, but each of keys, values, and `(time, diff)` pairs are held in a `ColumnStack`. Fixed
+//! width types are stored in flat arrays, and types with owned allocations (e.g. `String` and `Vec<T>`)
+//! have their contents copied into large shared regions. This avoids a heap allocation per record,
+//! which can be a substantial fraction of the memory footprint of an arrangement.
+//!
+//! Batches are immutable once formed, which means that the region-backed records are only ever
+//! exposed as shared references through cursors, and are released when the batch is dropped.
+
+use std::rc::Rc;
+use std::marker::PhantomData;
+
+use timely::progress::{Antichain, frontier::AntichainRef};
+
+use ::difference::Semigroup;
+use lattice::Lattice;
+
+use trace::layers::advance;
+use trace::{Batch, BatchReader, Builder, Merger, Cursor};
+use trace::description::Description;
+
+use super::spine_fueled::Spine;
+use super::merge_batcher::MergeBatcher;
+use super::columnation::{Columnation, ColumnStack};
+
+/// A trace implementation using a spine of columnar ordered lists.
+pub type ColValSpine<K, V, T, R> = Spine<K, V, T, R, Rc<ColValBatch<K, V, T, R>>>;
+
+/// A trace implementation for empty values using a spine of columnar ordered lists.
+pub type ColKeySpine<K, T, R> = Spine<K, (), T, R, Rc<ColKeyBatch<K, T, R>>>;
+
+
+/// Columnar storage for `(key, val, time, diff)` updates.
+///
+/// The values for `keys[i]` are `vals[keys_offs[i] .. keys_offs[i+1]]`, and the updates for
+/// `vals[j]` are `updates[vals_offs[j] .. vals_offs[j+1]]`.
+#[derive(Debug)]
+pub struct ColValLayer<K, V, T, R>
+where
+    K: Columnation,
+    V: Columnation,
+    T: Columnation,
+    R: Columnation,
+{
+    /// Ordered distinct keys.
+    pub keys: ColumnStack<K>,
+    /// Offsets into `vals` for each key, with a leading zero.
+    pub keys_offs: Vec<usize>,
+    /// Ordered values, distinct for each key.
+    pub vals: ColumnStack<V>,
+    /// Offsets into `updates` for each value, with a leading zero.
+    pub vals_offs: Vec<usize>,
+    /// Ordered `(time, diff)` updates, consolidated for each value.
+    pub updates: ColumnStack<(T, R)>,
+}
+
+impl<K, V, T, R> ColValLayer<K, V, T, R>
+where
+    K: Columnation,
+    V: Columnation,
+    T: Columnation,
+    R: Columnation,
+{
+    fn with_capacity(keys: usize, vals: usize, updates: usize) -> Self {
+        let mut keys_offs = Vec::with_capacity(keys + 1);
+        keys_offs.push(0);
+        let mut vals_offs = Vec::with_capacity(vals + 1);
+        vals_offs.push(0);
+        ColValLayer {
+            keys: ColumnStack::with_capacity(keys),
+            keys_offs,
+            vals: ColumnStack::with_capacity(vals),
+            vals_offs,
+            updates: ColumnStack::with_capacity(updates),
+        }
+    }
+    /// The range of `vals` associated with the key at `index`.
+    #[inline]
+    pub fn values_for_key(&self, index: usize) -> (usize, usize) {
+        (self.keys_offs[index], self.keys_offs[index+1])
+    }
+    /// The range of `updates` associated with the value at `index`.
+    #[inline]
+    pub fn updates_for_value(&self, index: usize) -> (usize, usize) {
+        (self.vals_offs[index], self.vals_offs[index+1])
+    }
+}
+
+/// An immutable collection of update tuples, from a contiguous interval of logical times.
+#[derive(Debug)]
+pub struct ColValBatch<K, V, T, R>
+where
+    K: Columnation,
+    V: Columnation,
+    T: Columnation,
+    R: Columnation,
+{
+    /// Where all the dataz is.
+    pub layer: ColValLayer<K, V, T, R>,
+    /// Description of the update times this layer represents.
+    pub desc: Description<T>,
+}
+
+impl<K, V, T, R> BatchReader<K, V, T, R> for ColValBatch<K, V, T, R>
+where
+    K: Columnation+Ord+Clone+'static,
+    V: Columnation+Ord+Clone+'static,
+    T: Columnation+Lattice+Ord+Clone+'static,
+    R: Columnation+Semigroup,
+{
+    type Cursor = ColValCursor<K, V, T, R>;
+    fn cursor(&self) -> Self::Cursor { ColValCursor { key_cursor: 0, val_cursor: 0, phantom: PhantomData } }
+    fn len(&self) -> usize { self.layer.updates.len() }
+    fn description(&self) -> &Description<T> { &self.desc }
+    fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) {
+        let size_of_usize = ::std::mem::size_of::<usize>();
+        self.layer.keys.heap_size(callback);
+        callback(self.layer.keys_offs.len() * size_of_usize, self.layer.keys_offs.capacity() * size_of_usize);
+        self.layer.vals.heap_size(callback);
+        callback(self.layer.vals_offs.len() * size_of_usize, self.layer.vals_offs.capacity() * size_of_usize);
+        self.layer.updates.heap_size(callback);
+    }
+}
+
+impl<K, V, T, R> Batch<K, V, T, R> for ColValBatch<K, V, T, R>
+where
+    K: Columnation+Ord+Clone+'static,
+    V: Columnation+Ord+Clone+'static,
+    T: Columnation+Lattice+timely::progress::Timestamp+Ord+Clone+::std::fmt::Debug+'static,
+    R: Columnation+Semigroup,
+{
+    type Batcher = MergeBatcher<K, V, T, R, Self>;
+    type Builder = ColValBuilder<K, V, T, R>;
+    type Merger = ColValMerger<K, V, T, R>;
+
+    fn begin_merge(&self, other: &Self, compaction_frontier: Option<AntichainRef<T>>) -> Self::Merger {
+        ColValMerger::new(self, other, compaction_frontier)
+    }
+}
+
+/// State for an in-progress merge.
+pub struct ColValMerger<K, V, T, R>
+where
+    K: Columnation+Ord+Clone+'static,
+    V: Columnation+Ord+Clone+'static,
+    T: Columnation+Lattice+Ord+Clone+::std::fmt::Debug+'static,
+    R: Columnation+Semigroup,
+{
+    // position in the keys of the first batch.
+    key_cursor1: usize,
+    // position in the keys of the second batch.
+    key_cursor2: usize,
+    // result that we are currently assembling.
+    result: ColValLayer<K, V, T, R>,
+    description: Description<T>,
+    should_compact: bool,
+    // staging area for the updates of a single value.
+    update_stash: Vec<(T, R)>,
+}
+
+impl<K, V, T, R> Merger<K, V, T, R, ColValBatch<K, V, T, R>> for ColValMerger<K, V, T, R>
+where
+    K: Columnation+Ord+Clone+'static,
+    V: Columnation+Ord+Clone+'static,
+    T: Columnation+Lattice+timely::progress::Timestamp+Ord+Clone+::std::fmt::Debug+'static,
+    R: Columnation+Semigroup,
+{
+    fn new(batch1: &ColValBatch<K, V, T, R>, batch2: &ColValBatch<K, V, T, R>, compaction_frontier: Option<AntichainRef<T>>) -> Self {
+
+        assert!(batch1.upper() == batch2.lower());
+
+        let mut since = batch1.description().since().join(batch2.description().since());
+        if let Some(compaction_frontier) = compaction_frontier {
+            since = since.join(&compaction_frontier.to_owned());
+        }
+
+        let description = Description::new(batch1.lower().clone(), batch2.upper().clone(), since);
+
+        ColValMerger {
+            key_cursor1: 0,
+            key_cursor2: 0,
+            result: ColValLayer::with_capacity(
+                batch1.layer.keys.len() + batch2.layer.keys.len(),
+                batch1.layer.vals.len() + batch2.layer.vals.len(),
+                batch1.layer.updates.len() + batch2.layer.updates.len(),
+            ),
+            description: description,
+            should_compact: compaction_frontier.is_some(),
+            update_stash: Vec::new(),
+        }
+    }
+    fn done(self) -> ColValBatch<K, V, T, R> {
+
+        assert!(self.result.keys_offs.len() == self.result.keys.len() + 1);
+        assert!(self.result.vals_offs.len() == self.result.vals.len() + 1);
+
+        ColValBatch {
+            layer: self.result,
+            desc: self.description,
+        }
+    }
+    fn work(&mut self, source1: &ColValBatch<K,V,T,R>, source2: &ColValBatch<K,V,T,R>, fuel: &mut isize) {
+
+        let starting_updates = self.result.updates.len();
+        let mut effort = 0isize;
+
+        // while both mergees are still active
+        while self.key_cursor1 < source1.layer.keys.len() && self.key_cursor2 < source2.layer.keys.len() && effort < *fuel {
+            self.merge_key(&source1.layer, &source2.layer);
+            effort = (self.result.updates.len() - starting_updates) as isize;
+        }
+
+        // Merging is complete; only copying remains.
+        while self.key_cursor1 < source1.layer.keys.len() && effort < *fuel {
+            self.copy_key(&source1.layer, self.key_cursor1);
+            self.key_cursor1 += 1;
+            effort = (self.result.updates.len() - starting_updates) as isize;
+        }
+        while self.key_cursor2 < source2.layer.keys.len() && effort < *fuel {
+            self.copy_key(&source2.layer, self.key_cursor2);
+            self.key_cursor2 += 1;
+            effort = (self.result.updates.len() - starting_updates) as isize;
+        }
+
+        *fuel -= effort;
+    }
+}
+
+impl<K, V, T, R> ColValMerger<K, V, T, R>
+where
+    K: Columnation+Ord+Clone+'static,
+    V: Columnation+Ord+Clone+'static,
+    T: Columnation+Lattice+Ord+Clone+::std::fmt::Debug+'static,
+    R: Columnation+Semigroup,
+{
+    /// Merges the keys at the two cursors, advancing one or both.
+    fn merge_key(&mut self, source1: &ColValLayer<K,V,T,R>, source2: &ColValLayer<K,V,T,R>) {
+        use ::std::cmp::Ordering;
+        match source1.keys[self.key_cursor1].cmp(&source2.keys[self.key_cursor2]) {
+            Ordering::Less => {
+                self.copy_key(source1, self.key_cursor1);
+                self.key_cursor1 += 1;
+            },
+            Ordering::Equal => {
+                let (mut lower1, upper1) = source1.values_for_key(self.key_cursor1);
+                let (mut lower2, upper2) = source2.values_for_key(self.key_cursor2);
+                let initial_vals = self.result.vals.len();
+                while lower1 < upper1 && lower2 < upper2 {
+                    match source1.vals[lower1].cmp(&source2.vals[lower2]) {
+                        Ordering::Less => {
+                            self.copy_val(source1, lower1);
+                            lower1 += 1;
+                        },
+                        Ordering::Equal => {
+                            self.stash_updates_for_val(source1, lower1);
+                            self.stash_updates_for_val(source2, lower2);
+                            if self.consolidate_updates() {
+                                self.result.vals.copy(&source1.vals[lower1]);
+                            }
+                            lower1 += 1;
+                            lower2 += 1;
+                        },
+                        Ordering::Greater => {
+                            self.copy_val(source2, lower2);
+                            lower2 += 1;
+                        },
+                    }
+                }
+                while lower1 < upper1 {
+                    self.copy_val(source1, lower1);
+                    lower1 += 1;
+                }
+                while lower2 < upper2 {
+                    self.copy_val(source2, lower2);
+                    lower2 += 1;
+                }
+                if self.result.vals.len() > initial_vals {
+                    self.result.keys.copy(&source1.keys[self.key_cursor1]);
+                    self.result.keys_offs.push(self.result.vals.len());
+                }
+                self.key_cursor1 += 1;
+                self.key_cursor2 += 1;
+            },
+            Ordering::Greater => {
+                self.copy_key(source2, self.key_cursor2);
+                self.key_cursor2 += 1;
+            },
+        }
+    }
+
+    /// Copies the key at `index` along with its values and updates, if any remain after compaction.
+    fn copy_key(&mut self, source: &ColValLayer<K,V,T,R>, index: usize) {
+        let initial_vals = self.result.vals.len();
+        let (lower, upper) = source.values_for_key(index);
+        for val_index in lower .. upper {
+            self.copy_val(source, val_index);
+        }
+        if self.result.vals.len() > initial_vals {
+            self.result.keys.copy(&source.keys[index]);
+            self.result.keys_offs.push(self.result.vals.len());
+        }
+    }
+
+    /// Copies the value at `index` along with its updates, if any remain after compaction.
+    fn copy_val(&mut self, source: &ColValLayer<K,V,T,R>, index: usize) {
+        self.stash_updates_for_val(source, index);
+        if self.consolidate_updates() {
+            self.result.vals.copy(&source.vals[index]);
+        }
+    }
+
+    /// Clones the updates of the value at `index` into `self.update_stash`.
+    fn stash_updates_for_val(&mut self, source: &ColValLayer<K,V,T,R>, index: usize) {
+        let (lower, upper) = source.updates_for_value(index);
+        self.update_stash.extend(source.updates[lower .. upper].iter().cloned());
+    }
+
+    /// Advances and consolidates stashed updates, moving them into the result.
+    ///
+    /// Returns `true` if any updates remain, in which case the caller must record the value.
+    fn consolidate_updates(&mut self) -> bool {
+        if self.should_compact {
+            for (time, _) in self.update_stash.iter_mut() {
+                time.advance_by(self.description.since().borrow());
+            }
+        }
+        crate::consolidation::consolidate(&mut self.update_stash);
+        if !self.update_stash.is_empty() {
+            for update in self.update_stash.iter() {
+                self.result.updates.copy(update);
+            }
+            self.update_stash.clear();
+            self.result.vals_offs.push(self.result.updates.len());
+            true
+        }
+        else {
+            false
+        }
+    }
+}
+
+/// A cursor for navigating a single layer.
+#[derive(Debug)]
+pub struct ColValCursor<K, V, T, R> {
+    key_cursor: usize,
+    val_cursor: usize,
+    phantom: PhantomData<(K, V, T, R)>,
+}
+
+impl<K, V, T, R> Cursor<K, V, T, R> for ColValCursor<K, V, T, R>
+where
+    K: Columnation+Ord+Clone,
+    V: Columnation+Ord+Clone,
+    T: Columnation+Lattice+Ord+Clone,
+    R: Columnation+Semigroup,
+{
+    type Storage = ColValBatch<K, V, T, R>;
+
+    fn key<'a>(&self, storage: &'a Self::Storage) -> &'a K { &storage.layer.keys[self.key_cursor] }
+    fn val<'a>(&self, storage: &'a Self::Storage) -> &'a V { &storage.layer.vals[self.val_cursor] }
+    fn map_times<L: FnMut(&T, &R)>(&mut self, storage: &Self::Storage, mut logic: L) {
+        let (lower, upper) = storage.layer.updates_for_value(self.val_cursor);
+        for (time, diff) in storage.layer.updates[lower .. upper].iter() {
+            logic(time, diff);
+        }
+    }
+    fn key_valid(&self, storage: &Self::Storage) -> bool { self.key_cursor < storage.layer.keys.len() }
+    fn val_valid(&self, storage: &Self::Storage) -> bool {
+        self.key_valid(storage) && self.val_cursor < storage.layer.keys_offs[self.key_cursor+1]
+    }
+    fn step_key(&mut self, storage: &Self::Storage){
+        self.key_cursor += 1;
+        if self.key_valid(storage) {
+            self.rewind_vals(storage);
+        }
+        else {
+            self.key_cursor = storage.layer.keys.len();
+        }
+    }
+    fn seek_key(&mut self, storage: &Self::Storage, key: &K) {
+        self.key_cursor += advance(&storage.layer.keys[self.key_cursor ..], |x| x.lt(key));
+        self.rewind_vals(storage);
+    }
+    fn step_val(&mut self, storage: &Self::Storage) {
+        self.val_cursor += 1;
+        if !self.val_valid(storage) {
+            self.val_cursor = storage.layer.values_for_key(self.key_cursor).1;
+        }
+    }
+    fn seek_val(&mut self, storage: &Self::Storage, val: &V) {
+        let upper = storage.layer.values_for_key(self.key_cursor).1;
+        self.val_cursor += advance(&storage.layer.vals[self.val_cursor .. upper], |x| x.lt(val));
+    }
+    fn rewind_keys(&mut self, storage: &Self::Storage) {
+        self.key_cursor = 0;
+        self.rewind_vals(storage);
+    }
+    fn rewind_vals(&mut self, storage: &Self::Storage) {
+        if self.key_valid(storage) {
+            self.val_cursor = storage.layer.values_for_key(self.key_cursor).0;
+        }
+    }
+}
+
+
+/// A builder for creating layers from ordered update tuples.
+pub struct ColValBuilder<K, V, T, R>
+where
+    K: Columnation+Ord,
+    V: Columnation+Ord,
+    T: Columnation+Ord+Lattice,
+    R: Columnation+Semigroup,
+{
+    result: ColValLayer<K, V, T, R>,
+}
+
+impl<K, V, T, R> Builder<K, V, T, R, ColValBatch<K, V, T, R>> for ColValBuilder<K, V, T, R>
+where
+    K: Columnation+Ord+Clone+'static,
+    V: Columnation+Ord+Clone+'static,
+    T: Columnation+Lattice+timely::progress::Timestamp+Ord+Clone+::std::fmt::Debug+'static,
+    R: Columnation+Semigroup,
+{
+
+    fn new() -> Self {
+        ColValBuilder {
+            result: ColValLayer::with_capacity(0, 0, 0),
+        }
+    }
+    fn with_capacity(cap: usize) -> Self {
+        ColValBuilder {
+            result: ColValLayer::with_capacity(0, 0, cap),
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, (key, val, time, diff): (K, V, T, R)) {
+
+        // A new key closes any open value and key.
+        if self.result.keys.last() != Some(&key) {
+            if !self.result.keys.is_empty() {
+                self.result.vals_offs.push(self.result.updates.len());
+                self.result.keys_offs.push(self.result.vals.len());
+            }
+            self.result.keys.copy(&key);
+            self.result.vals.copy(&val);
+        }
+        // A new value for the same key closes only the open value.
+        else if self.result.vals.last() != Some(&val) {
+            self.result.vals_offs.push(self.result.updates.len());
+            self.result.vals.copy(&val);
+        }
+
+        self.result.updates.copy(&(time, diff));
+    }
+
+    #[inline(never)]
+    fn done(mut self, lower: Antichain<T>, upper: Antichain<T>, since: Antichain<T>) -> ColValBatch<K, V, T, R> {
+        if !self.result.keys.is_empty() {
+            self.result.vals_offs.push(self.result.updates.len());
+            self.result.keys_offs.push(self.result.vals.len());
+        }
+        ColValBatch {
+            layer: self.result,
+            desc: Description::new(lower, upper, since)
+        }
+    }
+}
+
+
+
+
+/// Columnar storage for `(key, time, diff)` updates.
+///
+/// The updates for `keys[i]` are `updates[keys_offs[i] .. keys_offs[i+1]]`.
+#[derive(Debug)]
+pub struct ColKeyLayer<K, T, R>
+where
+    K: Columnation,
+    T: Columnation,
+    R: Columnation,
+{
+    /// Ordered distinct keys.
+    pub keys: ColumnStack<K>,
+    /// Offsets into `updates` for each key, with a leading zero.
+    pub keys_offs: Vec<usize>,
+    /// Ordered `(time, diff)` updates, consolidated for each key.
+    pub updates: ColumnStack<(T, R)>,
+}
+
+impl<K, T, R> ColKeyLayer<K, T, R>
+where
+    K: Columnation,
+    T: Columnation,
+    R: Columnation,
+{
+    fn with_capacity(keys: usize, updates: usize) -> Self {
+        let mut keys_offs = Vec::with_capacity(keys + 1);
+        keys_offs.push(0);
+        ColKeyLayer {
+            keys: ColumnStack::with_capacity(keys),
+            keys_offs,
+            updates: ColumnStack::with_capacity(updates),
+        }
+    }
+    /// The range of `updates` associated with the key at `index`.
+    #[inline]
+    pub fn updates_for_key(&self, index: usize) -> (usize, usize) {
+        (self.keys_offs[index], self.keys_offs[index+1])
+    }
+}
+
+/// An immutable collection of update tuples, from a contiguous interval of logical times.
+#[derive(Debug)]
+pub struct ColKeyBatch<K, T, R>
+where
+    K: Columnation,
+    T: Columnation,
+    R: Columnation,
+{
+    /// Where all the dataz is.
+    pub layer: ColKeyLayer<K, T, R>,
+    /// Description of the update times this layer represents.
+    pub desc: Description<T>,
+}
+
+impl<K, T, R> BatchReader<K, (), T, R> for ColKeyBatch<K, T, R>
+where
+    K: Columnation+Ord+Clone+'static,
+    T: Columnation+Lattice+Ord+Clone+'static,
+    R: Columnation+Semigroup,
+{
+    type Cursor = ColKeyCursor<K, T, R>;
+    fn cursor(&self) -> Self::Cursor {
+        ColKeyCursor {
+            empty: (),
+            valid: true,
+            key_cursor: 0,
+            phantom: PhantomData,
+        }
+    }
+    fn len(&self) -> usize { self.layer.updates.len() }
+    fn description(&self) -> &Description<T> { &self.desc }
+    fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) {
+        let size_of_usize = ::std::mem::size_of::<usize>();
+        self.layer.keys.heap_size(callback);
+        callback(self.layer.keys_offs.len() * size_of_usize, self.layer.keys_offs.capacity() * size_of_usize);
+        self.layer.updates.heap_size(callback);
+    }
+}
+
+impl<K, T, R> Batch<K, (), T, R> for ColKeyBatch<K, T, R>
+where
+    K: Columnation+Ord+Clone+'static,
+    T: Columnation+Lattice+timely::progress::Timestamp+Ord+Clone+'static,
+    R: Columnation+Semigroup,
+{
+    type Batcher = MergeBatcher<K, (), T, R, Self>;
+    type Builder = ColKeyBuilder<K, T, R>;
+    type Merger = ColKeyMerger<K, T, R>;
+
+    fn begin_merge(&self, other: &Self, compaction_frontier: Option<AntichainRef<T>>) -> Self::Merger {
+        ColKeyMerger::new(self, other, compaction_frontier)
+    }
+}
+
+/// State for an in-progress merge.
+pub struct ColKeyMerger<K, T, R>
+where
+    K: Columnation+Ord+Clone+'static,
+    T: Columnation+Lattice+Ord+Clone+'static,
+    R: Columnation+Semigroup,
+{
+    // position in the keys of the first batch.
+    key_cursor1: usize,
+    // position in the keys of the second batch.
+    key_cursor2: usize,
+    // result that we are currently assembling.
+    result: ColKeyLayer<K, T, R>,
+    description: Description<T>,
+    should_compact: bool,
+    // staging area for the updates of a single key.
+    update_stash: Vec<(T, R)>,
+}
+
+impl<K, T, R> Merger<K, (), T, R, ColKeyBatch<K, T, R>> for ColKeyMerger<K, T, R>
+where
+    K: Columnation+Ord+Clone+'static,
+    T: Columnation+Lattice+timely::progress::Timestamp+Ord+Clone+'static,
+    R: Columnation+Semigroup,
+{
+    fn new(batch1: &ColKeyBatch<K, T, R>, batch2: &ColKeyBatch<K, T, R>, compaction_frontier: Option<AntichainRef<T>>) -> Self {
+
+        assert!(batch1.upper() == batch2.lower());
+
+        let mut since = batch1.description().since().join(batch2.description().since());
+        if let Some(compaction_frontier) = compaction_frontier {
+            since = since.join(&compaction_frontier.to_owned());
+        }
+
+        let description = Description::new(batch1.lower().clone(), batch2.upper().clone(), since);
+
+        ColKeyMerger {
+            key_cursor1: 0,
+            key_cursor2: 0,
+            result: ColKeyLayer::with_capacity(
+                batch1.layer.keys.len() + batch2.layer.keys.len(),
+                batch1.layer.updates.len() + batch2.layer.updates.len(),
+            ),
+            description: description,
+            should_compact: compaction_frontier.is_some(),
+            update_stash: Vec::new(),
+        }
+    }
+    fn done(self) -> ColKeyBatch<K, T, R> {
+
+        assert!(self.result.keys_offs.len() == self.result.keys.len() + 1);
+
+        ColKeyBatch {
+            layer: self.result,
+            desc: self.description,
+        }
+    }
+    fn work(&mut self, source1: &ColKeyBatch<K,T,R>, source2: &ColKeyBatch<K,T,R>, fuel: &mut isize) {
+
+        let starting_updates = self.result.updates.len();
+        let mut effort = 0isize;
+
+        // while both mergees are still active
+        while self.key_cursor1 < source1.layer.keys.len() && self.key_cursor2 < source2.layer.keys.len() && effort < *fuel {
+            use ::std::cmp::Ordering;
+            match source1.layer.keys[self.key_cursor1].cmp(&source2.layer.keys[self.key_cursor2]) {
+                Ordering::Less => {
+                    self.copy_key(&source1.layer, self.key_cursor1);
+                    self.key_cursor1 += 1;
+                },
+                Ordering::Equal => {
+                    self.stash_updates_for_key(&source1.layer, self.key_cursor1);
+                    self.stash_updates_for_key(&source2.layer, self.key_cursor2);
+                    if self.consolidate_updates() {
+                        self.result.keys.copy(&source1.layer.keys[self.key_cursor1]);
+                    }
+                    self.key_cursor1 += 1;
+                    self.key_cursor2 += 1;
+                },
+                Ordering::Greater => {
+                    self.copy_key(&source2.layer, self.key_cursor2);
+                    self.key_cursor2 += 1;
+                },
+            }
+            effort = (self.result.updates.len() - starting_updates) as isize;
+        }
+
+        // Merging is complete; only copying remains.
+        while self.key_cursor1 < source1.layer.keys.len() && effort < *fuel {
+            self.copy_key(&source1.layer, self.key_cursor1);
+            self.key_cursor1 += 1;
+            effort = (self.result.updates.len() - starting_updates) as isize;
+        }
+        while self.key_cursor2 < source2.layer.keys.len() && effort < *fuel {
+            self.copy_key(&source2.layer, self.key_cursor2);
+            self.key_cursor2 += 1;
+            effort = (self.result.updates.len() - starting_updates) as isize;
+        }
+
+        *fuel -= effort;
+    }
+}
+
+impl<K, T, R> ColKeyMerger<K, T, R>
+where
+    K: Columnation+Ord+Clone+'static,
+    T: Columnation+Lattice+Ord+Clone+'static,
+    R: Columnation+Semigroup,
+{
+    /// Copies the key at `index` along with its updates, if any remain after compaction.
+    fn copy_key(&mut self, source: &ColKeyLayer<K,T,R>, index: usize) {
+        self.stash_updates_for_key(source, index);
+        if self.consolidate_updates() {
+            self.result.keys.copy(&source.keys[index]);
+        }
+    }
+
+    /// Clones the updates of the key at `index` into `self.update_stash`.
+    fn stash_updates_for_key(&mut self, source: &ColKeyLayer<K,T,R>, index: usize) {
+        let (lower, upper) = source.updates_for_key(index);
+        self.update_stash.extend(source.updates[lower .. upper].iter().cloned());
+    }
+
+    /// Advances and consolidates stashed updates, moving them into the result.
+    ///
+    /// Returns `true` if any updates remain, in which case the caller must record the key.
+    fn consolidate_updates(&mut self) -> bool {
+        if self.should_compact {
+            for (time, _) in self.update_stash.iter_mut() {
+                time.advance_by(self.description.since().borrow());
+            }
+        }
+        crate::consolidation::consolidate(&mut self.update_stash);
+        if !self.update_stash.is_empty() {
+            for update in self.update_stash.iter() {
+                self.result.updates.copy(update);
+            }
+            self.update_stash.clear();
+            self.result.keys_offs.push(self.result.updates.len());
+            true
+        }
+        else {
+            false
+        }
+    }
+}
+
+
+/// A cursor for navigating a single layer.
+#[derive(Debug)]
+pub struct ColKeyCursor<K, T, R> {
+    valid: bool,
+    empty: (),
+    key_cursor: usize,
+    phantom: PhantomData<(K, T, R)>,
+}
+
+impl<K, T, R> Cursor<K, (), T, R> for ColKeyCursor<K, T, R>
+where
+    K: Columnation+Ord+Clone,
+    T: Columnation+Lattice+Ord+Clone,
+    R: Columnation+Semigroup,
+{
+    type Storage = ColKeyBatch<K, T, R>;
+
+    fn key<'a>(&self, storage: &'a Self::Storage) -> &'a K { &storage.layer.keys[self.key_cursor] }
+    fn val<'a>(&self, _storage: &'a Self::Storage) -> &'a () { unsafe { ::std::mem::transmute(&self.empty) } }
+    fn map_times<L: FnMut(&T, &R)>(&mut self, storage: &Self::Storage, mut logic: L) {
+        let (lower, upper) = storage.layer.updates_for_key(self.key_cursor);
+        for (time, diff) in storage.layer.updates[lower .. upper].iter() {
+            logic(time, diff);
+        }
+    }
+    fn key_valid(&self, storage: &Self::Storage) -> bool { self.key_cursor < storage.layer.keys.len() }
+    fn val_valid(&self, _storage: &Self::Storage) -> bool { self.valid }
+    fn step_key(&mut self, storage: &Self::Storage){
+        if self.key_valid(storage) { self.key_cursor += 1; }
+        self.valid = true;
+    }
+    fn seek_key(&mut self, storage: &Self::Storage, key: &K) {
+        self.key_cursor += advance(&storage.layer.keys[self.key_cursor ..], |x| x.lt(key));
+        self.valid = true;
+    }
+    fn step_val(&mut self, _storage: &Self::Storage) { self.valid = false; }
+    fn seek_val(&mut self, _storage: &Self::Storage, _val: &()) { }
+    fn rewind_keys(&mut self, _storage: &Self::Storage) { self.key_cursor = 0; self.valid = true; }
+    fn rewind_vals(&mut self, _storage: &Self::Storage) { self.valid = true; }
+}
+
+
+/// A builder for creating layers from ordered update tuples.
+pub struct ColKeyBuilder<K, T, R>
+where
+    K: Columnation+Ord,
+    T: Columnation+Ord+Lattice,
+    R: Columnation+Semigroup,
+{
+    result: ColKeyLayer<K, T, R>,
+}
+
+impl<K, T, R> Builder<K, (), T, R, ColKeyBatch<K, T, R>> for ColKeyBuilder<K, T, R>
+where
+    K: Columnation+Ord+Clone+'static,
+    T: Columnation+Lattice+timely::progress::Timestamp+Ord+Clone+'static,
+    R: Columnation+Semigroup,
+{
+
+    fn new() -> Self {
+        ColKeyBuilder {
+            result: ColKeyLayer::with_capacity(0, 0),
+        }
+    }
+
+    fn with_capacity(cap: usize) -> Self {
+        ColKeyBuilder {
+            result: ColKeyLayer::with_capacity(0, cap),
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, (key, _, time, diff): (K, (), T, R)) {
+        // A new key closes any open key.
+        if self.result.keys.last() != Some(&key) {
+            if !self.result.keys.is_empty() {
+                self.result.keys_offs.push(self.result.updates.len());
+            }
+            self.result.keys.copy(&key);
+        }
+        self.result.updates.copy(&(time, diff));
+    }
+
+    #[inline(never)]
+    fn done(mut self, lower: Antichain<T>, upper: Antichain<T>, since: Antichain<T>) -> ColKeyBatch<K, T, R> {
+        if !self.result.keys.is_empty() {
+            self.result.keys_offs.push(self.result.updates.len());
+        }
+        ColKeyBatch {
+            layer: self.result,
+            desc: Description::new(lower, upper, since)
+        }
+    }
+}
@@ -0,0 +1,443 @@
+//! Region-allocated storage for columns of owned values.
+//!
+//! A `ColumnStack<T>` looks like a `Vec<T>`, but any heap allocations owned by its elements are
+//! copied into large shared regions rather than held as individual allocations. Fixed-width types
+//! (integers, timestamps, `()`) are stored in a flat array with no further indirection. Strings
+//! and vectors are stored as headers in a flat array whose contents point into contiguous byte (or
+//! element) regions, which removes the per-record allocator overhead of owned Rust values.
+//!
+//! The stored elements are never dropped, and their backing memory is released only when the
+//! stack is cleared or dropped. The elements must not be mutated, which `ColumnStack` enforces by
+//! only revealing them through shared references.
+
+use std::fmt::Debug;
+
+/// A type that can be stored in a `ColumnStack`.
+///
+/// The associated region is responsible for copying instances of the type so that any owned
+/// allocations are drawn from the region rather than the global allocator.
+pub trait Columnation: Sized {
+    /// The type of region used to copy instances of `Self`.
+    type InnerRegion: Region<Item=Self>;
+}
+
+/// A container for the owned allocations of region-copied items.
+pub trait Region : Default {
+    /// The type of item the region copies.
+    type Item: Columnation;
+    /// Copies `item` into the region, returning a copy whose owned allocations are in the region.
+    ///
+    /// The result must not be dropped, and should not outlive the region or a call to `clear`.
+    unsafe fn copy(&mut self, item: &Self::Item) -> Self::Item;
+    /// Releases (or makes available for reuse) all copied allocations.
+    fn clear(&mut self);
+    /// Reports the size and capacity, in bytes, of each heap allocation held by the region.
+    fn heap_size(&self, callback: &mut dyn FnMut(usize, usize));
+}
+
+/// A region for types with no owned allocations, which copies by `Clone`.
+#[derive(Debug)]
+pub struct CopyRegion<T> {
+    phantom: ::std::marker::PhantomData<T>,
+}
+
+impl<T> Default for CopyRegion<T> {
+    fn default() -> Self { CopyRegion { phantom: ::std::marker::PhantomData } }
+}
+
+impl<T: Columnation+Clone> Region for CopyRegion<T> {
+    type Item = T;
+    #[inline] unsafe fn copy(&mut self, item: &T) -> T { item.clone() }
+    #[inline] fn clear(&mut self) { }
+    #[inline] fn heap_size(&self, _callback: &mut dyn FnMut(usize, usize)) { }
+}
+
+/// A region of contiguous allocations whose elements never move once written.
+///
+/// New allocations double in size up to `limit` elements, after which each allocation has
+/// room for `limit` elements (or more, if a single request requires it).
+#[derive(Debug)]
+pub struct StableRegion<T> {
+    local: Vec<T>,
+    stash: Vec<Vec<T>>,
+    limit: usize,
+}
+
+impl<T> Default for StableRegion<T> {
+    fn default() -> Self {
+        StableRegion {
+            local: Vec::new(),
+            stash: Vec::new(),
+            limit: 1 << 16,
+        }
+    }
+}
+
+impl<T> StableRegion<T> {
+    /// Allocates a region whose allocations are no larger than `limit` elements, where possible.
+    pub fn with_limit(limit: usize) -> Self {
+        StableRegion {
+            local: Vec::new(),
+            stash: Vec::new(),
+            limit,
+        }
+    }
+    /// Clears the region, without dropping its elements.
+    ///
+    /// The most recent allocation is retained for reuse.
+    #[inline]
+    pub fn clear(&mut self) {
+        unsafe {
+            // Elements are bit-copies whose allocations belong to other regions; do not drop them.
+            self.local.set_len(0);
+            for mut buffer in self.stash.drain(..) {
+                buffer.set_len(0);
+            }
+        }
+    }
+    /// Copies the exactly `count` elements of `items` into the region.
+    ///
+    /// The returned slice is stable until the region is cleared or dropped.
+    #[inline]
+    pub fn copy_iter<I: Iterator<Item=T>>(&mut self, items: I, count: usize) -> &mut [T] {
+        self.reserve(count);
+        let index = self.local.len();
+        self.local.extend(items);
+        debug_assert_eq!(self.local.len(), index + count);
+        &mut self.local[index ..]
+    }
+    /// Copies a slice of cloneable elements into the region.
+    #[inline]
+    pub fn copy_slice(&mut self, items: &[T]) -> &mut [T] where T: Clone {
+        self.copy_iter(items.iter().cloned(), items.len())
+    }
+    /// Ensures that there is space in `self.local` to copy at least `count` items.
+    #[inline(always)]
+    pub fn reserve(&mut self, count: usize) {
+        if count > self.local.capacity() - self.local.len() {
+            let mut next_len = (self.local.capacity() + 1).next_power_of_two();
+            next_len = std::cmp::min(next_len, self.limit);
+            next_len = std::cmp::max(count, next_len);
+            let new_local = Vec::with_capacity(next_len);
+            if self.local.is_empty() {
+                self.local = new_local;
+            }
+            else {
+                self.stash.push(std::mem::replace(&mut self.local, new_local));
+            }
+        }
+    }
+    /// Reports the size and capacity, in bytes, of each allocation.
+    pub fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) {
+        let size_of_t = std::mem::size_of::<T>();
+        callback(self.local.len() * size_of_t, self.local.capacity() * size_of_t);
+        for buffer in self.stash.iter() {
+            callback(buffer.len() * size_of_t, buffer.capacity() * size_of_t);
+        }
+    }
+}
+
+impl<T> Drop for StableRegion<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// A stack of region-copied items.
+///
+/// The stack acts like an append-only `Vec<T>` that can be read through `Deref<Target=[T]>`.
+pub struct ColumnStack<T: Columnation> {
+    local: Vec<T>,
+    inner: T::InnerRegion,
+}
+
+impl<T: Columnation> ColumnStack<T> {
+    /// Allocates a new empty stack.
+    pub fn new() -> Self {
+        ColumnStack {
+            local: Vec::new(),
+            inner: T::InnerRegion::default(),
+        }
+    }
+    /// Allocates a new empty stack with room for `capacity` items (but not their allocations).
+    pub fn with_capacity(capacity: usize) -> Self {
+        ColumnStack {
+            local: Vec::with_capacity(capacity),
+            inner: T::InnerRegion::default(),
+        }
+    }
+    /// Copies `item` onto the end of the stack.
+    #[inline]
+    pub fn copy(&mut self, item: &T) {
+        unsafe {
+            self.local.push(self.inner.copy(item));
+        }
+    }
+    /// Reserves space for at least `additional` more items (but not their allocations).
+    pub fn reserve(&mut self, additional: usize) {
+        self.local.reserve(additional);
+    }
+    /// Empties the stack, releasing the allocations of its items.
+    pub fn clear(&mut self) {
+        unsafe {
+            // Items point into `self.inner`, and must not be dropped.
+            self.local.set_len(0);
+        }
+        self.inner.clear();
+    }
+    /// Reports the size and capacity, in bytes, of each heap allocation held by the stack.
+    pub fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) {
+        let size_of_t = std::mem::size_of::<T>();
+        callback(self.local.len() * size_of_t, self.local.capacity() * size_of_t);
+        self.inner.heap_size(callback);
+    }
+}
+
+impl<T: Columnation> ::std::ops::Deref for ColumnStack<T> {
+    type Target = [T];
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.local[..]
+    }
+}
+
+impl<T: Columnation> Drop for ColumnStack<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Columnation> Default for ColumnStack<T> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T: Columnation> Clone for ColumnStack<T> {
+    fn clone(&self) -> Self {
+        let mut new = Self::with_capacity(self.len());
+        for item in self.iter() {
+            new.copy(item);
+        }
+        new
+    }
+}
+
+impl<T: Columnation+Debug> Debug for ColumnStack<T> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        (&self[..]).fmt(f)
+    }
+}
+
+impl<T: Columnation+Eq> Eq for ColumnStack<T> { }
+impl<T: Columnation+PartialEq> PartialEq for ColumnStack<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self[..] == other[..]
+    }
+}
+
+/// Implementations for types whose clones own no allocations.
+macro_rules! implement_columnation {
+    ($($index_type:ty),*) => (
+        $(
+            impl Columnation for $index_type {
+                type InnerRegion = CopyRegion<$index_type>;
+            }
+        )*
+    )
+}
+
+implement_columnation!(u8, u16, u32, u64, u128, usize);
+implement_columnation!(i8, i16, i32, i64, i128, isize);
+implement_columnation!(f32, f64);
+implement_columnation!(char, bool, ());
+implement_columnation!(::std::num::Wrapping<i8>, ::std::num::Wrapping<i16>, ::std::num::Wrapping<i32>);
+implement_columnation!(::std::num::Wrapping<i64>, ::std::num::Wrapping<i128>, ::std::num::Wrapping<isize>);
+implement_columnation!(::std::time::Duration);
+implement_columnation!(::difference::Present);
+
+pub use self::string::StringRegion;
+mod string {
+
+    use super::{Columnation, Region, StableRegion};
+
+    /// A region for `String` whose bytes are stored contiguously.
+    #[derive(Default, Debug)]
+    pub struct StringRegion {
+        inner: StableRegion<u8>,
+    }
+
+    impl Columnation for String {
+        type InnerRegion = StringRegion;
+    }
+
+    impl Region for StringRegion {
+        type Item = String;
+        #[inline]
+        unsafe fn copy(&mut self, item: &String) -> String {
+            let bytes = self.inner.copy_slice(item.as_bytes());
+            String::from_raw_parts(bytes.as_mut_ptr(), item.len(), item.len())
+        }
+        #[inline(always)]
+        fn clear(&mut self) {
+            self.inner.clear();
+        }
+        fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) {
+            self.inner.heap_size(callback)
+        }
+    }
+}
+
+pub use self::vector::VecRegion;
+mod vector {
+
+    use super::{Columnation, Region, StableRegion};
+
+    /// A region for `Vec<T>` whose elements are stored contiguously.
+    ///
+    /// Any allocations owned by the elements are themselves copied into a region for `T`.
+    pub struct VecRegion<T: Columnation> {
+        region: StableRegion<T>,
+        inner: T::InnerRegion,
+    }
+
+    impl<T: Columnation> Default for VecRegion<T> {
+        fn default() -> Self {
+            VecRegion {
+                region: StableRegion::default(),
+                inner: T::InnerRegion::default(),
+            }
+        }
+    }
+
+    impl<T: Columnation> Columnation for Vec<T> {
+        type InnerRegion = VecRegion<T>;
+    }
+
+    impl<T: Columnation> Region for VecRegion<T> {
+        type Item = Vec<T>;
+        #[inline]
+        unsafe fn copy(&mut self, item: &Vec<T>) -> Vec<T> {
+            let inner = &mut self.inner;
+            let slice = self.region.copy_iter(item.iter().map(|element| inner.copy(element)), item.len());
+            Vec::from_raw_parts(slice.as_mut_ptr(), item.len(), item.len())
+        }
+        #[inline(always)]
+        fn clear(&mut self) {
+            self.region.clear();
+            self.inner.clear();
+        }
+        fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) {
+            self.region.heap_size(callback);
+            self.inner.heap_size(callback);
+        }
+    }
+}
+
+pub use self::option::OptionRegion;
+mod option {
+
+    use super::{Columnation, Region};
+
+    /// A region for `Option<T>`, which copies the contents of `Some` variants.
+    #[derive(Default)]
+    pub struct OptionRegion<R: Region> {
+        region: R,
+    }
+
+    impl<T: Columnation> Columnation for Option<T> {
+        type InnerRegion = OptionRegion<T::InnerRegion>;
+    }
+
+    impl<T: Columnation, R: Region<Item=T>> Region for OptionRegion<R> {
+        type Item = Option<T>;
+        #[inline]
+        unsafe fn copy(&mut self, item: &Option<T>) -> Option<T> {
+            item.as_ref().map(|inner| self.region.copy(inner))
+        }
+        #[inline(always)]
+        fn clear(&mut self) {
+            self.region.clear();
+        }
+        fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) {
+            self.region.heap_size(callback)
+        }
+    }
+}
+
+pub use self::product::ProductRegion;
+mod product {
+
+    use timely::order::Product;
+    use super::{Columnation, Region};
+
+    /// A region for timely's `Product` timestamps, which copies each coordinate.
+    #[derive(Default)]
+    pub struct ProductRegion<R1: Region, R2: Region> {
+        outer: R1,
+        inner: R2,
+    }
+
+    impl<TOuter: Columnation, TInner: Columnation> Columnation for Product<TOuter, TInner> {
+        type InnerRegion = ProductRegion<TOuter::InnerRegion, TInner::InnerRegion>;
+    }
+
+    impl<TOuter: Columnation, TInner: Columnation, R1: Region<Item=TOuter>, R2: Region<Item=TInner>> Region for ProductRegion<R1, R2> {
+        type Item = Product<TOuter, TInner>;
+        #[inline]
+        unsafe fn copy(&mut self, item: &Self::Item) -> Self::Item {
+            Product::new(self.outer.copy(&item.outer), self.inner.copy(&item.inner))
+        }
+        #[inline(always)]
+        fn clear(&mut self) {
+            self.outer.clear();
+            self.inner.clear();
+        }
+        fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) {
+            self.outer.heap_size(callback);
+            self.inner.heap_size(callback);
+        }
+    }
+}
+
+pub use self::tuples::{TupleRegion1, TupleRegion2, TupleRegion3, TupleRegion4};
+mod tuples {
+
+    use super::{Columnation, Region};
+
+    /// Implementations for tuples. Each argument names a type, its region, and a field name.
+    macro_rules! tuple_columnation {
+        ($region:ident, $(($name:ident, $region_name:ident, $index:tt)),*) => (
+
+            /// A region for tuples, which copies each field into its own region.
+            #[allow(non_snake_case)]
+            #[derive(Default)]
+            pub struct $region<$($region_name: Region),*> {
+                $($name: $region_name),*
+            }
+
+            impl<$($name: Columnation),*> Columnation for ($($name,)*) {
+                type InnerRegion = $region<$($name::InnerRegion),*>;
+            }
+
+            #[allow(non_snake_case)]
+            impl<$($name: Columnation, $region_name: Region<Item=$name>),*> Region for $region<$($region_name),*> {
+                type Item = ($($name,)*);
+                #[inline]
+                unsafe fn copy(&mut self, item: &Self::Item) -> Self::Item {
+                    ( $(self.$name.copy(&item.$index),)* )
+                }
+                #[inline(always)]
+                fn clear(&mut self) {
+                    $(self.$name.clear();)*
+                }
+                fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) {
+                    $(self.$name.heap_size(callback);)*
+                }
+            }
+        )
+    }
+
+    tuple_columnation!(TupleRegion1, (A, RA, 0));
+    tuple_columnation!(TupleRegion2, (A, RA, 0), (B, RB, 1));
+    tuple_columnation!(TupleRegion3, (A, RA, 0), (B, RB, 1), (C, RC, 2));
+    tuple_columnation!(TupleRegion4, (A, RA, 0), (B, RB, 1), (C, RC, 2), (D, RD, 3));
+}
@@ -0,0 +1,126 @@
+//! Policies determining the merge effort a `Spine` applies.
+//!
+//! A spine applies fuel to its in-progress merges each time a batch is introduced, in proportion
+//! to the number of updates introduced. A `MergeEffort` policy determines the amount of fuel, and
+//! is informed of the outcome of each application of fuel: the fuel spent, the time taken, and the
+//! backlog of updates in merges that remain in progress.
+//!
+//! The `FixedEffort` policy applies a fixed multiple of the introduced updates, and is what the
+//! spine uses by default. The `AdaptiveEffort` policy adjusts its multiple to keep the time spent
+//! in each application of fuel near a target, while increasing effort if the backlog grows.
+
+use std::time::Duration;
+
+/// A report of the outcome of an application of fuel to in-progress merges.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct MergeProgress {
+    /// The amount of fuel spent across all merges.
+    pub spent: usize,
+    /// The time spent applying fuel.
+    pub elapsed: Duration,
+    /// The number of updates in merges that remain in progress.
+    pub backlog: usize,
+}
+
+/// A policy determining the amount of fuel to apply to in-progress merges.
+pub trait MergeEffort {
+    /// The fuel to apply to each in-progress merge, when introducing `2^batch_index` updates.
+    ///
+    /// To maintain the spine's invariants without forcing merges to complete, the fuel should be
+    /// at least `8 << batch_index`.
+    fn fuel(&mut self, batch_index: usize) -> isize;
+    /// Observes the outcome of an application of fuel.
+    ///
+    /// The result indicates the number of updates by which the policy has fallen behind, if it has,
+    /// which the spine reports as a `MergeShortfall` event.
+    fn observe(&mut self, progress: &MergeProgress) -> Option<usize>;
+}
+
+/// Applies a fixed multiple of the minimum fuel.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct FixedEffort {
+    effort: usize,
+}
+
+impl FixedEffort {
+    /// Allocates a policy with multiplier `effort`, which is increased to one if zero.
+    pub fn new(effort: usize) -> Self {
+        FixedEffort { effort: ::std::cmp::max(effort, 1) }
+    }
+}
+
+impl MergeEffort for FixedEffort {
+    fn fuel(&mut self, batch_index: usize) -> isize {
+        ((8 << batch_index) * self.effort) as isize
+    }
+    fn observe(&mut self, _progress: &MergeProgress) -> Option<usize> {
+        None
+    }
+}
+
+/// Adjusts the multiple of the minimum fuel to spend about `target` time in each application.
+///
+/// The multiplier is halved when an application of fuel takes longer than `target`, and is doubled
+/// (up to `maximum`) when the backlog grows or when an application takes less than a quarter of
+/// `target` with work outstanding.
+///
+/// The backlog grows whenever the spine starts a merge, which is not itself a sign of falling behind.
+/// Rather, the spine's fueling discipline completes merges before as many updates as they contain
+/// are introduced. If more updates than the backlog are introduced without the backlog shrinking,
+/// while the policy is unable to increase its effort, it reports the backlog as a shortfall.
+#[derive(Debug, Clone)]
+pub struct AdaptiveEffort {
+    target: Duration,
+    maximum: usize,
+    effort: usize,
+    backlog: usize,
+    /// Updates introduced since the backlog last shrank.
+    introduced: usize,
+}
+
+impl AdaptiveEffort {
+    /// Allocates a policy targeting `target` time per application of fuel, with multipliers at most `maximum`.
+    pub fn new(target: Duration, maximum: usize) -> Self {
+        AdaptiveEffort {
+            target,
+            maximum: ::std::cmp::max(maximum, 1),
+            effort: 1,
+            backlog: 0,
+            introduced: 0,
+        }
+    }
+
+    /// The current multiplier of the minimum fuel.
+    pub fn effort(&self) -> usize { self.effort }
+}
+
+impl MergeEffort for AdaptiveEffort {
+    fn fuel(&mut self, batch_index: usize) -> isize {
+        self.introduced = self.introduced.saturating_add(1 << batch_index);
+        ((8 << batch_index) * self.effort) as isize
+    }
+    fn observe(&mut self, progress: &MergeProgress) -> Option<usize> {
+
+        let previous = ::std::mem::replace(&mut self.backlog, progress.backlog);
+        let growing = progress.backlog > previous;
+        if progress.backlog < previous || progress.backlog == 0 {
+            self.introduced = 0;
+        }
+
+        if progress.elapsed > self.target {
+            self.effort = ::std::cmp::max(self.effort / 2, 1);
+        }
+        else if self.effort < self.maximum && (growing || (progress.backlog > 0 && progress.elapsed < self.target / 4)) {
+            self.effort = ::std::cmp::min(self.effort * 2, self.maximum);
+            return None;
+        }
+
+        if progress.backlog > 0 && self.introduced > progress.backlog {
+            self.introduced = 0;
+            Some(progress.backlog)
+        }
+        else {
+            None
+        }
+    }
+}
@@ -0,0 +1,688 @@
+//! Trace and batch implementations based on Robin Hood hash tables of keys.
+//!
+//! The types and type aliases in this module start with either
+//!
+//! * `HashVal`: Collections whose data have the form `(key, val)` where `key` is hash-ordered.
+//! * `HashKey`: Collections whose data have the form `key` where `key` is hash-ordered.
+//!
+//! These batches are structured like those of the `ord` module, except that their keys are laid
+//! out in a `HashedLayer`. Seeking a key is an expected constant time probe, rather than a
+//! galloping search, which benefits point lookups against large arrangements. Keys must be
+//! `HashOrdered`, for example by wrapping them in a `HashableWrapper`.
+
+use std::rc::Rc;
+
+use timely::progress::{Antichain, frontier::AntichainRef};
+
+use ::difference::Semigroup;
+use ::hashable::HashOrdered;
+use lattice::Lattice;
+
+use trace::layers::{Trie, TupleBuilder};
+use trace::layers::Builder as TrieBuilder;
+use trace::layers::Cursor as TrieCursor;
+use trace::layers::hashed::{HashedLayer, HashedBuilder, HashedCursor};
+use trace::layers::ordered::{OrderedLayer, OrderedBuilder};
+use trace::layers::ordered_leaf::{OrderedLeaf, OrderedLeafBuilder};
+use trace::{Batch, BatchReader, Builder, Merger, Cursor};
+use trace::description::Description;
+
+use trace::layers::MergeBuilder;
+
+use super::spine_fueled::Spine;
+use super::merge_batcher::MergeBatcher;
+
+/// A trace implementation using a spine of hashed lists.
+pub type HashValSpine<K, V, T, R> = Spine<K, V, T, R, Rc<HashValBatch<K, V, T, R>>>;
+
+/// A trace implementation for empty values using a spine of hashed lists.
+pub type HashKeySpine<K, T, R> = Spine<K, (), T, R, Rc<HashKeyBatch<K, T, R>>>;
+
+
+/// An immutable collection of update tuples, from a contiguous interval of logical times.
+#[derive(Debug, Abomonation)]
+pub struct HashValBatch<K, V, T, R>
+where
+    K: HashOrdered,
+    V: Ord,
+    T: Lattice,
+{
+    /// Where all the dataz is.
+    pub layer: HashedLayer<K, OrderedLayer<V, OrderedLeaf<T, R>>>,
+    /// Description of the update times this layer represents.
+    pub desc: Description<T>,
+}
+
+impl<K, V, T, R> BatchReader<K, V, T, R> for HashValBatch<K, V, T, R>
+where
+    K: HashOrdered+Clone+'static,
+    V: Ord+Clone+'static,
+    T: Lattice+Ord+Clone+'static,
+    R: Semigroup,
+{
+    type Cursor = HashValCursor<V, T, R>;
+    fn cursor(&self) -> Self::Cursor { HashValCursor { cursor: self.layer.cursor() } }
+    fn len(&self) -> usize { <HashedLayer<K, OrderedLayer<V, OrderedLeaf<T, R>>> as Trie>::tuples(&self.layer) }
+    fn description(&self) -> &Description<T> { &self.desc }
+    fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) {
+        use std::mem::size_of;
+        let keys = &self.layer;
+        let vals = &self.layer.vals;
+        let updates = &self.layer.vals.vals.vals;
+        callback(keys.keys.len() * size_of::<Option<K>>(), keys.keys.capacity() * size_of::<Option<K>>());
+        callback(keys.offs.len() * size_of::<usize>(), keys.offs.capacity() * size_of::<usize>());
+        callback(vals.keys.len() * size_of::<V>(), vals.keys.capacity() * size_of::<V>());
+        callback(vals.offs.len() * size_of::<usize>(), vals.offs.capacity() * size_of::<usize>());
+        callback(updates.len() * size_of::<(T, R)>(), updates.capacity() * size_of::<(T, R)>());
+    }
+}
+
+impl<K, V, T, R> Batch<K, V, T, R> for HashValBatch<K, V, T, R>
+where
+    K: HashOrdered+Clone+'static,
+    V: Ord+Clone+'static,
+    T: Lattice+timely::progress::Timestamp+Ord+Clone+::std::fmt::Debug+'static,
+    R: Semigroup,
+{
+    type Batcher = MergeBatcher<K, V, T, R, Self>;
+    type Builder = HashValBuilder<K, V, T, R>;
+    type Merger = HashValMerger<K, V, T, R>;
+
+    fn begin_merge(&self, other: &Self, compaction_frontier: Option<AntichainRef<T>>) -> Self::Merger {
+        HashValMerger::new(self, other, compaction_frontier)
+    }
+}
+
+impl<K, V, T, R> HashValBatch<K, V, T, R>
+where
+    K: HashOrdered+Clone+'static,
+    V: Ord+Clone+'static,
+    T: Lattice+Ord+Clone+::std::fmt::Debug+'static,
+    R: Semigroup,
+{
+    fn advance_builder_from(layer: &mut HashedBuilder<K, OrderedBuilder<V, OrderedLeafBuilder<T, R>>>, frontier: AntichainRef<T>, key_pos: usize) {
+
+        let key_start = key_pos;
+        let val_start: usize = layer.offs[key_pos];
+        let time_start: usize = layer.vals.offs[val_start];
+
+        // We have unique ownership of the batch, and can advance times in place.
+        // We must still sort, collapse, and remove empty updates.
+
+        // 1. For each (time, diff) pair, advance the time.
+        for i in time_start .. layer.vals.vals.vals.len() {
+            layer.vals.vals.vals[i].0.advance_by(frontier);
+        }
+
+        // 2. For each `(val, off)` pair, sort the range, compact, and rewrite `off`.
+        //    This may leave `val` with an empty range; filtering happens in step 3.
+        let mut write_position = time_start;
+        for i in val_start .. layer.vals.keys.len() {
+
+            // NB: batch.layer.vals.offs[i+1] will be used next iteration, and should not be changed.
+            //     we will change batch.layer.vals.offs[i] in this iteration, from `write_position`'s
+            //     initial value.
+
+            let lower: usize = layer.vals.offs[i];
+            let upper: usize = layer.vals.offs[i+1];
+
+            layer.vals.offs[i] = write_position;
+
+            let updates = &mut layer.vals.vals.vals[..];
+
+            // sort the range by the times (ignore the diffs; they will collapse).
+            let count = crate::consolidation::consolidate_slice(&mut updates[lower .. upper]);
+
+            for index in lower .. (lower + count) {
+                updates.swap(write_position, index);
+                write_position += 1;
+            }
+        }
+        layer.vals.vals.vals.truncate(write_position);
+        layer.vals.offs[layer.vals.keys.len()] = write_position;
+
+        // 3. For each `(key, off)` pair, (values already sorted), filter vals, and rewrite `off`.
+        //    This may leave `key` with an empty range. Filtering happens in step 4.
+        let mut write_position = val_start;
+        for i in key_start .. layer.keys.len() {
+
+            // NB: batch.layer.offs[i+1] must remain as is for the next iteration.
+            //     instead, we update batch.layer.offs[i]
+
+            let lower: usize = layer.offs[i];
+            let upper: usize = layer.offs[i+1];
+
+            layer.offs[i] = write_position;
+
+            // values should already be sorted, but some might now be empty.
+            for index in lower .. upper {
+                let val_lower: usize = layer.vals.offs[index];
+                let val_upper: usize = layer.vals.offs[index+1];
+                if val_lower < val_upper {
+                    layer.vals.keys.swap(write_position, index);
+                    layer.vals.offs[write_position+1] = layer.vals.offs[index+1];
+                    write_position += 1;
+                }
+            }
+        }
+        layer.vals.keys.truncate(write_position);
+        layer.vals.offs.truncate(write_position + 1);
+        layer.offs[layer.keys.len()] = write_position;
+
+        // 4. Remove empty keys.
+        let mut write_position = key_start;
+        for i in key_start .. layer.keys.len() {
+
+            let lower: usize = layer.offs[i];
+            let upper: usize = layer.offs[i+1];
+
+            if lower < upper {
+                layer.keys.swap(write_position, i);
+                // batch.layer.offs updated via `dedup` below; keeps me sane.
+                write_position += 1;
+            }
+        }
+        layer.offs.dedup();
+        layer.keys.truncate(write_position);
+        layer.offs.truncate(write_position+1);
+    }
+}
+
+/// State for an in-progress merge.
+pub struct HashValMerger<K, V, T, R>
+where
+    K: HashOrdered+Clone+'static,
+    V: Ord+Clone+'static,
+    T: Lattice+Ord+Clone+::std::fmt::Debug+'static,
+    R: Semigroup,
+{
+    // first batch, and position therein.
+    lower1: usize,
+    upper1: usize,
+    // second batch, and position therein.
+    lower2: usize,
+    upper2: usize,
+    // result that we are currently assembling.
+    result: <HashedLayer<K, OrderedLayer<V, OrderedLeaf<T, R>>> as Trie>::MergeBuilder,
+    description: Description<T>,
+    should_compact: bool,
+}
+
+impl<K, V, T, R> Merger<K, V, T, R, HashValBatch<K, V, T, R>> for HashValMerger<K, V, T, R>
+where
+    K: HashOrdered+Clone+'static,
+    V: Ord+Clone+'static,
+    T: Lattice+timely::progress::Timestamp+Ord+Clone+::std::fmt::Debug+'static,
+    R: Semigroup,
+{
+    fn new(batch1: &HashValBatch<K, V, T, R>, batch2: &HashValBatch<K, V, T, R>, compaction_frontier: Option<AntichainRef<T>>) -> Self {
+
+        assert!(batch1.upper() == batch2.lower());
+
+        let mut since = batch1.description().since().join(batch2.description().since());
+        if let Some(compaction_frontier) = compaction_frontier {
+            since = since.join(&compaction_frontier.to_owned());
+        }
+
+        let description = Description::new(batch1.lower().clone(), batch2.upper().clone(), since);
+
+        HashValMerger {
+            lower1: 0,
+            upper1: batch1.layer.keys(),
+            lower2: 0,
+            upper2: batch2.layer.keys(),
+            result: <<HashedLayer<K, OrderedLayer<V, OrderedLeaf<T, R>>> as Trie>::MergeBuilder as MergeBuilder>::with_capacity(&batch1.layer, &batch2.layer),
+            description: description,
+            should_compact: compaction_frontier.is_some(),
+        }
+    }
+    fn done(self) -> HashValBatch<K, V, T, R> {
+
+        assert!(self.lower1 == self.upper1);
+        assert!(self.lower2 == self.upper2);
+
+        HashValBatch {
+            layer: self.result.done(),
+            desc: self.description,
+        }
+    }
+    fn work(&mut self, source1: &HashValBatch<K,V,T,R>, source2: &HashValBatch<K,V,T,R>, fuel: &mut isize) {
+
+        let starting_updates = self.result.vals.vals.vals.len();
+        let mut effort = 0isize;
+
+        let initial_key_pos = self.result.keys.len();
+
+        // while both mergees are still active
+        while self.lower1 < self.upper1 && self.lower2 < self.upper2 && effort < *fuel {
+            self.result.merge_step((&source1.layer, &mut self.lower1, self.upper1), (&source2.layer, &mut self.lower2, self.upper2));
+            effort = (self.result.vals.vals.vals.len() - starting_updates) as isize;
+        }
+
+        // Merging is complete; only copying remains. Copying is probably faster than merging, so could take some liberties here.
+        if self.lower1 == self.upper1 || self.lower2 == self.upper2 {
+            // Limit merging by remaining fuel.
+            let remaining_fuel = *fuel - effort;
+            if remaining_fuel > 0 {
+                if self.lower1 < self.upper1 {
+                    let mut to_copy = remaining_fuel as usize;
+                    if to_copy < 1_000 { to_copy = 1_000; }
+                    if to_copy > (self.upper1 - self.lower1) { to_copy = self.upper1 - self.lower1; }
+                    self.result.copy_range(&source1.layer, self.lower1, self.lower1 + to_copy);
+                    self.lower1 += to_copy;
+                }
+                if self.lower2 < self.upper2 {
+                    let mut to_copy = remaining_fuel as usize;
+                    if to_copy < 1_000 { to_copy = 1_000; }
+                    if to_copy > (self.upper2 - self.lower2) { to_copy = self.upper2 - self.lower2; }
+                    self.result.copy_range(&source2.layer, self.lower2, self.lower2 + to_copy);
+                    self.lower2 += to_copy;
+                }
+            }
+        }
+
+        effort = (self.result.vals.vals.vals.len() - starting_updates) as isize;
+
+        // Copying vacant slots costs no effort, but the merge is incomplete while slots remain.
+        if (self.lower1 < self.upper1 || self.lower2 < self.upper2) && effort < *fuel {
+            effort = *fuel;
+        }
+
+        // if we are supplied a frontier, we should compact.
+        if self.should_compact {
+            HashValBatch::advance_builder_from(&mut self.result, self.description.since().borrow(), initial_key_pos);
+        }
+
+        *fuel -= effort;
+    }
+}
+
+/// A cursor for navigating a single layer.
+#[derive(Debug)]
+pub struct HashValCursor<V, T, R>
+where
+    V: Ord+Clone,
+    T: Lattice+Ord+Clone,
+    R: Semigroup,
+{
+    cursor: HashedCursor<OrderedLayer<V, OrderedLeaf<T, R>>>,
+}
+
+impl<K, V, T, R> Cursor<K, V, T, R> for HashValCursor<V, T, R>
+where
+    K: HashOrdered+Clone,
+    V: Ord+Clone,
+    T: Lattice+Ord+Clone,
+    R: Semigroup,
+{
+    type Storage = HashValBatch<K, V, T, R>;
+
+    fn key<'a>(&self, storage: &'a Self::Storage) -> &'a K { &self.cursor.key(&storage.layer) }
+    fn val<'a>(&self, storage: &'a Self::Storage) -> &'a V { &self.cursor.child.key(&storage.layer.vals) }
+    fn map_times<L: FnMut(&T, &R)>(&mut self, storage: &Self::Storage, mut logic: L) {
+        self.cursor.child.child.rewind(&storage.layer.vals.vals);
+        while self.cursor.child.child.valid(&storage.layer.vals.vals) {
+            logic(&self.cursor.child.child.key(&storage.layer.vals.vals).0, &self.cursor.child.child.key(&storage.layer.vals.vals).1);
+            self.cursor.child.child.step(&storage.layer.vals.vals);
+        }
+    }
+    fn key_valid(&self, storage: &Self::Storage) -> bool { self.cursor.valid(&storage.layer) }
+    fn val_valid(&self, storage: &Self::Storage) -> bool { self.cursor.child.valid(&storage.layer.vals) }
+    fn step_key(&mut self, storage: &Self::Storage){ self.cursor.step(&storage.layer); }
+    fn seek_key(&mut self, storage: &Self::Storage, key: &K) { self.cursor.seek(&storage.layer, key); }
+    fn step_val(&mut self, storage: &Self::Storage) { self.cursor.child.step(&storage.layer.vals); }
+    fn seek_val(&mut self, storage: &Self::Storage, val: &V) { self.cursor.child.seek(&storage.layer.vals, val); }
+    fn rewind_keys(&mut self, storage: &Self::Storage) { self.cursor.rewind(&storage.layer); }
+    fn rewind_vals(&mut self, storage: &Self::Storage) { self.cursor.child.rewind(&storage.layer.vals); }
+}
+
+
+/// A builder for creating layers from unsorted update tuples.
+pub struct HashValBuilder<K, V, T, R>
+where
+    K: HashOrdered,
+    V: Ord,
+    T: Ord+Lattice,
+    R: Semigroup,
+{
+    builder: HashedBuilder<K, OrderedBuilder<V, OrderedLeafBuilder<T, R>>>,
+}
+
+impl<K, V, T, R> Builder<K, V, T, R, HashValBatch<K, V, T, R>> for HashValBuilder<K, V, T, R>
+where
+    K: HashOrdered+Clone+'static,
+    V: Ord+Clone+'static,
+    T: Lattice+timely::progress::Timestamp+Ord+Clone+::std::fmt::Debug+'static,
+    R: Semigroup,
+{
+
+    fn new() -> Self {
+        HashValBuilder {
+            builder: HashedBuilder::<K, OrderedBuilder<V, OrderedLeafBuilder<T, R>>>::new()
+        }
+    }
+    fn with_capacity(cap: usize) -> Self {
+        HashValBuilder {
+            builder: <HashedBuilder<K, OrderedBuilder<V, OrderedLeafBuilder<T, R>>> as TupleBuilder>::with_capacity(cap)
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, (key, val, time, diff): (K, V, T, R)) {
+        self.builder.push_tuple((key, (val, (time, diff))));
+    }
+
+    #[inline(never)]
+    fn done(self, lower: Antichain<T>, upper: Antichain<T>, since: Antichain<T>) -> HashValBatch<K, V, T, R> {
+        HashValBatch {
+            layer: self.builder.done(),
+            desc: Description::new(lower, upper, since)
+        }
+    }
+}
+
+
+
+
+/// An immutable collection of update tuples, from a contiguous interval of logical times.
+#[derive(Debug, Abomonation)]
+pub struct HashKeyBatch<K, T, R>
+where
+    K: HashOrdered,
+    T: Lattice,
+{
+    /// Where all the dataz is.
+    pub layer: HashedLayer<K, OrderedLeaf<T, R>>,
+    /// Description of the update times this layer represents.
+    pub desc: Description<T>,
+}
+
+impl<K, T, R> BatchReader<K, (), T, R> for HashKeyBatch<K, T, R>
+where
+    K: HashOrdered+Clone+'static,
+    T: Lattice+Ord+Clone+'static,
+    R: Semigroup,
+{
+    type Cursor = HashKeyCursor<T, R>;
+    fn cursor(&self) -> Self::Cursor {
+        HashKeyCursor {
+            empty: (),
+            valid: true,
+            cursor: self.layer.cursor(),
+        }
+    }
+    fn len(&self) -> usize { <HashedLayer<K, OrderedLeaf<T, R>> as Trie>::tuples(&self.layer) }
+    fn description(&self) -> &Description<T> { &self.desc }
+    fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) {
+        use std::mem::size_of;
+        let keys = &self.layer;
+        let updates = &self.layer.vals.vals;
+        callback(keys.keys.len() * size_of::<Option<K>>(), keys.keys.capacity() * size_of::<Option<K>>());
+        callback(keys.offs.len() * size_of::<usize>(), keys.offs.capacity() * size_of::<usize>());
+        callback(updates.len() * size_of::<(T, R)>(), updates.capacity() * size_of::<(T, R)>());
+    }
+}
+
+impl<K, T, R> Batch<K, (), T, R> for HashKeyBatch<K, T, R>
+where
+    K: HashOrdered+Clone+'static,
+    T: Lattice+timely::progress::Timestamp+Ord+Clone+'static,
+    R: Semigroup,
+{
+    type Batcher = MergeBatcher<K, (), T, R, Self>;
+    type Builder = HashKeyBuilder<K, T, R>;
+    type Merger = HashKeyMerger<K, T, R>;
+
+    fn begin_merge(&self, other: &Self, compaction_frontier: Option<AntichainRef<T>>) -> Self::Merger {
+        HashKeyMerger::new(self, other, compaction_frontier)
+    }
+}
+
+impl<K, T, R> HashKeyBatch<K, T, R>
+where
+    K: HashOrdered+Clone+'static,
+    T: Lattice+Ord+Clone+'static,
+    R: Semigroup,
+{
+    fn advance_builder_from(layer: &mut HashedBuilder<K, OrderedLeafBuilder<T, R>>, frontier: AntichainRef<T>, key_pos: usize) {
+
+        let key_start = key_pos;
+        let time_start: usize = layer.offs[key_pos];
+
+        // 1. For each (time, diff) pair, advance the time.
+        for i in time_start .. layer.vals.vals.len() {
+            layer.vals.vals[i].0.advance_by(frontier);
+        }
+
+        // 2. For each `(key, off)` pair, sort the range, compact, and rewrite `off`.
+        //    This may leave `key` with an empty range; filtering happens in step 3.
+        let mut write_position = time_start;
+        for i in key_start .. layer.keys.len() {
+
+            // NB: batch.layer.offs[i+1] will be used next iteration, and should not be changed.
+            //     we will change batch.layer.offs[i] in this iteration, from `write_position`'s
+            //     initial value.
+
+            let lower: usize = layer.offs[i];
+            let upper: usize = layer.offs[i+1];
+
+            layer.offs[i] = write_position;
+
+            let updates = &mut layer.vals.vals[..];
+
+            // sort the range by the times (ignore the diffs; they will collapse).
+            let count = crate::consolidation::consolidate_slice(&mut updates[lower .. upper]);
+
+            for index in lower .. (lower + count) {
+                updates.swap(write_position, index);
+                write_position += 1;
+            }
+        }
+        layer.vals.vals.truncate(write_position);
+        layer.offs[layer.keys.len()] = write_position;
+
+        // 3. Remove empty keys.
+        let mut write_position = key_start;
+        for i in key_start .. layer.keys.len() {
+
+            let lower: usize = layer.offs[i];
+            let upper: usize = layer.offs[i+1];
+
+            if lower < upper {
+                layer.keys.swap(write_position, i);
+                // batch.layer.offs updated via `dedup` below; keeps me sane.
+                write_position += 1;
+            }
+        }
+        layer.offs.dedup();
+        layer.keys.truncate(write_position);
+        layer.offs.truncate(write_position+1);
+    }
+}
+
+/// State for an in-progress merge.
+pub struct HashKeyMerger<K, T, R>
+where
+    K: HashOrdered+Clone+'static,
+    T: Lattice+Ord+Clone+'static,
+    R: Semigroup,
+{
+    // first batch, and position therein.
+    lower1: usize,
+    upper1: usize,
+    // second batch, and position therein.
+    lower2: usize,
+    upper2: usize,
+    // result that we are currently assembling.
+    result: <HashedLayer<K, OrderedLeaf<T, R>> as Trie>::MergeBuilder,
+    description: Description<T>,
+    should_compact: bool,
+}
+
+impl<K, T, R> Merger<K, (), T, R, HashKeyBatch<K, T, R>> for HashKeyMerger<K, T, R>
+where
+    K: HashOrdered+Clone+'static,
+    T: Lattice+timely::progress::Timestamp+Ord+Clone+'static,
+    R: Semigroup,
+{
+    fn new(batch1: &HashKeyBatch<K, T, R>, batch2: &HashKeyBatch<K, T, R>, compaction_frontier: Option<AntichainRef<T>>) -> Self {
+
+        assert!(batch1.upper() == batch2.lower());
+
+        let mut since = batch1.description().since().join(batch2.description().since());
+        if let Some(compaction_frontier) = compaction_frontier {
+            since = since.join(&compaction_frontier.to_owned());
+        }
+
+        let description = Description::new(batch1.lower().clone(), batch2.upper().clone(), since);
+
+        HashKeyMerger {
+            lower1: 0,
+            upper1: batch1.layer.keys(),
+            lower2: 0,
+            upper2: batch2.layer.keys(),
+            result: <<HashedLayer<K, OrderedLeaf<T, R>> as Trie>::MergeBuilder as MergeBuilder>::with_capacity(&batch1.layer, &batch2.layer),
+            description: description,
+            should_compact: compaction_frontier.is_some(),
+        }
+    }
+    fn done(self) -> HashKeyBatch<K, T, R> {
+
+        assert!(self.lower1 == self.upper1);
+        assert!(self.lower2 == self.upper2);
+
+        HashKeyBatch {
+            layer: self.result.done(),
+            desc: self.description,
+        }
+    }
+    fn work(&mut self, source1: &HashKeyBatch<K,T,R>, source2: &HashKeyBatch<K,T,R>, fuel: &mut isize) {
+
+        let starting_updates = self.result.vals.vals.len();
+        let mut effort = 0isize;
+
+        let initial_key_pos = self.result.keys.len();
+
+        // while both mergees are still active
+        while self.lower1 < self.upper1 && self.lower2 < self.upper2 && effort < *fuel {
+            self.result.merge_step((&source1.layer, &mut self.lower1, self.upper1), (&source2.layer, &mut self.lower2, self.upper2));
+            effort = (self.result.vals.vals.len() - starting_updates) as isize;
+        }
+
+        // Merging is complete; only copying remains. Copying is probably faster than merging, so could take some liberties here.
+        if self.lower1 == self.upper1 || self.lower2 == self.upper2 {
+            // Limit merging by remaining fuel.
+            let remaining_fuel = *fuel - effort;
+            if remaining_fuel > 0 {
+                if self.lower1 < self.upper1 {
+                    let mut to_copy = remaining_fuel as usize;
+                    if to_copy < 1_000 { to_copy = 1_000; }
+                    if to_copy > (self.upper1 - self.lower1) { to_copy = self.upper1 - self.lower1; }
+                    self.result.copy_range(&source1.layer, self.lower1, self.lower1 + to_copy);
+                    self.lower1 += to_copy;
+                }
+                if self.lower2 < self.upper2 {
+                    let mut to_copy = remaining_fuel as usize;
+                    if to_copy < 1_000 { to_copy = 1_000; }
+                    if to_copy > (self.upper2 - self.lower2) { to_copy = self.upper2 - self.lower2; }
+                    self.result.copy_range(&source2.layer, self.lower2, self.lower2 + to_copy);
+                    self.lower2 += to_copy;
+                }
+            }
+        }
+
+        effort = (self.result.vals.vals.len() - starting_updates) as isize;
+
+        // Copying vacant slots costs no effort, but the merge is incomplete while slots remain.
+        if (self.lower1 < self.upper1 || self.lower2 < self.upper2) && effort < *fuel {
+            effort = *fuel;
+        }
+
+        // if we are supplied a frontier, we should compact.
+        if self.should_compact {
+            HashKeyBatch::advance_builder_from(&mut self.result, self.description.since().borrow(), initial_key_pos);
+        }
+
+        *fuel -= effort;
+    }
+}
+
+
+/// A cursor for navigating a single layer.
+#[derive(Debug)]
+pub struct HashKeyCursor<T: Lattice+Ord+Clone, R: Semigroup> {
+    valid: bool,
+    empty: (),
+    cursor: HashedCursor<OrderedLeaf<T, R>>,
+}
+
+impl<K, T, R> Cursor<K, (), T, R> for HashKeyCursor<T, R>
+where
+    K: HashOrdered+Clone,
+    T: Lattice+Ord+Clone,
+    R: Semigroup,
+{
+    type Storage = HashKeyBatch<K, T, R>;
+
+    fn key<'a>(&self, storage: &'a Self::Storage) -> &'a K { &self.cursor.key(&storage.layer) }
+    fn val<'a>(&self, _storage: &'a Self::Storage) -> &'a () { unsafe { ::std::mem::transmute(&self.empty) } }
+    fn map_times<L: FnMut(&T, &R)>(&mut self, storage: &Self::Storage, mut logic: L) {
+        self.cursor.child.rewind(&storage.layer.vals);
+        while self.cursor.child.valid(&storage.layer.vals) {
+            logic(&self.cursor.child.key(&storage.layer.vals).0, &self.cursor.child.key(&storage.layer.vals).1);
+            self.cursor.child.step(&storage.layer.vals);
+        }
+    }
+    fn key_valid(&self, storage: &Self::Storage) -> bool { self.cursor.valid(&storage.layer) }
+    fn val_valid(&self, _storage: &Self::Storage) -> bool { self.valid }
+    fn step_key(&mut self, storage: &Self::Storage){ self.cursor.step(&storage.layer); self.valid = true; }
+    fn seek_key(&mut self, storage: &Self::Storage, key: &K) { self.cursor.seek(&storage.layer, key); self.valid = true; }
+    fn step_val(&mut self, _storage: &Self::Storage) { self.valid = false; }
+    fn seek_val(&mut self, _storage: &Self::Storage, _val: &()) { }
+    fn rewind_keys(&mut self, storage: &Self::Storage) { self.cursor.rewind(&storage.layer); self.valid = true; }
+    fn rewind_vals(&mut self, _storage: &Self::Storage) { self.valid = true; }
+}
+
+
+/// A builder for creating layers from unsorted update tuples.
+pub struct HashKeyBuilder<K, T, R>
+where
+    K: HashOrdered,
+    T: Ord+Lattice,
+    R: Semigroup,
+{
+    builder: HashedBuilder<K, OrderedLeafBuilder<T, R>>,
+}
+
+impl<K, T, R> Builder<K, (), T, R, HashKeyBatch<K, T, R>> for HashKeyBuilder<K, T, R>
+where
+    K: HashOrdered+Clone+'static,
+    T: Lattice+timely::progress::Timestamp+Ord+Clone+'static,
+    R: Semigroup,
+{
+
+    fn new() -> Self {
+        HashKeyBuilder {
+            builder: HashedBuilder::<K, OrderedLeafBuilder<T, R>>::new()
+        }
+    }
+
+    fn with_capacity(cap: usize) -> Self {
+        HashKeyBuilder {
+            builder: <HashedBuilder<K, OrderedLeafBuilder<T, R>> as TupleBuilder>::with_capacity(cap)
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, (key, _, time, diff): (K, (), T, R)) {
+        self.builder.push_tuple((key, (time, diff)));
+    }
+
+    #[inline(never)]
+    fn done(self, lower: Antichain<T>, upper: Antichain<T>, since: Antichain<T>) -> HashKeyBatch<K, T, R> {
+        HashKeyBatch {
+            layer: self.builder.done(),
+            desc: Description::new(lower, upper, since)
+        }
+    }
+}
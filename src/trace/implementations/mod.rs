@@ -17,6 +17,20 @@
 //! *  The `time` module is meant for collections with a single time value. This can remove repetition
 //!    from the representation, at the cost of requiring more instances and run-time merging.
 //!
+//! *  The `columnar` module is meant for data whose records own heap allocations (for example `String`
+//!    or `Vec<T>`). It organizes data like `ord`, but copies the records into large shared regions
+//!    rather than maintaining an allocation per record.
+//!
+//! *  The `hashed` module organizes data like `ord`, but lays keys out in a Robin Hood hash table
+//!    ordered by hash value. This makes seeking a key an expected constant time operation, at the cost
+//!    of requiring `HashOrdered` keys and some vacant slots.
+//!
+//! *  The `spilled` module wraps another batch type, and writes batches produced by merging to local
+//!    files once they exceed a configurable size, reading them back through memory mapped files.
+//!
+//! *  The `background` module wraps another batch type in an `Arc`, and hands large merges to a pool
+//!    of helper threads rather than performing them on the worker thread.
+//!
 //! *  The `base` module is meant for collections with a single time value equivalent to the least time.
 //!    These collections must always accumulate to non-negative collections, and as such we can indicate
 //!    the frequency of an element by its multiplicity. This removes both the time and weight from the
@@ -39,9 +53,18 @@
 //! trace, rather than just a batch of the type merged.
 
 pub mod spine_fueled;
+pub mod effort;
 
 mod merge_batcher;
 
 pub use self::merge_batcher::MergeBatcher as Batcher;
 
+pub mod radix_batcher;
+
 pub mod ord;
+pub mod bloom;
+pub mod columnation;
+pub mod columnar;
+pub mod hashed;
+pub mod spilled;
+pub mod background;
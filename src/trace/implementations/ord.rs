@@ -31,25 +31,35 @@ use trace::layers::MergeBuilder;
 // use super::spine::Spine;
 use super::spine_fueled::Spine;
 use super::merge_batcher::MergeBatcher;
+use super::bloom::{KeyFilter, BloomFilter};
 
 use abomonation::abomonated::Abomonated;
 
 /// A trace implementation using a spine of ordered lists.
 pub type OrdValSpine<K, V, T, R, O=usize> = Spine<K, V, T, R, Rc<OrdValBatch<K, V, T, R, O>>>;
 
+/// A trace implementation using a spine of ordered lists, whose batches maintain Bloom filters of their keys.
+pub type OrdValSpineBloom<K, V, T, R, O=usize> = Spine<K, V, T, R, Rc<OrdValBatch<K, V, T, R, O, BloomFilter>>>;
+
 /// A trace implementation using a spine of abomonated ordered lists.
 pub type OrdValSpineAbom<K, V, T, R, O=usize> = Spine<K, V, T, R, Rc<Abomonated<OrdValBatch<K, V, T, R, O>, Vec<u8>>>>;
 
 /// A trace implementation for empty values using a spine of ordered lists.
 pub type OrdKeySpine<K, T, R, O=usize> = Spine<K, (), T, R, Rc<OrdKeyBatch<K, T, R, O>>>;
 
+/// A trace implementation for empty values using a spine of ordered lists, whose batches maintain Bloom filters of their keys.
+pub type OrdKeySpineBloom<K, T, R, O=usize> = Spine<K, (), T, R, Rc<OrdKeyBatch<K, T, R, O, BloomFilter>>>;
+
 /// A trace implementation for empty values using a spine of abomonated ordered lists.
 pub type OrdKeySpineAbom<K, T, R, O=usize> = Spine<K, (), T, R, Rc<Abomonated<OrdKeyBatch<K, T, R, O>, Vec<u8>>>>;
 
 
 /// An immutable collection of update tuples, from a contiguous interval of logical times.
+///
+/// The batch records the least and greatest of its keys, and a summary `F` of its keys, which
+/// cursors use to report keys the batch cannot contain.
 #[derive(Debug, Abomonation)]
-pub struct OrdValBatch<K, V, T, R, O=usize>
+pub struct OrdValBatch<K, V, T, R, O=usize, F=()>
 where
     K: Ord,
     V: Ord,
@@ -60,47 +70,76 @@ where
     pub layer: OrderedLayer<K, OrderedLayer<V, OrderedLeaf<T, R>, O>, O>,
     /// Description of the update times this layer represents.
     pub desc: Description<T>,
+    /// The least and greatest keys of the batch, if it has any.
+    pub bounds: Option<(K, K)>,
+    /// A summary of the keys of the batch.
+    pub filter: F,
 }
 
-impl<K, V, T, R, O> BatchReader<K, V, T, R> for OrdValBatch<K, V, T, R, O>
+impl<K, V, T, R, O, F> BatchReader<K, V, T, R> for OrdValBatch<K, V, T, R, O, F>
 where
     K: Ord+Clone+'static,
     V: Ord+Clone+'static,
     T: Lattice+Ord+Clone+'static,
     R: Semigroup,
-    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug
+    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug,
+    F: KeyFilter<K>,
 {
-    type Cursor = OrdValCursor<V, T, R, O>;
-    fn cursor(&self) -> Self::Cursor { OrdValCursor { cursor: self.layer.cursor() } }
+    type Cursor = OrdValCursor<V, T, R, O, F>;
+    fn cursor(&self) -> Self::Cursor { OrdValCursor { cursor: self.layer.cursor(), phantom: PhantomData } }
     fn len(&self) -> usize { <OrderedLayer<K, OrderedLayer<V, OrderedLeaf<T, R>, O>, O> as Trie>::tuples(&self.layer) }
     fn description(&self) -> &Description<T> { &self.desc }
+    fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) {
+        use std::mem::size_of;
+        let keys = &self.layer;
+        let vals = &self.layer.vals;
+        let updates = &self.layer.vals.vals.vals;
+        callback(keys.keys.len() * size_of::<K>(), keys.keys.capacity() * size_of::<K>());
+        callback(keys.offs.len() * size_of::<O>(), keys.offs.capacity() * size_of::<O>());
+        callback(vals.keys.len() * size_of::<V>(), vals.keys.capacity() * size_of::<V>());
+        callback(vals.offs.len() * size_of::<O>(), vals.offs.capacity() * size_of::<O>());
+        callback(updates.len() * size_of::<(T, R)>(), updates.capacity() * size_of::<(T, R)>());
+        callback(self.filter.heap_size(), self.filter.heap_size());
+    }
 }
 
-impl<K, V, T, R, O> Batch<K, V, T, R> for OrdValBatch<K, V, T, R, O>
+impl<K, V, T, R, O, F> Batch<K, V, T, R> for OrdValBatch<K, V, T, R, O, F>
 where
     K: Ord+Clone+'static,
     V: Ord+Clone+'static,
     T: Lattice+timely::progress::Timestamp+Ord+Clone+::std::fmt::Debug+'static,
     R: Semigroup,
-    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug
+    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug,
+    F: KeyFilter<K>,
 {
     type Batcher = MergeBatcher<K, V, T, R, Self>;
-    type Builder = OrdValBuilder<K, V, T, R, O>;
-    type Merger = OrdValMerger<K, V, T, R, O>;
+    type Builder = OrdValBuilder<K, V, T, R, O, F>;
+    type Merger = OrdValMerger<K, V, T, R, O, F>;
 
     fn begin_merge(&self, other: &Self, compaction_frontier: Option<AntichainRef<T>>) -> Self::Merger {
         OrdValMerger::new(self, other, compaction_frontier)
     }
 }
 
-impl<K, V, T, R, O> OrdValBatch<K, V, T, R, O>
+impl<K, V, T, R, O, F> OrdValBatch<K, V, T, R, O, F>
 where
     K: Ord+Clone+'static,
     V: Ord+Clone+'static,
     T: Lattice+Ord+Clone+::std::fmt::Debug+'static,
     R: Semigroup,
-    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug
+    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug,
+    F: KeyFilter<K>,
 {
+    /// Assembles a batch from a layer, summarizing its keys.
+    fn from_layer(layer: OrderedLayer<K, OrderedLayer<V, OrderedLeaf<T, R>, O>, O>, desc: Description<T>) -> Self {
+        let bounds = match (layer.keys.first(), layer.keys.last()) {
+            (Some(min), Some(max)) => Some((min.clone(), max.clone())),
+            _ => None,
+        };
+        let filter = F::build(&layer.keys[..]);
+        OrdValBatch { layer, desc, bounds, filter }
+    }
+
     fn advance_builder_from(layer: &mut OrderedBuilder<K, OrderedBuilder<V, OrderedLeafBuilder<T, R>, O>, O>, frontier: AntichainRef<T>, key_pos: usize) {
 
         let key_start = key_pos;
@@ -195,7 +234,7 @@ where
 }
 
 /// State for an in-progress merge.
-pub struct OrdValMerger<K, V, T, R, O=usize>
+pub struct OrdValMerger<K, V, T, R, O=usize, F=()>
 where
     K: Ord+Clone+'static,
     V: Ord+Clone+'static,
@@ -213,17 +252,19 @@ where
     result: <OrderedLayer<K, OrderedLayer<V, OrderedLeaf<T, R>, O>, O> as Trie>::MergeBuilder,
     description: Description<T>,
     should_compact: bool,
+    phantom: PhantomData<F>,
 }
 
-impl<K, V, T, R, O> Merger<K, V, T, R, OrdValBatch<K, V, T, R, O>> for OrdValMerger<K, V, T, R, O>
+impl<K, V, T, R, O, F> Merger<K, V, T, R, OrdValBatch<K, V, T, R, O, F>> for OrdValMerger<K, V, T, R, O, F>
 where
     K: Ord+Clone+'static,
     V: Ord+Clone+'static,
     T: Lattice+timely::progress::Timestamp+Ord+Clone+::std::fmt::Debug+'static,
     R: Semigroup,
-    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug
+    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug,
+    F: KeyFilter<K>,
 {
-    fn new(batch1: &OrdValBatch<K, V, T, R, O>, batch2: &OrdValBatch<K, V, T, R, O>, compaction_frontier: Option<AntichainRef<T>>) -> Self {
+    fn new(batch1: &OrdValBatch<K, V, T, R, O, F>, batch2: &OrdValBatch<K, V, T, R, O, F>, compaction_frontier: Option<AntichainRef<T>>) -> Self {
 
         assert!(batch1.upper() == batch2.lower());
 
@@ -242,19 +283,17 @@ where
             result: <<OrderedLayer<K, OrderedLayer<V, OrderedLeaf<T, R>, O>, O> as Trie>::MergeBuilder as MergeBuilder>::with_capacity(&batch1.layer, &batch2.layer),
             description: description,
             should_compact: compaction_frontier.is_some(),
+            phantom: PhantomData,
         }
     }
-    fn done(self) -> OrdValBatch<K, V, T, R, O> {
+    fn done(self) -> OrdValBatch<K, V, T, R, O, F> {
 
         assert!(self.lower1 == self.upper1);
         assert!(self.lower2 == self.upper2);
 
-        OrdValBatch {
-            layer: self.result.done(),
-            desc: self.description,
-        }
+        OrdValBatch::from_layer(self.result.done(), self.description)
     }
-    fn work(&mut self, source1: &OrdValBatch<K,V,T,R,O>, source2: &OrdValBatch<K,V,T,R,O>, fuel: &mut isize) {
+    fn work(&mut self, source1: &OrdValBatch<K,V,T,R,O,F>, source2: &OrdValBatch<K,V,T,R,O,F>, fuel: &mut isize) {
 
         let starting_updates = self.result.vals.vals.vals.len();
         let mut effort = 0isize;
@@ -293,7 +332,7 @@ where
 
         // if we are supplied a frontier, we should compact.
         if self.should_compact {
-            OrdValBatch::advance_builder_from(&mut self.result, self.description.since().borrow(), initial_key_pos);
+            <OrdValBatch<K, V, T, R, O, F>>::advance_builder_from(&mut self.result, self.description.since().borrow(), initial_key_pos);
         }
 
         *fuel -= effort;
@@ -306,7 +345,7 @@ where
 
 /// A cursor for navigating a single layer.
 #[derive(Debug)]
-pub struct OrdValCursor<V, T, R, O=usize>
+pub struct OrdValCursor<V, T, R, O=usize, F=()>
 where
     V: Ord+Clone,
     T: Lattice+Ord+Clone,
@@ -314,17 +353,19 @@ where
     O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug
 {
     cursor: OrderedCursor<OrderedLayer<V, OrderedLeaf<T, R>, O>>,
+    phantom: PhantomData<F>,
 }
 
-impl<K, V, T, R, O> Cursor<K, V, T, R> for OrdValCursor<V, T, R, O>
+impl<K, V, T, R, O, F> Cursor<K, V, T, R> for OrdValCursor<V, T, R, O, F>
 where
     K: Ord+Clone,
     V: Ord+Clone,
     T: Lattice+Ord+Clone,
     R: Semigroup,
-    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug
+    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug,
+    F: KeyFilter<K>,
 {
-    type Storage = OrdValBatch<K, V, T, R, O>;
+    type Storage = OrdValBatch<K, V, T, R, O, F>;
 
     fn key<'a>(&self, storage: &'a Self::Storage) -> &'a K { &self.cursor.key(&storage.layer) }
     fn val<'a>(&self, storage: &'a Self::Storage) -> &'a V { &self.cursor.child.key(&storage.layer.vals) }
@@ -339,6 +380,12 @@ where
     fn val_valid(&self, storage: &Self::Storage) -> bool { self.cursor.child.valid(&storage.layer.vals) }
     fn step_key(&mut self, storage: &Self::Storage){ self.cursor.step(&storage.layer); }
     fn seek_key(&mut self, storage: &Self::Storage, key: &K) { self.cursor.seek(&storage.layer, key); }
+    fn may_contain(&self, storage: &Self::Storage, key: &K) -> bool {
+        match &storage.bounds {
+            Some((min, max)) => min <= key && key <= max && storage.filter.may_contain(key),
+            None => false,
+        }
+    }
     fn step_val(&mut self, storage: &Self::Storage) { self.cursor.child.step(&storage.layer.vals); }
     fn seek_val(&mut self, storage: &Self::Storage, val: &V) { self.cursor.child.seek(&storage.layer.vals, val); }
     fn rewind_keys(&mut self, storage: &Self::Storage) { self.cursor.rewind(&storage.layer); }
@@ -347,7 +394,7 @@ where
 
 
 /// A builder for creating layers from unsorted update tuples.
-pub struct OrdValBuilder<K, V, T, R, O=usize>
+pub struct OrdValBuilder<K, V, T, R, O=usize, F=()>
 where
     K: Ord,
     V: Ord,
@@ -356,25 +403,29 @@ where
     O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug
 {
     builder: OrderedBuilder<K, OrderedBuilder<V, OrderedLeafBuilder<T, R>, O>, O>,
+    phantom: PhantomData<F>,
 }
 
-impl<K, V, T, R, O> Builder<K, V, T, R, OrdValBatch<K, V, T, R, O>> for OrdValBuilder<K, V, T, R, O>
+impl<K, V, T, R, O, F> Builder<K, V, T, R, OrdValBatch<K, V, T, R, O, F>> for OrdValBuilder<K, V, T, R, O, F>
 where
     K: Ord+Clone+'static,
     V: Ord+Clone+'static,
     T: Lattice+timely::progress::Timestamp+Ord+Clone+::std::fmt::Debug+'static,
     R: Semigroup,
-    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug
+    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug,
+    F: KeyFilter<K>,
 {
 
     fn new() -> Self {
         OrdValBuilder {
-            builder: OrderedBuilder::<K, OrderedBuilder<V, OrderedLeafBuilder<T, R>, O>, O>::new()
+            builder: OrderedBuilder::<K, OrderedBuilder<V, OrderedLeafBuilder<T, R>, O>, O>::new(),
+            phantom: PhantomData,
         }
     }
     fn with_capacity(cap: usize) -> Self {
         OrdValBuilder {
-            builder: <OrderedBuilder<K, OrderedBuilder<V, OrderedLeafBuilder<T, R>, O>, O> as TupleBuilder>::with_capacity(cap)
+            builder: <OrderedBuilder<K, OrderedBuilder<V, OrderedLeafBuilder<T, R>, O>, O> as TupleBuilder>::with_capacity(cap),
+            phantom: PhantomData,
         }
     }
 
@@ -384,11 +435,8 @@ where
     }
 
     #[inline(never)]
-    fn done(self, lower: Antichain<T>, upper: Antichain<T>, since: Antichain<T>) -> OrdValBatch<K, V, T, R, O> {
-        OrdValBatch {
-            layer: self.builder.done(),
-            desc: Description::new(lower, upper, since)
-        }
+    fn done(self, lower: Antichain<T>, upper: Antichain<T>, since: Antichain<T>) -> OrdValBatch<K, V, T, R, O, F> {
+        OrdValBatch::from_layer(self.builder.done(), Description::new(lower, upper, since))
     }
 }
 
@@ -396,8 +444,11 @@ where
 
 
 /// An immutable collection of update tuples, from a contiguous interval of logical times.
+///
+/// The batch records the least and greatest of its keys, and a summary `F` of its keys, which
+/// cursors use to report keys the batch cannot contain.
 #[derive(Debug, Abomonation)]
-pub struct OrdKeyBatch<K, T, R, O=usize>
+pub struct OrdKeyBatch<K, T, R, O=usize, F=()>
 where
     K: Ord,
     T: Lattice,
@@ -407,16 +458,21 @@ where
     pub layer: OrderedLayer<K, OrderedLeaf<T, R>, O>,
     /// Description of the update times this layer represents.
     pub desc: Description<T>,
+    /// The least and greatest keys of the batch, if it has any.
+    pub bounds: Option<(K, K)>,
+    /// A summary of the keys of the batch.
+    pub filter: F,
 }
 
-impl<K, T, R, O> BatchReader<K, (), T, R> for OrdKeyBatch<K, T, R, O>
+impl<K, T, R, O, F> BatchReader<K, (), T, R> for OrdKeyBatch<K, T, R, O, F>
 where
     K: Ord+Clone+'static,
     T: Lattice+Ord+Clone+'static,
     R: Semigroup,
-    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug
+    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug,
+    F: KeyFilter<K>,
 {
-    type Cursor = OrdKeyCursor<T, R, O>;
+    type Cursor = OrdKeyCursor<T, R, O, F>;
     fn cursor(&self) -> Self::Cursor {
         OrdKeyCursor {
             empty: (),
@@ -427,31 +483,52 @@ where
     }
     fn len(&self) -> usize { <OrderedLayer<K, OrderedLeaf<T, R>, O> as Trie>::tuples(&self.layer) }
     fn description(&self) -> &Description<T> { &self.desc }
+    fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) {
+        use std::mem::size_of;
+        let keys = &self.layer;
+        let updates = &self.layer.vals.vals;
+        callback(keys.keys.len() * size_of::<K>(), keys.keys.capacity() * size_of::<K>());
+        callback(keys.offs.len() * size_of::<O>(), keys.offs.capacity() * size_of::<O>());
+        callback(updates.len() * size_of::<(T, R)>(), updates.capacity() * size_of::<(T, R)>());
+        callback(self.filter.heap_size(), self.filter.heap_size());
+    }
 }
 
-impl<K, T, R, O> Batch<K, (), T, R> for OrdKeyBatch<K, T, R, O>
+impl<K, T, R, O, F> Batch<K, (), T, R> for OrdKeyBatch<K, T, R, O, F>
 where
     K: Ord+Clone+'static,
     T: Lattice+timely::progress::Timestamp+Ord+Clone+'static,
     R: Semigroup,
-    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug
+    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug,
+    F: KeyFilter<K>,
 {
     type Batcher = MergeBatcher<K, (), T, R, Self>;
-    type Builder = OrdKeyBuilder<K, T, R, O>;
-    type Merger = OrdKeyMerger<K, T, R, O>;
+    type Builder = OrdKeyBuilder<K, T, R, O, F>;
+    type Merger = OrdKeyMerger<K, T, R, O, F>;
 
     fn begin_merge(&self, other: &Self, compaction_frontier: Option<AntichainRef<T>>) -> Self::Merger {
         OrdKeyMerger::new(self, other, compaction_frontier)
     }
 }
 
-impl<K, T, R, O> OrdKeyBatch<K, T, R, O>
+impl<K, T, R, O, F> OrdKeyBatch<K, T, R, O, F>
 where
     K: Ord+Clone+'static,
     T: Lattice+Ord+Clone+'static,
     R: Semigroup,
-    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug
+    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug,
+    F: KeyFilter<K>,
 {
+    /// Assembles a batch from a layer, summarizing its keys.
+    fn from_layer(layer: OrderedLayer<K, OrderedLeaf<T, R>, O>, desc: Description<T>) -> Self {
+        let bounds = match (layer.keys.first(), layer.keys.last()) {
+            (Some(min), Some(max)) => Some((min.clone(), max.clone())),
+            _ => None,
+        };
+        let filter = F::build(&layer.keys[..]);
+        OrdKeyBatch { layer, desc, bounds, filter }
+    }
+
     fn advance_builder_from(layer: &mut OrderedBuilder<K, OrderedLeafBuilder<T, R>, O>, frontier: AntichainRef<T>, key_pos: usize) {
 
         let key_start = key_pos;
@@ -516,7 +593,7 @@ where
 }
 
 /// State for an in-progress merge.
-pub struct OrdKeyMerger<K, T, R, O=usize>
+pub struct OrdKeyMerger<K, T, R, O=usize, F=()>
 where
     K: Ord+Clone+'static,
     T: Lattice+Ord+Clone+'static,
@@ -533,16 +610,18 @@ where
     result: <OrderedLayer<K, OrderedLeaf<T, R>, O> as Trie>::MergeBuilder,
     description: Description<T>,
     should_compact: bool,
+    phantom: PhantomData<F>,
 }
 
-impl<K, T, R, O> Merger<K, (), T, R, OrdKeyBatch<K, T, R, O>> for OrdKeyMerger<K, T, R, O>
+impl<K, T, R, O, F> Merger<K, (), T, R, OrdKeyBatch<K, T, R, O, F>> for OrdKeyMerger<K, T, R, O, F>
 where
     K: Ord+Clone+'static,
     T: Lattice+timely::progress::Timestamp+Ord+Clone+'static,
     R: Semigroup,
-    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug
+    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug,
+    F: KeyFilter<K>,
 {
-    fn new(batch1: &OrdKeyBatch<K, T, R, O>, batch2: &OrdKeyBatch<K, T, R, O>, compaction_frontier: Option<AntichainRef<T>>) -> Self {
+    fn new(batch1: &OrdKeyBatch<K, T, R, O, F>, batch2: &OrdKeyBatch<K, T, R, O, F>, compaction_frontier: Option<AntichainRef<T>>) -> Self {
 
         assert!(batch1.upper() == batch2.lower());
 
@@ -561,19 +640,17 @@ where
             result: <<OrderedLayer<K, OrderedLeaf<T, R>, O> as Trie>::MergeBuilder as MergeBuilder>::with_capacity(&batch1.layer, &batch2.layer),
             description: description,
             should_compact: compaction_frontier.is_some(),
+            phantom: PhantomData,
         }
     }
-    fn done(self) -> OrdKeyBatch<K, T, R, O> {
+    fn done(self) -> OrdKeyBatch<K, T, R, O, F> {
 
         assert!(self.lower1 == self.upper1);
         assert!(self.lower2 == self.upper2);
 
-        OrdKeyBatch {
-            layer: self.result.done(),
-            desc: self.description,
-        }
+        OrdKeyBatch::from_layer(self.result.done(), self.description)
     }
-    fn work(&mut self, source1: &OrdKeyBatch<K,T,R,O>, source2: &OrdKeyBatch<K,T,R,O>, fuel: &mut isize) {
+    fn work(&mut self, source1: &OrdKeyBatch<K,T,R,O,F>, source2: &OrdKeyBatch<K,T,R,O,F>, fuel: &mut isize) {
 
         let starting_updates = self.result.vals.vals.len();
         let mut effort = 0isize;
@@ -618,7 +695,7 @@ where
 
         // if we are supplied a frontier, we should compact.
         if self.should_compact {
-            OrdKeyBatch::advance_builder_from(&mut self.result, self.description.since().borrow(), initial_key_pos);
+            <OrdKeyBatch<K, T, R, O, F>>::advance_builder_from(&mut self.result, self.description.since().borrow(), initial_key_pos);
         }
 
         *fuel -= effort;
@@ -632,22 +709,23 @@ where
 
 /// A cursor for navigating a single layer.
 #[derive(Debug)]
-pub struct OrdKeyCursor<T: Lattice+Ord+Clone, R: Semigroup, O=usize> {
+pub struct OrdKeyCursor<T: Lattice+Ord+Clone, R: Semigroup, O=usize, F=()> {
     valid: bool,
     empty: (),
     cursor: OrderedCursor<OrderedLeaf<T, R>>,
-    phantom: PhantomData<O>
+    phantom: PhantomData<(O, F)>
 }
 
-impl<K, T, R, O> Cursor<K, (), T, R> for OrdKeyCursor<T, R, O>
+impl<K, T, R, O, F> Cursor<K, (), T, R> for OrdKeyCursor<T, R, O, F>
 where
     K: Ord+Clone,
     T: Lattice+Ord+Clone,
     R: Semigroup,
-    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug
+    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug,
+    F: KeyFilter<K>,
 {
 
-    type Storage = OrdKeyBatch<K, T, R, O>;
+    type Storage = OrdKeyBatch<K, T, R, O, F>;
 
     fn key<'a>(&self, storage: &'a Self::Storage) -> &'a K { &self.cursor.key(&storage.layer) }
     fn val<'a>(&self, _storage: &'a Self::Storage) -> &'a () { unsafe { ::std::mem::transmute(&self.empty) } }
@@ -662,6 +740,12 @@ where
     fn val_valid(&self, _storage: &Self::Storage) -> bool { self.valid }
     fn step_key(&mut self, storage: &Self::Storage){ self.cursor.step(&storage.layer); self.valid = true; }
     fn seek_key(&mut self, storage: &Self::Storage, key: &K) { self.cursor.seek(&storage.layer, key); self.valid = true; }
+    fn may_contain(&self, storage: &Self::Storage, key: &K) -> bool {
+        match &storage.bounds {
+            Some((min, max)) => min <= key && key <= max && storage.filter.may_contain(key),
+            None => false,
+        }
+    }
     fn step_val(&mut self, _storage: &Self::Storage) { self.valid = false; }
     fn seek_val(&mut self, _storage: &Self::Storage, _val: &()) { }
     fn rewind_keys(&mut self, storage: &Self::Storage) { self.cursor.rewind(&storage.layer); self.valid = true; }
@@ -670,7 +754,7 @@ where
 
 
 /// A builder for creating layers from unsorted update tuples.
-pub struct OrdKeyBuilder<K, T, R, O=usize>
+pub struct OrdKeyBuilder<K, T, R, O=usize, F=()>
 where
     K: Ord,
     T: Ord+Lattice,
@@ -678,25 +762,29 @@ where
     O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug
 {
     builder: OrderedBuilder<K, OrderedLeafBuilder<T, R>, O>,
+    phantom: PhantomData<F>,
 }
 
-impl<K, T, R, O> Builder<K, (), T, R, OrdKeyBatch<K, T, R, O>> for OrdKeyBuilder<K, T, R, O>
+impl<K, T, R, O, F> Builder<K, (), T, R, OrdKeyBatch<K, T, R, O, F>> for OrdKeyBuilder<K, T, R, O, F>
 where
     K: Ord+Clone+'static,
     T: Lattice+timely::progress::Timestamp+Ord+Clone+'static,
     R: Semigroup,
-    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug
+    O: OrdOffset, <O as TryFrom<usize>>::Error: Debug, <O as TryInto<usize>>::Error: Debug,
+    F: KeyFilter<K>,
 {
 
     fn new() -> Self {
         OrdKeyBuilder {
-            builder: OrderedBuilder::<K, OrderedLeafBuilder<T, R>, O>::new()
+            builder: OrderedBuilder::<K, OrderedLeafBuilder<T, R>, O>::new(),
+            phantom: PhantomData,
         }
     }
 
     fn with_capacity(cap: usize) -> Self {
         OrdKeyBuilder {
-            builder: <OrderedBuilder<K, OrderedLeafBuilder<T, R>, O> as TupleBuilder>::with_capacity(cap)
+            builder: <OrderedBuilder<K, OrderedLeafBuilder<T, R>, O> as TupleBuilder>::with_capacity(cap),
+            phantom: PhantomData,
         }
     }
 
@@ -706,10 +794,7 @@ where
     }
 
     #[inline(never)]
-    fn done(self, lower: Antichain<T>, upper: Antichain<T>, since: Antichain<T>) -> OrdKeyBatch<K, T, R, O> {
-        OrdKeyBatch {
-            layer: self.builder.done(),
-            desc: Description::new(lower, upper, since)
-        }
+    fn done(self, lower: Antichain<T>, upper: Antichain<T>, since: Antichain<T>) -> OrdKeyBatch<K, T, R, O, F> {
+        OrdKeyBatch::from_layer(self.builder.done(), Description::new(lower, upper, since))
     }
 }
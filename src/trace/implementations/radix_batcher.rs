@@ -0,0 +1,239 @@
+//! A `Batcher` implementation based on radix sort, for keys with integer projections.
+//!
+//! The `MergeBatcher` relies on comparison sorting, which is general but can dominate the cost of
+//! arranging data whose keys are small integers (e.g. graph node identifiers). The `RadixBatcher`
+//! instead sorts updates by an unsigned integer projection of their keys, using a least-significant
+//! digit radix sort, and only compares values and times within runs of equal keys, which it also
+//! consolidates as it goes.
+//!
+//! The batcher is selected per arrangement, using `Collection::arrange_core_with_batcher` or
+//! `Collection::arrange_with_batcher_named`, and works with any batch type.
+
+use timely::progress::frontier::Antichain;
+
+use ::difference::Semigroup;
+
+use lattice::Lattice;
+use trace::{Batch, Batcher, Builder};
+
+/// An order-preserving and injective projection of a key to an unsigned integer.
+///
+/// For any two keys `x` and `y`, `x.cmp(&y)` must equal `x.radix_projection().cmp(&y.radix_projection())`.
+pub trait RadixProjection {
+    /// The number of low-order bytes of the projection that may be non-zero.
+    const BYTES: usize;
+    /// The projection of the key to an unsigned integer.
+    fn radix_projection(&self) -> u64;
+}
+
+macro_rules! unsigned_projection {
+    ($($index_type:ty),*) => (
+        $(
+            impl RadixProjection for $index_type {
+                const BYTES: usize = ::std::mem::size_of::<$index_type>();
+                #[inline(always)] fn radix_projection(&self) -> u64 { *self as u64 }
+            }
+        )*
+    )
+}
+
+unsigned_projection!(u8, u16, u32, u64, usize);
+
+macro_rules! signed_projection {
+    ($($index_type:ty),*) => (
+        $(
+            impl RadixProjection for $index_type {
+                const BYTES: usize = ::std::mem::size_of::<$index_type>();
+                // Flipping the sign bit maps the signed order onto the unsigned order.
+                #[inline(always)] fn radix_projection(&self) -> u64 {
+                    let bits = 8 * Self::BYTES;
+                    ((*self as i64 as u64) ^ (1 << (bits - 1))) & (!0u64 >> (64 - bits))
+                }
+            }
+        )*
+    )
+}
+
+signed_projection!(i8, i16, i32, i64, isize);
+
+/// Creates batches from unordered tuples, by radix sorting on their keys.
+pub struct RadixBatcher<K: Ord+RadixProjection, V: Ord, T: Ord, R: Semigroup, B: Batch<K, V, T, R>> {
+    // Received updates that have not yet been sorted.
+    pending: Vec<((K, V), T, R)>,
+    // Sorted and consolidated updates, not in advance of the most recent `seal` upper bound.
+    sorted: Vec<((K, V), T, R)>,
+    // Scratch space for sorting key projections.
+    projections: Vec<(u64, usize)>,
+    scratch: Vec<(u64, usize)>,
+    // For each position, the original index of its update, and for each original index, its position.
+    locations: Vec<(usize, usize)>,
+    lower: Antichain<T>,
+    frontier: Antichain<T>,
+    phantom: ::std::marker::PhantomData<B>,
+}
+
+impl<K, V, T, R, B> Batcher<K, V, T, R, B> for RadixBatcher<K, V, T, R, B>
+where
+    K: Ord+Clone+RadixProjection,
+    V: Ord+Clone,
+    T: Lattice+timely::progress::Timestamp+Ord+Clone,
+    R: Semigroup,
+    B: Batch<K, V, T, R>,
+{
+    fn new() -> Self {
+        RadixBatcher {
+            pending: Vec::new(),
+            sorted: Vec::new(),
+            projections: Vec::new(),
+            scratch: Vec::new(),
+            locations: Vec::new(),
+            frontier: Antichain::new(),
+            lower: Antichain::from_elem(T::minimum()),
+            phantom: ::std::marker::PhantomData,
+        }
+    }
+
+    #[inline(never)]
+    fn push_batch(&mut self, batch: &mut Vec<((K,V),T,R)>) {
+        if self.pending.is_empty() {
+            ::std::mem::swap(&mut self.pending, batch);
+        }
+        else {
+            self.pending.append(batch);
+        }
+
+        // Consolidate once pending updates outweigh sorted updates, to bound the footprint.
+        if self.pending.len() > ::std::cmp::max(self.sorted.len(), 1 << 16) {
+            self.sort_pending();
+        }
+    }
+
+    // Sealing a batch means finding those updates with times not greater or equal to any time
+    // in `upper`. All updates must have time greater or equal to the previously used `upper`,
+    // which we call `lower`, by assumption that after sealing a batcher we receive no more
+    // updates with times not greater or equal to `upper`.
+    #[inline(never)]
+    fn seal(&mut self, upper: Antichain<T>) -> B {
+
+        self.sort_pending();
+
+        let mut builder = B::Builder::new();
+        let mut kept = Vec::new();
+
+        self.frontier.clear();
+
+        for ((key, val), time, diff) in self.sorted.drain(..) {
+            if upper.less_equal(&time) {
+                self.frontier.insert(time.clone());
+                kept.push(((key, val), time, diff));
+            }
+            else {
+                builder.push((key, val, time, diff));
+            }
+        }
+
+        // Retained updates remain sorted and consolidated.
+        self.sorted = kept;
+
+        let seal = builder.done(self.lower.clone(), upper.clone(), Antichain::from_elem(T::minimum()));
+        self.lower = upper;
+        seal
+    }
+
+    // the frontier of elements remaining after the most recent call to `self.seal`.
+    fn frontier(&mut self) -> timely::progress::frontier::AntichainRef<T> {
+        self.frontier.borrow()
+    }
+}
+
+impl<K, V, T, R, B> RadixBatcher<K, V, T, R, B>
+where
+    K: Ord+Clone+RadixProjection,
+    V: Ord+Clone,
+    T: Ord+Clone,
+    R: Semigroup,
+    B: Batch<K, V, T, R>,
+{
+    /// Sorts and consolidates all pending and sorted updates into `self.sorted`.
+    #[inline(never)]
+    fn sort_pending(&mut self) {
+
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let mut updates = ::std::mem::replace(&mut self.pending, Vec::new());
+        updates.append(&mut self.sorted);
+
+        // 1. Radix sort `(projection, index)` pairs by projection; the sort is stable.
+        self.projections.clear();
+        self.projections.extend(updates.iter().enumerate().map(|(index, update)| ((update.0).0.radix_projection(), index)));
+        radix_sort(&mut self.projections, &mut self.scratch, K::BYTES);
+
+        // 2. Move updates into the sorted order in place, consolidating each run of equal keys
+        //    by value and time once its last update is in position.
+        self.locations.clear();
+        self.locations.extend((0 .. updates.len()).map(|index| (index, index)));
+        let mut write_position = 0;
+        let mut lower = 0;
+        for position in 0 .. updates.len() {
+            // Swap the update for `position` into place, and record where the displaced update went.
+            let location = self.locations[self.projections[position].1].1;
+            updates.swap(position, location);
+            let displaced = self.locations[position].0;
+            self.locations[location].0 = displaced;
+            self.locations[displaced].1 = location;
+
+            let upper = position + 1;
+            if upper == updates.len() || self.projections[upper].0 != self.projections[lower].0 {
+                let count = crate::consolidation::consolidate_updates_slice(&mut updates[lower .. upper]);
+                for index in lower .. (lower + count) {
+                    updates.swap(write_position, index);
+                    write_position += 1;
+                }
+                lower = upper;
+            }
+        }
+        updates.truncate(write_position);
+
+        self.sorted = updates;
+    }
+}
+
+/// Stably sorts `(projection, index)` pairs by their projections, examining the low `bytes` bytes.
+///
+/// Each byte is a pass of a counting sort, and passes in which all projections share a digit are skipped.
+fn radix_sort(data: &mut Vec<(u64, usize)>, scratch: &mut Vec<(u64, usize)>, bytes: usize) {
+
+    // Histograms for each byte, determined in one pass over the data.
+    let mut counts = vec![[0usize; 256]; bytes];
+    for &(projection, _) in data.iter() {
+        for (byte, count) in counts.iter_mut().enumerate() {
+            count[((projection >> (8 * byte)) & 0xFF) as usize] += 1;
+        }
+    }
+
+    for (byte, count) in counts.iter().enumerate() {
+        // Skip passes that would not re-order anything.
+        if count.iter().any(|&c| c == data.len()) {
+            continue;
+        }
+
+        // Convert counts to starting offsets.
+        let mut offsets = [0usize; 256];
+        let mut total = 0;
+        for digit in 0 .. 256 {
+            offsets[digit] = total;
+            total += count[digit];
+        }
+
+        scratch.clear();
+        scratch.resize(data.len(), (0, 0));
+        for &(projection, index) in data.iter() {
+            let digit = ((projection >> (8 * byte)) & 0xFF) as usize;
+            scratch[offsets[digit]] = (projection, index);
+            offsets[digit] += 1;
+        }
+        ::std::mem::swap(data, scratch);
+    }
+}
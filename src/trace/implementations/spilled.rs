@@ -0,0 +1,286 @@
+//! Batches that move to local files once they become large.
+//!
+//! A `SpilledBatch<B, P>` wraps a batch of type `B`, and is either held in memory or written out
+//! to a file using the abomonation encoding and memory mapped back in. Batches produced by batchers
+//! and builders are always held in memory; only the results of merges are considered for spilling,
+//! and only if they contain more updates than the policy `P` indicates. As the largest batches in a
+//! spine are the result of merges and are rarely re-merged, this moves the bulk of an arrangement
+//! to disk while the small and frequently merged batches remain in memory.
+//!
+//! Spilled batches are mapped copy-on-write, so that pages are read from the file on demand and
+//! may be evicted by the operating system, except for those pages the decoding step must rewrite
+//! (the headers of owned allocations, like `Vec` and `String`). The file is removed when the batch
+//! is dropped.
+
+use std::fs::OpenOptions;
+use std::io::{BufWriter, Write};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use memmap::{MmapMut, MmapOptions};
+
+use timely::progress::{Antichain, frontier::AntichainRef};
+
+use abomonation::Abomonation;
+use abomonation::abomonated::Abomonated;
+
+use trace::{Batch, BatchReader, Batcher, Builder, Merger, Cursor, Description};
+
+use super::spine_fueled::Spine;
+use super::ord::{OrdValBatch, OrdKeyBatch};
+
+/// A trace implementation using a spine of ordered lists, where large batches are spilled to disk.
+pub type OrdValSpineSpill<K, V, T, R, P=DefaultSpillPolicy> = Spine<K, V, T, R, Rc<SpilledBatch<OrdValBatch<K, V, T, R>, P>>>;
+
+/// A trace implementation for empty values using a spine of ordered lists, where large batches are spilled to disk.
+pub type OrdKeySpineSpill<K, T, R, P=DefaultSpillPolicy> = Spine<K, (), T, R, Rc<SpilledBatch<OrdKeyBatch<K, T, R>, P>>>;
+
+/// Determines which batches are spilled, and where to.
+pub trait SpillPolicy: 'static {
+    /// Merged batches with more than this many updates are written to disk.
+    fn threshold() -> usize;
+    /// The directory in which to create spill files.
+    fn directory() -> PathBuf;
+}
+
+/// A spill policy configured by environment variables.
+///
+/// The threshold is read from `DIFFERENTIAL_SPILL_THRESHOLD`, defaulting to `1 << 24` updates, and
+/// the directory from `DIFFERENTIAL_SPILL_DIRECTORY`, defaulting to the system temporary directory.
+pub struct DefaultSpillPolicy;
+
+impl SpillPolicy for DefaultSpillPolicy {
+    fn threshold() -> usize {
+        ::std::env::var("DIFFERENTIAL_SPILL_THRESHOLD")
+            .ok()
+            .and_then(|threshold| threshold.parse().ok())
+            .unwrap_or(1 << 24)
+    }
+    fn directory() -> PathBuf {
+        ::std::env::var_os("DIFFERENTIAL_SPILL_DIRECTORY")
+            .map(PathBuf::from)
+            .unwrap_or_else(::std::env::temp_dir)
+    }
+}
+
+/// Distinguishes spill files created by this process.
+static SPILL_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// A batch decoded from a memory mapped file, which is removed when the batch is dropped.
+struct SpillFile<B> {
+    // Always `Some` until dropped; the mapping must be released before the file is removed.
+    batch: Option<Abomonated<B, MmapMut>>,
+    path: PathBuf,
+}
+
+impl<B: Abomonation> SpillFile<B> {
+    /// Writes `batch` to a new file in `directory` and maps it back in.
+    fn write(batch: &B, directory: PathBuf) -> ::std::io::Result<Self> {
+        let count = SPILL_FILES.fetch_add(1, Ordering::SeqCst);
+        let path = directory.join(format!("differential-spill-{}-{}.abom", ::std::process::id(), count));
+
+        let result = Self::write_to(batch, &path);
+        if result.is_err() {
+            let _ = ::std::fs::remove_file(&path);
+        }
+        result.map(|batch| SpillFile { batch: Some(batch), path })
+    }
+
+    fn write_to(batch: &B, path: &PathBuf) -> ::std::io::Result<Abomonated<B, MmapMut>> {
+        let file = OpenOptions::new().read(true).write(true).create_new(true).open(path)?;
+        {
+            let mut writer = BufWriter::new(&file);
+            unsafe { abomonation::encode(batch, &mut writer)?; }
+            writer.flush()?;
+        }
+        let map = unsafe { MmapOptions::new().map_copy(&file)? };
+        unsafe { Abomonated::<B, _>::new(map) }
+            .ok_or_else(|| ::std::io::Error::new(::std::io::ErrorKind::InvalidData, "failed to decode spilled batch"))
+    }
+}
+
+impl<B> Drop for SpillFile<B> {
+    fn drop(&mut self) {
+        self.batch = None;
+        let _ = ::std::fs::remove_file(&self.path);
+    }
+}
+
+enum Contents<B> {
+    Memory(B),
+    Disk(SpillFile<B>),
+}
+
+/// A batch that is either held in memory or spilled to a memory mapped file.
+pub struct SpilledBatch<B, P> {
+    contents: Contents<B>,
+    phantom: PhantomData<P>,
+}
+
+impl<B, P> SpilledBatch<B, P> {
+    /// Wraps an in-memory batch.
+    pub fn in_memory(batch: B) -> Self {
+        SpilledBatch {
+            contents: Contents::Memory(batch),
+            phantom: PhantomData,
+        }
+    }
+    /// True iff the batch is backed by a file.
+    pub fn is_spilled(&self) -> bool {
+        match self.contents {
+            Contents::Memory(_) => false,
+            Contents::Disk(_) => true,
+        }
+    }
+}
+
+impl<B: Abomonation, P: SpillPolicy> SpilledBatch<B, P> {
+    /// Writes `batch` to a file in `P::directory()`.
+    ///
+    /// The caller retains `batch`, and may fall back to `in_memory` if the batch cannot be written.
+    pub fn spill(batch: &B) -> ::std::io::Result<Self> {
+        SpillFile::write(batch, P::directory())
+            .map(|file| SpilledBatch {
+                contents: Contents::Disk(file),
+                phantom: PhantomData,
+            })
+    }
+}
+
+impl<B: Abomonation, P> Deref for SpilledBatch<B, P> {
+    type Target = B;
+    fn deref(&self) -> &B {
+        match &self.contents {
+            Contents::Memory(batch) => batch,
+            Contents::Disk(file) => &**file.batch.as_ref().expect("spill file already released"),
+        }
+    }
+}
+
+impl<K, V, T, R, B: BatchReader<K,V,T,R>+Abomonation, P> BatchReader<K,V,T,R> for SpilledBatch<B, P> {
+
+    /// The type used to enumerate the batch's contents.
+    type Cursor = SpilledBatchCursor<K, V, T, R, B, P>;
+    /// Acquires a cursor to the batch's contents.
+    fn cursor(&self) -> Self::Cursor {
+        SpilledBatchCursor::new((&**self).cursor())
+    }
+
+    /// The number of updates in the batch.
+    fn len(&self) -> usize { (&**self).len() }
+    /// Describes the times of the updates in the batch.
+    fn description(&self) -> &Description<T> { (&**self).description() }
+    /// Reports the heap allocations of the batch, which are none once it is spilled.
+    fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) {
+        if !self.is_spilled() {
+            (&**self).heap_size(callback)
+        }
+    }
+}
+
+/// Wrapper to provide cursor to nested scope.
+pub struct SpilledBatchCursor<K, V, T, R, B: BatchReader<K, V, T, R>, P> {
+    phantom: PhantomData<(K, V, T, R, P)>,
+    cursor: B::Cursor,
+}
+
+impl<K, V, T, R, B: BatchReader<K, V, T, R>, P> SpilledBatchCursor<K, V, T, R, B, P> {
+    fn new(cursor: B::Cursor) -> Self {
+        SpilledBatchCursor {
+            cursor,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<K, V, T, R, B: BatchReader<K, V, T, R>+Abomonation, P> Cursor<K, V, T, R> for SpilledBatchCursor<K, V, T, R, B, P> {
+
+    type Storage = SpilledBatch<B, P>;
+
+    #[inline] fn key_valid(&self, storage: &Self::Storage) -> bool { self.cursor.key_valid(storage) }
+    #[inline] fn val_valid(&self, storage: &Self::Storage) -> bool { self.cursor.val_valid(storage) }
+
+    #[inline] fn key<'a>(&self, storage: &'a Self::Storage) -> &'a K { self.cursor.key(storage) }
+    #[inline] fn val<'a>(&self, storage: &'a Self::Storage) -> &'a V { self.cursor.val(storage) }
+
+    #[inline]
+    fn map_times<L: FnMut(&T, &R)>(&mut self, storage: &Self::Storage, logic: L) {
+        self.cursor.map_times(storage, logic)
+    }
+
+    #[inline] fn step_key(&mut self, storage: &Self::Storage) { self.cursor.step_key(storage) }
+    #[inline] fn seek_key(&mut self, storage: &Self::Storage, key: &K) { self.cursor.seek_key(storage, key) }
+    #[inline] fn may_contain(&self, storage: &Self::Storage, key: &K) -> bool { self.cursor.may_contain(storage, key) }
+
+    #[inline] fn step_val(&mut self, storage: &Self::Storage) { self.cursor.step_val(storage) }
+    #[inline] fn seek_val(&mut self, storage: &Self::Storage, val: &V) { self.cursor.seek_val(storage, val) }
+
+    #[inline] fn rewind_keys(&mut self, storage: &Self::Storage) { self.cursor.rewind_keys(storage) }
+    #[inline] fn rewind_vals(&mut self, storage: &Self::Storage) { self.cursor.rewind_vals(storage) }
+}
+
+/// An immutable collection of updates.
+impl<K, V, T, R, B: Batch<K,V,T,R>+Abomonation, P: SpillPolicy> Batch<K, V, T, R> for SpilledBatch<B, P> {
+    type Batcher = SpilledBatcher<K, V, T, R, B, P>;
+    type Builder = SpilledBuilder<K, V, T, R, B, P>;
+    type Merger = SpilledMerger<K, V, T, R, B, P>;
+}
+
+/// Wrapper type for batching spillable batches.
+pub struct SpilledBatcher<K, V, T, R, B: Batch<K,V,T,R>, P> {
+    batcher: B::Batcher,
+    phantom: PhantomData<P>,
+}
+
+/// Functionality for collecting and batching updates.
+impl<K, V, T, R, B: Batch<K,V,T,R>+Abomonation, P: SpillPolicy> Batcher<K, V, T, R, SpilledBatch<B, P>> for SpilledBatcher<K, V, T, R, B, P> {
+    fn new() -> Self { SpilledBatcher { batcher: <B::Batcher as Batcher<K,V,T,R,B>>::new(), phantom: PhantomData } }
+    fn push_batch(&mut self, batch: &mut Vec<((K, V), T, R)>) { self.batcher.push_batch(batch) }
+    fn seal(&mut self, upper: Antichain<T>) -> SpilledBatch<B, P> { SpilledBatch::in_memory(self.batcher.seal(upper)) }
+    fn frontier(&mut self) -> AntichainRef<T> { self.batcher.frontier() }
+}
+
+/// Wrapper type for building spillable batches.
+pub struct SpilledBuilder<K, V, T, R, B: Batch<K,V,T,R>, P> {
+    builder: B::Builder,
+    phantom: PhantomData<P>,
+}
+
+/// Functionality for building batches from ordered update sequences.
+impl<K, V, T, R, B: Batch<K,V,T,R>+Abomonation, P: SpillPolicy> Builder<K, V, T, R, SpilledBatch<B, P>> for SpilledBuilder<K, V, T, R, B, P> {
+    fn new() -> Self { SpilledBuilder { builder: <B::Builder as Builder<K,V,T,R,B>>::new(), phantom: PhantomData } }
+    fn with_capacity(cap: usize) -> Self { SpilledBuilder { builder: <B::Builder as Builder<K,V,T,R,B>>::with_capacity(cap), phantom: PhantomData } }
+    fn push(&mut self, element: (K, V, T, R)) { self.builder.push(element) }
+    fn done(self, lower: Antichain<T>, upper: Antichain<T>, since: Antichain<T>) -> SpilledBatch<B, P> {
+        SpilledBatch::in_memory(self.builder.done(lower, upper, since))
+    }
+}
+
+/// Wrapper type for merging spillable batches.
+pub struct SpilledMerger<K, V, T, R, B: Batch<K,V,T,R>, P> {
+    merger: B::Merger,
+    phantom: PhantomData<P>,
+}
+
+/// Represents a merge in progress.
+impl<K, V, T, R, B: Batch<K,V,T,R>+Abomonation, P: SpillPolicy> Merger<K, V, T, R, SpilledBatch<B, P>> for SpilledMerger<K, V, T, R, B, P> {
+    fn new(source1: &SpilledBatch<B, P>, source2: &SpilledBatch<B, P>, compaction_frontier: Option<AntichainRef<T>>) -> Self {
+        SpilledMerger { merger: B::begin_merge(source1, source2, compaction_frontier), phantom: PhantomData }
+    }
+    fn work(&mut self, source1: &SpilledBatch<B, P>, source2: &SpilledBatch<B, P>, fuel: &mut isize) {
+        self.merger.work(source1, source2, fuel)
+    }
+    fn done(self) -> SpilledBatch<B, P> {
+        let batch = self.merger.done();
+        if batch.len() > P::threshold() {
+            // Should the batch fail to spill, we retain it in memory rather than lose it.
+            if let Ok(spilled) = SpilledBatch::spill(&batch) {
+                return spilled;
+            }
+        }
+        SpilledBatch::in_memory(batch)
+    }
+}
@@ -78,6 +78,8 @@ use trace::{Batch, BatchReader, Trace, TraceReader};
 use trace::cursor::{Cursor, CursorList};
 use trace::Merger;
 
+use super::effort::{MergeEffort, MergeProgress, FixedEffort};
+
 use ::timely::dataflow::operators::generic::OperatorInfo;
 use ::timely::progress::{Antichain, frontier::AntichainRef};
 use ::timely::order::PartialOrder;
@@ -96,7 +98,7 @@ pub struct Spine<K, V, T: Lattice+Ord, R: Semigroup, B: Batch<K, V, T, R>> {
     merging: Vec<MergeState<K,V,T,R,B>>,// Several possibly shared collections of updates.
     pending: Vec<B>,                       // Batches at times in advance of `frontier`.
     upper: Antichain<T>,
-    effort: usize,
+    effort: Box<dyn MergeEffort>,
     activator: Option<timely::scheduling::activate::Activator>,
 }
 
@@ -298,6 +300,8 @@ where
         assert!(batch.lower() != batch.upper());
         assert_eq!(batch.lower(), &self.upper);
 
+        ::trace::validate::debug_validate_batch::<K, V, T, R, _>(&batch);
+
         self.upper.clone_from(batch.upper());
 
         // TODO: Consolidate or discard empty batches.
@@ -419,15 +423,21 @@ where
     /// of the batch's length in effort to each merge. The `effort` parameter is that multiplier.
     /// This value should be at least one for the merging to happen; a value of zero is not helpful.
     pub fn with_effort(
-        mut effort: usize,
+        effort: usize,
         operator: OperatorInfo,
         logger: Option<::logging::Logger>,
         activator: Option<timely::scheduling::activate::Activator>,
     ) -> Self {
+        Self::with_effort_policy(Box::new(FixedEffort::new(effort)), operator, logger, activator)
+    }
 
-        // Zero effort is .. not smart.
-        if effort == 0 { effort = 1; }
-
+    /// Allocates a fueled `Spine` whose merge effort is determined by `effort`.
+    pub fn with_effort_policy(
+        effort: Box<dyn MergeEffort>,
+        operator: OperatorInfo,
+        logger: Option<::logging::Logger>,
+        activator: Option<timely::scheduling::activate::Activator>,
+    ) -> Self {
         Spine {
             operator,
             logger,
@@ -442,6 +452,11 @@ where
         }
     }
 
+    /// Replaces the policy determining the spine's merge effort.
+    pub fn set_effort_policy(&mut self, effort: Box<dyn MergeEffort>) {
+        self.effort = effort;
+    }
+
     /// Migrate data from `self.pending` into `self.merging`.
     ///
     /// This method reflects on the bookmarks held by others that may prevent merging, and in the
@@ -509,24 +524,18 @@ where
         //          should be a configuration knob controlling this.
 
         // The amount of fuel to use is proportional to 2^batch_index, scaled
-        // by a factor of self.effort which determines how eager we are in
-        // performing maintenance work. We need to ensure that each merge in
-        // progress receives fuel for each introduced batch, and so multiply
-        // by that as well.
+        // by the effort policy which determines how eager we are in performing
+        // maintenance work. We need to ensure that each merge in progress
+        // receives fuel for each introduced batch, and so multiply by that as
+        // well.
         if batch_index > 32 { println!("Large batch index: {}", batch_index); }
 
         // We believe that eight units of fuel is sufficient for each introduced
         // record, accounted as four for each record, and a potential four more
         // for each virtual record associated with promoting existing smaller
-        // batches. We could try and make this be less, or be scaled to merges
-        // based on their deficit at time of instantiation. For now, we remain
-        // conservative.
-        let mut fuel = 8 << batch_index;
-        // Scale up by the effort parameter, which is calibrated to one as the
-        // minimum amount of effort.
-        fuel *= self.effort;
-        // Convert to an `isize` so we can observe any fuel shortfall.
-        let mut fuel = fuel as isize;
+        // batches. The effort policy scales this up, and is calibrated so that
+        // `8 << batch_index` is the minimum amount of effort.
+        let mut fuel = self.effort.fuel(batch_index);
 
         // Step 1.  Apply fuel to each in-progress merge.
         //
@@ -616,11 +625,19 @@ where
         // great idea, but we need better accounting in place to ensure that merges
         // that borrow against later layers but then complete still "acquire" fuel
         // to pay back their debts.
+        let start = ::std::time::Instant::now();
+        let given = *fuel;
+        let mut spent = 0;
         for index in 0 .. self.merging.len() {
             // Give each level independent fuel, for now.
             let mut fuel = *fuel;
+            // Record the fuel spent, for the effort policy.
+            let merging = self.merging[index].is_double();
             // Pass along various logging stuffs, in case we need to report success.
             self.merging[index].work(&mut fuel);
+            if merging {
+                spent += (given - ::std::cmp::max(fuel, 0)) as usize;
+            }
             // `fuel` could have a deficit at this point, meaning we over-spent when
             // we took a merge step. We could ignore this, or maintain the deficit
             // and account future fuel against it before spending again. It isn't
@@ -636,6 +653,23 @@ where
                 self.insert_at(complete, index+1);
             }
         }
+
+        // Report the outcome to the effort policy, and any shortfall it reports to the logger.
+        let progress = MergeProgress {
+            spent,
+            elapsed: start.elapsed(),
+            backlog: self.merging.iter().filter(|m| m.is_double()).map(|m| m.len()).sum(),
+        };
+        if let Some(shortfall) = self.effort.observe(&progress) {
+            let scale = self.merging.iter().rposition(|m| m.is_double()).unwrap_or(0);
+            self.logger.as_ref().map(|l| l.log(
+                ::logging::MergeShortfall {
+                    operator: self.operator.global_id,
+                    scale,
+                    shortfall,
+                }
+            ));
+        }
     }
 
     /// Inserts a batch at a specific location.
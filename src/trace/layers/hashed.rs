@@ -0,0 +1,321 @@
+//! Implementation using hash-ordered keys and Robin Hood hashing.
+//!
+//! Keys are placed into slots of a table according to their hash values, where each key is
+//! placed at its desired slot or, if that slot is occupied, at the next vacant slot. Because
+//! keys are `HashOrdered`, and they are inserted in order, occupied slots are also in key order.
+//! This means the layer can be enumerated like an ordered layer, but a key can be sought by
+//! jumping directly to its desired slot and probing a (typically short) run of slots.
+//!
+//! Building the layer collects keys densely, as `OrderedBuilder` does, and lays them out into
+//! slots only in `done`. As a consequence the layer must be the outermost layer of a trie, as
+//! the builder's `boundary` does not report slot positions.
+
+use ::hashable::{Hashable, HashOrdered};
+
+use super::{Trie, Cursor, Builder, MergeBuilder, TupleBuilder};
+
+/// The number of slots allocated per hundred keys.
+const SLOTS_PER_HUNDRED_KEYS: usize = 125;
+
+/// The slot at which a key with hash `hash` would prefer to be placed, in a table of `slots` slots.
+#[inline(always)]
+fn desired_slot(hash: u64, slots: usize) -> usize {
+    ((hash as u128 * slots as u128) >> 64) as usize
+}
+
+/// A level of the trie, with keys in hashed slots and offsets into a lower layer.
+///
+/// In this representation, the values for an occupied slot `keys[i]` are found at
+/// `vals[offs[i] .. offs[i+1]]`. Vacant slots have empty ranges.
+#[derive(Debug, Eq, PartialEq, Clone, Abomonation)]
+pub struct HashedLayer<K, L> {
+    /// The slots of the layer, where occupied slots are in key order.
+    pub keys: Vec<Option<K>>,
+    /// The offsets associated with each slot.
+    ///
+    /// The bounds for `keys[i]` are `(offs[i], offs[i+1]`). The offset array is guaranteed to be one
+    /// element longer than the keys array, ensuring that these accesses do not panic.
+    pub offs: Vec<usize>,
+    /// The number of slots into which keys hash. There may be more slots, to accommodate displaced keys.
+    pub slots: usize,
+    /// The ranges of values associated with the keys.
+    pub vals: L,
+}
+
+impl<K, L> HashedLayer<K, L>
+where
+    K: HashOrdered+Clone,
+{
+    /// The number of occupied slots.
+    pub fn occupied(&self) -> usize {
+        self.keys.iter().filter(|key| key.is_some()).count()
+    }
+}
+
+impl<K, L> Trie for HashedLayer<K, L>
+where
+    K: HashOrdered+Clone,
+    L: Trie,
+{
+    type Item = (K, L::Item);
+    type Cursor = HashedCursor<L>;
+    type MergeBuilder = HashedBuilder<K, L::MergeBuilder>;
+    type TupleBuilder = HashedBuilder<K, L::TupleBuilder>;
+
+    /// The number of slots, which bounds the number of distinct keys.
+    ///
+    /// Positions in a hashed layer are slots, some of which may be vacant.
+    fn keys(&self) -> usize { self.keys.len() }
+    fn tuples(&self) -> usize { self.vals.tuples() }
+    fn cursor_from(&self, lower: usize, upper: usize) -> Self::Cursor {
+        let mut cursor = HashedCursor {
+            bounds: (lower, upper),
+            child: self.vals.cursor_from(0, 0),
+            pos: lower,
+        };
+        cursor.rewind(self);
+        cursor
+    }
+}
+
+/// Assembles a hashed layer, from keys presented in order.
+pub struct HashedBuilder<K, L> {
+    /// Keys, in order.
+    pub keys: Vec<K>,
+    /// Offsets, one more than the number of keys.
+    pub offs: Vec<usize>,
+    /// The next layer down
+    pub vals: L,
+}
+
+impl<K, L> Builder for HashedBuilder<K, L>
+where
+    K: HashOrdered+Clone,
+    L: Builder,
+{
+    type Trie = HashedLayer<K, L::Trie>;
+    fn boundary(&mut self) -> usize {
+        self.offs[self.keys.len()] = self.vals.boundary();
+        self.keys.len()
+    }
+    fn done(mut self) -> Self::Trie {
+        if self.keys.len() > 0 && self.offs[self.keys.len()] == 0 {
+            self.offs[self.keys.len()] = self.vals.boundary();
+        }
+
+        // Lay out keys in slots, each at the later of its desired slot and the slot after its predecessor.
+        let slots = (self.keys.len() * SLOTS_PER_HUNDRED_KEYS) / 100;
+        let mut keys = Vec::with_capacity(slots);
+        let mut offs = Vec::with_capacity(slots + 1);
+        offs.push(0);
+        for (index, key) in self.keys.into_iter().enumerate() {
+            let desired = desired_slot(key.hashed().into(), slots);
+            while keys.len() < desired {
+                keys.push(None);
+                offs.push(self.offs[index]);
+            }
+            keys.push(Some(key));
+            offs.push(self.offs[index + 1]);
+        }
+
+        HashedLayer {
+            keys,
+            offs,
+            slots,
+            vals: self.vals.done(),
+        }
+    }
+}
+
+impl<K, L> MergeBuilder for HashedBuilder<K, L>
+where
+    K: HashOrdered+Clone,
+    L: MergeBuilder,
+{
+    fn with_capacity(other1: &Self::Trie, other2: &Self::Trie) -> Self {
+        let mut offs = Vec::with_capacity(other1.keys() + other2.keys() + 1);
+        offs.push(0);
+        HashedBuilder {
+            keys: Vec::with_capacity(other1.keys() + other2.keys()),
+            offs: offs,
+            vals: L::with_capacity(&other1.vals, &other2.vals),
+        }
+    }
+    #[inline]
+    fn copy_range(&mut self, other: &Self::Trie, lower: usize, upper: usize) {
+        debug_assert!(lower < upper);
+        let other_basis = other.offs[lower];
+        let self_basis = self.offs.last().map(|&x| x).unwrap_or(0);
+
+        for index in lower .. upper {
+            if let Some(key) = &other.keys[index] {
+                self.keys.push(key.clone());
+                self.offs.push((other.offs[index + 1] + self_basis) - other_basis);
+            }
+        }
+        // Ranges of vacant slots have no values to copy.
+        if other_basis < other.offs[upper] {
+            self.vals.copy_range(&other.vals, other_basis, other.offs[upper]);
+        }
+    }
+
+    fn push_merge(&mut self, other1: (&Self::Trie, usize, usize), other2: (&Self::Trie, usize, usize)) -> usize {
+        let (trie1, mut lower1, upper1) = other1;
+        let (trie2, mut lower2, upper2) = other2;
+
+        // while both mergees are still active
+        while lower1 < upper1 && lower2 < upper2 {
+            self.merge_step((trie1, &mut lower1, upper1), (trie2, &mut lower2, upper2));
+        }
+
+        if lower1 < upper1 { self.copy_range(trie1, lower1, upper1); }
+        if lower2 < upper2 { self.copy_range(trie2, lower2, upper2); }
+
+        self.keys.len()
+    }
+}
+
+impl<K, L> HashedBuilder<K, L>
+where
+    K: HashOrdered+Clone,
+    L: MergeBuilder,
+{
+    /// Performs one step of merging.
+    #[inline]
+    pub fn merge_step(&mut self, other1: (&<Self as Builder>::Trie, &mut usize, usize), other2: (&<Self as Builder>::Trie, &mut usize, usize)) {
+
+        let (trie1, lower1, _upper1) = other1;
+        let (trie2, lower2, _upper2) = other2;
+
+        match (&trie1.keys[*lower1], &trie2.keys[*lower2]) {
+            // Vacant slots are simply skipped.
+            (None, _) => { *lower1 += 1; },
+            (_, None) => { *lower2 += 1; },
+            (Some(key1), Some(key2)) => {
+                match key1.cmp(key2) {
+                    ::std::cmp::Ordering::Less => {
+                        self.copy_range(trie1, *lower1, *lower1 + 1);
+                        *lower1 += 1;
+                    },
+                    ::std::cmp::Ordering::Equal => {
+                        let lower = self.vals.boundary();
+                        // record vals_length so we can tell if anything was pushed.
+                        let upper = self.vals.push_merge(
+                            (&trie1.vals, trie1.offs[*lower1], trie1.offs[*lower1 + 1]),
+                            (&trie2.vals, trie2.offs[*lower2], trie2.offs[*lower2 + 1])
+                        );
+                        if upper > lower {
+                            self.keys.push(key1.clone());
+                            self.offs.push(upper);
+                        }
+
+                        *lower1 += 1;
+                        *lower2 += 1;
+                    },
+                    ::std::cmp::Ordering::Greater => {
+                        self.copy_range(trie2, *lower2, *lower2 + 1);
+                        *lower2 += 1;
+                    },
+                }
+            },
+        }
+    }
+}
+
+impl<K, L> TupleBuilder for HashedBuilder<K, L>
+where
+    K: HashOrdered+Clone,
+    L: TupleBuilder,
+{
+    type Item = (K, L::Item);
+    fn new() -> Self { HashedBuilder { keys: Vec::new(), offs: vec![0], vals: L::new() } }
+    fn with_capacity(cap: usize) -> Self {
+        let mut offs = Vec::with_capacity(cap + 1);
+        offs.push(0);
+        HashedBuilder {
+            keys: Vec::with_capacity(cap),
+            offs: offs,
+            vals: L::with_capacity(cap),
+        }
+    }
+    #[inline]
+    fn push_tuple(&mut self, (key, val): (K, L::Item)) {
+
+        // if first element, prior element finish, or different element, need to push and maybe punctuate.
+        if self.keys.len() == 0 || self.offs[self.keys.len()] != 0 || self.keys[self.keys.len()-1] != key {
+            if self.keys.len() > 0 && self.offs[self.keys.len()] == 0 {
+                self.offs[self.keys.len()] = self.vals.boundary();
+            }
+            self.keys.push(key);
+            self.offs.push(0);        // <-- indicates "unfinished".
+        }
+        self.vals.push_tuple(val);
+    }
+}
+
+/// A cursor with a child cursor that is updated as we move.
+///
+/// The cursor is always positioned at an occupied slot, or at the upper bound of its range.
+#[derive(Debug)]
+pub struct HashedCursor<L: Trie> {
+    pos: usize,
+    bounds: (usize, usize),
+    /// The cursor for the trie layer below this one.
+    pub child: L::Cursor,
+}
+
+impl<L: Trie> HashedCursor<L> {
+    /// Advances to the first occupied slot at or after the current position, and repositions the child.
+    #[inline]
+    fn settle<K>(&mut self, storage: &HashedLayer<K, L>) {
+        while self.pos < self.bounds.1 && storage.keys[self.pos].is_none() {
+            self.pos += 1;
+        }
+        if self.pos < self.bounds.1 {
+            self.child.reposition(&storage.vals, storage.offs[self.pos], storage.offs[self.pos + 1]);
+        }
+    }
+}
+
+impl<K, L> Cursor<HashedLayer<K, L>> for HashedCursor<L>
+where
+    K: HashOrdered,
+    L: Trie,
+{
+    type Key = K;
+    fn key<'a>(&self, storage: &'a HashedLayer<K, L>) -> &'a Self::Key {
+        storage.keys[self.pos].as_ref().expect("HashedCursor::key called on vacant slot")
+    }
+    fn step(&mut self, storage: &HashedLayer<K, L>) {
+        if self.pos < self.bounds.1 {
+            self.pos += 1;
+        }
+        self.settle(storage);
+    }
+    fn seek(&mut self, storage: &HashedLayer<K, L>, key: &Self::Key) {
+        // Slots before the desired slot contain only keys with lesser hashes, and so lesser keys.
+        let desired = desired_slot(key.hashed().into(), storage.slots);
+        if self.pos < desired {
+            self.pos = ::std::cmp::min(desired, self.bounds.1);
+        }
+        // Probe the run of displaced keys; a vacant slot ends the run, after which all keys are greater.
+        while self.pos < self.bounds.1 && storage.keys[self.pos].as_ref().map(|k| k.lt(key)).unwrap_or(true) {
+            if storage.keys[self.pos].is_none() {
+                self.settle(storage);
+                return;
+            }
+            self.pos += 1;
+        }
+        self.settle(storage);
+    }
+    fn valid(&self, _storage: &HashedLayer<K, L>) -> bool { self.pos < self.bounds.1 }
+    fn rewind(&mut self, storage: &HashedLayer<K, L>) {
+        self.pos = self.bounds.0;
+        self.settle(storage);
+    }
+    fn reposition(&mut self, storage: &HashedLayer<K, L>, lower: usize, upper: usize) {
+        self.pos = lower;
+        self.bounds = (lower, upper);
+        self.settle(storage);
+    }
+}
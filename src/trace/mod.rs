@@ -11,6 +11,7 @@ pub mod cursor;
 pub mod description;
 pub mod implementations;
 pub mod layers;
+pub mod validate;
 pub mod wrappers;
 
 use timely::progress::{Antichain, frontier::AntichainRef};
@@ -251,6 +252,18 @@ where
     fn lower(&self) -> &Antichain<T> { self.description().lower() }
     /// All times in the batch are not greater or equal to any element of `upper`.
     fn upper(&self) -> &Antichain<T> { self.description().upper() }
+
+    /// Reports the heap allocations of the batch, as `(length, capacity)` pairs of byte counts.
+    ///
+    /// The default implementation reports the size of a `(K, V, T, R)` tuple for each update, and
+    /// does not account for allocations owned by the updates themselves. The ordered and hashed
+    /// batches similarly report only the sizes of their keys, values, times, and differences, so
+    /// data like `String` or `Vec<T>` is under-counted by the size of its contents. The columnar
+    /// batches report these allocations, as they hold them in their own regions.
+    fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) {
+        let size = self.len() * ::std::mem::size_of::<(K, V, T, R)>();
+        callback(size, size);
+    }
 }
 
 /// An immutable collection of updates.
@@ -344,6 +357,8 @@ pub mod rc_blanket_impls {
         fn len(&self) -> usize { (&**self).len() }
         /// Describes the times of the updates in the batch.
         fn description(&self) -> &Description<T> { (&**self).description() }
+        /// Reports the heap allocations of the batch.
+        fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) { (&**self).heap_size(callback) }
     }
 
     /// Wrapper to provide cursor to nested scope.
@@ -378,6 +393,7 @@ pub mod rc_blanket_impls {
 
         #[inline] fn step_key(&mut self, storage: &Self::Storage) { self.cursor.step_key(storage) }
         #[inline] fn seek_key(&mut self, storage: &Self::Storage, key: &K) { self.cursor.seek_key(storage, key) }
+        #[inline] fn may_contain(&self, storage: &Self::Storage, key: &K) -> bool { self.cursor.may_contain(storage, key) }
 
         #[inline] fn step_val(&mut self, storage: &Self::Storage) { self.cursor.step_val(storage) }
         #[inline] fn seek_val(&mut self, storage: &Self::Storage, val: &V) { self.cursor.seek_val(storage, val) }
@@ -451,6 +467,8 @@ pub mod abomonated_blanket_impls {
         fn len(&self) -> usize { (&**self).len() }
         /// Describes the times of the updates in the batch.
         fn description(&self) -> &Description<T> { (&**self).description() }
+        /// Reports the heap allocations of the batch.
+        fn heap_size(&self, callback: &mut dyn FnMut(usize, usize)) { (&**self).heap_size(callback) }
     }
 
     /// Wrapper to provide cursor to nested scope.
@@ -485,6 +503,7 @@ pub mod abomonated_blanket_impls {
 
         #[inline] fn step_key(&mut self, storage: &Self::Storage) { self.cursor.step_key(storage) }
         #[inline] fn seek_key(&mut self, storage: &Self::Storage, key: &K) { self.cursor.seek_key(storage, key) }
+        #[inline] fn may_contain(&self, storage: &Self::Storage, key: &K) -> bool { self.cursor.may_contain(storage, key) }
 
         #[inline] fn step_val(&mut self, storage: &Self::Storage) { self.cursor.step_val(storage) }
         #[inline] fn seek_val(&mut self, storage: &Self::Storage, val: &V) { self.cursor.seek_val(storage, val) }
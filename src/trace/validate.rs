@@ -0,0 +1,255 @@
+//! Checks of the structural invariants of traces and batches.
+//!
+//! Operators rely on traces to present their updates in a specific form, and implementations of
+//! `Batch` that violate it produce incorrect results rather than errors. This module walks traces
+//! (through `map_batches`) and batches (through their cursors) and reports each violation of:
+//!
+//! *  batch descriptions that tile, each lower frontier equal to the preceding upper frontier,
+//! *  keys, and values within each key, that strictly increase,
+//! *  updates for each value that are consolidated, with distinct times and non-zero differences,
+//! *  times greater or equal to the batch's lower frontier and unchanged by advancing them by its
+//!    `since` frontier,
+//! *  batch lengths that match the number of updates presented.
+//!
+//! In debug builds, `Spine::insert` validates each inserted batch, including those inserted through
+//! `TraceWriter::insert`, and panics if it finds violations.
+
+use timely::progress::Antichain;
+
+use ::difference::Semigroup;
+use lattice::Lattice;
+use trace::{TraceReader, BatchReader, Cursor};
+
+/// A violation of the invariants of a trace, found in one of its batches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation<T> {
+    /// The index of the batch, in the order presented by `map_batches`.
+    pub batch: usize,
+    /// The violated invariant.
+    pub kind: ViolationKind<T>,
+}
+
+/// The invariants a trace may violate.
+///
+/// Keys and values are identified by their position in the batch (the index of a key among keys,
+/// and the index of a value among the values of its key), as they need not be printable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViolationKind<T> {
+    /// The batch's lower frontier is not the upper frontier of the preceding batch.
+    Gap {
+        /// The upper frontier of the preceding batch.
+        expected: Antichain<T>,
+        /// The lower frontier of the batch.
+        found: Antichain<T>,
+    },
+    /// The batch's reported length differs from the number of updates its cursor presents.
+    Length {
+        /// The length reported by the batch.
+        reported: usize,
+        /// The number of updates presented by the cursor.
+        found: usize,
+    },
+    /// A key is not strictly greater than the preceding key.
+    KeyOrder {
+        /// The position of the key.
+        key: usize,
+    },
+    /// A key has no values.
+    EmptyKey {
+        /// The position of the key.
+        key: usize,
+    },
+    /// A value is not strictly greater than the preceding value of its key.
+    ValOrder {
+        /// The position of the key.
+        key: usize,
+        /// The position of the value.
+        val: usize,
+    },
+    /// A value has no updates.
+    EmptyVal {
+        /// The position of the key.
+        key: usize,
+        /// The position of the value.
+        val: usize,
+    },
+    /// A value has multiple updates at the same time.
+    Unconsolidated {
+        /// The position of the key.
+        key: usize,
+        /// The position of the value.
+        val: usize,
+        /// The repeated time.
+        time: T,
+    },
+    /// An update has a zero difference.
+    ZeroDiff {
+        /// The position of the key.
+        key: usize,
+        /// The position of the value.
+        val: usize,
+        /// The time of the update.
+        time: T,
+    },
+    /// An update's time is not greater or equal to the batch's lower frontier.
+    BeforeLower {
+        /// The position of the key.
+        key: usize,
+        /// The position of the value.
+        val: usize,
+        /// The time of the update.
+        time: T,
+    },
+    /// An update's time changes when advanced by the batch's `since` frontier.
+    BeforeSince {
+        /// The position of the key.
+        key: usize,
+        /// The position of the value.
+        val: usize,
+        /// The time of the update.
+        time: T,
+    },
+}
+
+/// Validates the batches of `trace`, and that their descriptions tile.
+pub fn validate<Tr>(trace: &Tr) -> Vec<Violation<Tr::Time>>
+where
+    Tr: TraceReader,
+    Tr::Key: Ord,
+    Tr::Val: Ord,
+    Tr::Time: Lattice+Ord+Clone,
+    Tr::R: Semigroup,
+{
+    let mut violations = Vec::new();
+    let mut upper: Option<Antichain<Tr::Time>> = None;
+    let mut index = 0;
+    trace.map_batches(|batch| {
+        if let Some(upper) = &upper {
+            if upper != batch.lower() {
+                violations.push(Violation {
+                    batch: index,
+                    kind: ViolationKind::Gap { expected: upper.clone(), found: batch.lower().clone() },
+                });
+            }
+        }
+        validate_batch_into(batch, index, &mut violations);
+        upper = Some(batch.upper().clone());
+        index += 1;
+    });
+    violations
+}
+
+/// Validates a single batch, whose violations are reported with batch index zero.
+pub fn validate_batch<K, V, T, R, B>(batch: &B) -> Vec<Violation<T>>
+where
+    K: Ord,
+    V: Ord,
+    T: Lattice+Ord+Clone,
+    R: Semigroup,
+    B: BatchReader<K, V, T, R>,
+{
+    let mut violations = Vec::new();
+    validate_batch_into(batch, 0, &mut violations);
+    violations
+}
+
+/// Panics if `batch` violates the invariants, in debug builds.
+#[cfg(debug_assertions)]
+pub(crate) fn debug_validate_batch<K, V, T, R, B>(batch: &B)
+where
+    K: Ord,
+    V: Ord,
+    T: Lattice+Ord+Clone+::std::fmt::Debug,
+    R: Semigroup,
+    B: BatchReader<K, V, T, R>,
+{
+    let violations = validate_batch(batch);
+    assert!(violations.is_empty(), "invalid batch inserted into trace: {:?}", violations);
+}
+
+/// Panics if `batch` violates the invariants, in debug builds.
+#[cfg(not(debug_assertions))]
+#[inline(always)]
+pub(crate) fn debug_validate_batch<K, V, T, R, B>(_batch: &B)
+where
+    B: BatchReader<K, V, T, R>,
+{ }
+
+fn validate_batch_into<K, V, T, R, B>(batch: &B, index: usize, violations: &mut Vec<Violation<T>>)
+where
+    K: Ord,
+    V: Ord,
+    T: Lattice+Ord+Clone,
+    R: Semigroup,
+    B: BatchReader<K, V, T, R>,
+{
+    let mut report = |kind| violations.push(Violation { batch: index, kind });
+
+    let description = batch.description();
+    let mut updates = 0;
+    let mut times = Vec::new();
+
+    let mut cursor = batch.cursor();
+    let mut prev_key: Option<&K> = None;
+    let mut key_pos = 0;
+    while let Some(key) = cursor.get_key(batch) {
+        if prev_key.map(|prev| prev >= key).unwrap_or(false) {
+            report(ViolationKind::KeyOrder { key: key_pos });
+        }
+        prev_key = Some(key);
+
+        let mut prev_val: Option<&V> = None;
+        let mut val_pos = 0;
+        while let Some(val) = cursor.get_val(batch) {
+            if prev_val.map(|prev| prev >= val).unwrap_or(false) {
+                report(ViolationKind::ValOrder { key: key_pos, val: val_pos });
+            }
+            prev_val = Some(val);
+
+            let mut zeros = Vec::new();
+            cursor.map_times(batch, |time, diff| {
+                times.push(time.clone());
+                if diff.is_zero() { zeros.push(time.clone()); }
+            });
+            updates += times.len();
+
+            if times.is_empty() {
+                report(ViolationKind::EmptyVal { key: key_pos, val: val_pos });
+            }
+            for time in zeros {
+                report(ViolationKind::ZeroDiff { key: key_pos, val: val_pos, time });
+            }
+            for time in times.iter() {
+                if !description.lower().less_equal(time) {
+                    report(ViolationKind::BeforeLower { key: key_pos, val: val_pos, time: time.clone() });
+                }
+                // Times need not be greater or equal to an element of `since`, for partially ordered times.
+                let mut advanced = time.clone();
+                advanced.advance_by(description.since().borrow());
+                if &advanced != time {
+                    report(ViolationKind::BeforeSince { key: key_pos, val: val_pos, time: time.clone() });
+                }
+            }
+            times.sort();
+            for pair in times.windows(2) {
+                if pair[0] == pair[1] {
+                    report(ViolationKind::Unconsolidated { key: key_pos, val: val_pos, time: pair[0].clone() });
+                }
+            }
+            times.clear();
+
+            cursor.step_val(batch);
+            val_pos += 1;
+        }
+        if val_pos == 0 {
+            report(ViolationKind::EmptyKey { key: key_pos });
+        }
+
+        cursor.step_key(batch);
+        key_pos += 1;
+    }
+
+    if updates != batch.len() {
+        report(ViolationKind::Length { reported: batch.len(), found: updates });
+    }
+}
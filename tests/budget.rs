@@ -0,0 +1,41 @@
+extern crate timely;
+extern crate differential_dataflow;
+
+use timely::dataflow::operators::Probe;
+
+use differential_dataflow::input::InputSession;
+use differential_dataflow::operators::arrange::ArrangeBySelf;
+use differential_dataflow::operators::arrange::budget;
+
+#[test]
+fn test_memory_budget() {
+
+    let mut config = timely::Config::thread();
+    differential_dataflow::configure(&mut config.worker, &differential_dataflow::Config::default().memory_budget(Some(1 << 10)));
+
+    timely::execute(config, |worker| {
+
+        let mut input = InputSession::<usize, u64, isize>::new();
+        let probe = worker.dataflow(|scope| {
+            input.to_collection(scope).arrange_by_self().stream.probe()
+        });
+
+        // Nothing has been arranged, so the worker is within budget.
+        assert!(input.try_flush());
+
+        for value in 0 .. 1000 {
+            input.insert(value);
+        }
+        input.advance_to(1);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+
+        // The arrangement now holds more than the budget, and the session defers its updates.
+        assert!(budget::usage() > 1 << 10);
+        assert!(budget::over_budget());
+        input.insert(1000);
+        input.advance_to(2);
+        assert!(!input.try_flush());
+
+    }).unwrap();
+}
@@ -0,0 +1,73 @@
+extern crate timely;
+extern crate differential_dataflow;
+
+use timely::dataflow::operators::Probe;
+use timely::dataflow::operators::capture::{Capture, Extract};
+
+use differential_dataflow::input::InputSession;
+use differential_dataflow::operators::arrange::ArrangeByKey;
+use differential_dataflow::operators::arrange::checkpoint::{checkpoint, arrange_from_checkpoint};
+use differential_dataflow::trace::implementations::ord::OrdValSpine;
+
+#[test]
+fn test_checkpoint_restore() {
+
+    let directory = std::env::temp_dir().join(format!("differential-checkpoint-{}", std::process::id()));
+
+    // Arrange some updates, and checkpoint the arrangement through time 2.
+    let written = directory.clone();
+    timely::execute_directly(move |worker| {
+
+        let mut input = InputSession::<usize, (u64, u64), isize>::new();
+        let (probe, mut trace) = worker.dataflow(|scope| {
+            let arranged = input.to_collection(scope).arrange_by_key();
+            (arranged.stream.probe(), arranged.trace)
+        });
+
+        for key in 0 .. 10 {
+            input.insert((key, key));
+        }
+        input.advance_to(1);
+        for key in 0 .. 5 {
+            input.remove((key, key));
+            input.insert((key, key + 1));
+        }
+        input.advance_to(2);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+
+        checkpoint(&mut trace, &written).expect("failed to write checkpoint");
+    });
+
+    // Restore the arrangement, and continue with updates at time 2.
+    let captured = timely::execute_directly(move |worker| {
+
+        let mut input = InputSession::<usize, (u64, u64), isize>::new();
+        let (probe, captured) = worker.dataflow(|scope| {
+            let arranged = arrange_from_checkpoint::<_,_,_,_,OrdValSpine<_,_,_,_>,_>(&input.to_collection(scope), &directory, "Restored")
+                .expect("failed to read checkpoint");
+            let collection = arranged.as_collection(|key, val| (*key, *val));
+            (collection.inner.probe(), collection.inner.capture())
+        });
+
+        input.advance_to(2);
+        input.insert((10, 10));
+        input.remove((9, 9));
+        input.advance_to(3);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+
+        std::fs::remove_dir_all(&directory).expect("failed to remove checkpoint");
+        captured
+    });
+
+    let mut results = captured.extract().into_iter().flat_map(|(_, data)| data).map(|(data, _time, diff)| (data, diff)).collect::<Vec<_>>();
+    differential_dataflow::consolidation::consolidate(&mut results);
+
+    let mut expected = Vec::new();
+    for key in 0 .. 5 { expected.push(((key, key + 1), 1)); }
+    for key in 5 .. 9 { expected.push(((key, key), 1)); }
+    expected.push(((10, 10), 1));
+
+    assert_eq!(results, expected);
+}
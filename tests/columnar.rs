@@ -0,0 +1,85 @@
+extern crate timely;
+extern crate differential_dataflow;
+
+use timely::dataflow::operators::generic::OperatorInfo;
+use timely::progress::{Antichain, frontier::AntichainRef};
+
+use differential_dataflow::trace::implementations::columnar::{ColValSpine, ColKeySpine};
+use differential_dataflow::trace::{Trace, TraceReader, Batch, Batcher};
+use differential_dataflow::trace::cursor::CursorDebug;
+
+type StringTrace = ColValSpine<String, Vec<u64>, usize, i64>;
+type KeyTrace = ColKeySpine<String, usize, i64>;
+
+#[test]
+fn test_columnar_val_trace() {
+
+    let op_info = OperatorInfo::new(0, 0, &[]);
+    let mut trace = StringTrace::new(op_info, None, None);
+    {
+        let mut batcher = <<StringTrace as TraceReader>::Batch as Batch<String, Vec<u64>, usize, i64>>::Batcher::new();
+
+        batcher.push_batch(&mut vec![
+            (("a".to_string(), vec![1, 2]), 0, 1),
+            (("b".to_string(), vec![3]), 1, 1),
+            (("b".to_string(), vec![3]), 2, -1),
+            (("b".to_string(), vec![]), 2, 1),
+        ]);
+
+        for upper in 1 .. 4 {
+            trace.insert(batcher.seal(Antichain::from_elem(upper)));
+        }
+    }
+
+    let (mut cursor, storage) = trace.cursor_through(AntichainRef::new(&[2])).unwrap();
+    assert_eq!(cursor.to_vec(&storage), vec![
+        (("a".to_string(), vec![1, 2]), vec![(0, 1)]),
+        (("b".to_string(), vec![3]), vec![(1, 1)]),
+    ]);
+
+    // Compaction to time 2 should cancel the updates for `("b", [3])`, once merged.
+    trace.set_logical_compaction(AntichainRef::new(&[2]));
+    trace.set_physical_compaction(AntichainRef::new(&[3]));
+    trace.exert(&mut 1_000_000);
+
+    let (mut cursor, storage) = trace.cursor();
+    let mut accumulated = Vec::new();
+    for ((key, val), times) in cursor.to_vec(&storage) {
+        let count: i64 = times.iter().map(|(_, diff)| diff).sum();
+        if count != 0 {
+            accumulated.push((key, val, count));
+        }
+    }
+    assert_eq!(accumulated, vec![
+        ("a".to_string(), vec![1, 2], 1),
+        ("b".to_string(), vec![], 1),
+    ]);
+}
+
+#[test]
+fn test_columnar_key_trace() {
+
+    let op_info = OperatorInfo::new(0, 0, &[]);
+    let mut trace = KeyTrace::new(op_info, None, None);
+    {
+        let mut batcher = <<KeyTrace as TraceReader>::Batch as Batch<String, (), usize, i64>>::Batcher::new();
+
+        batcher.push_batch(&mut vec![
+            (("a".to_string(), ()), 0, 1),
+            (("b".to_string(), ()), 1, 1),
+            (("c".to_string(), ()), 2, 1),
+            (("b".to_string(), ()), 2, -1),
+        ]);
+
+        for upper in 1 .. 4 {
+            trace.insert(batcher.seal(Antichain::from_elem(upper)));
+        }
+    }
+
+    let (mut cursor, storage) = trace.cursor();
+    assert_eq!(cursor.to_vec(&storage), vec![
+        (("a".to_string(), ()), vec![(0, 1)]),
+        (("b".to_string(), ()), vec![(1, 1), (2, -1)]),
+        (("c".to_string(), ()), vec![(2, 1)]),
+    ]);
+}
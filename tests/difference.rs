@@ -0,0 +1,165 @@
+extern crate timely;
+extern crate differential_dataflow;
+
+use timely::dataflow::operators::{ToStream, Capture};
+use timely::dataflow::operators::capture::Extract;
+use differential_dataflow::AsCollection;
+use differential_dataflow::difference::{Semigroup, Abelian, Multiply, Checked, Saturating};
+use differential_dataflow::operators::{Consolidate, Join, Threshold};
+
+#[test]
+fn checked_join() {
+
+    let data = timely::example(|scope| {
+
+        let col1 = vec![((0,0), Default::default(), Checked(3i64)),((1,2), Default::default(), Checked(1))]
+                        .into_iter()
+                        .to_stream(scope)
+                        .as_collection();
+
+        let col2 = vec![((0,'a'), Default::default(), Checked(5i64)),((1,'B'), Default::default(), Checked(1))]
+                        .into_iter()
+                        .to_stream(scope)
+                        .as_collection();
+
+        // Checked differences multiply through joins, and can be produced by `distinct`.
+        col1.join(&col2)
+            .concat(&col1.map(|(k,_)| (k, (0, 'z'))).distinct_core::<Checked<i64>>())
+            .consolidate()
+            .inner
+            .capture()
+    });
+
+    let extracted = data.extract();
+    assert_eq!(extracted.len(), 1);
+    assert_eq!(extracted[0].1, vec![
+        ((0,(0,'a')), Default::default(), Checked(15)),
+        ((0,(0,'z')), Default::default(), Checked(1)),
+        ((1,(0,'z')), Default::default(), Checked(1)),
+        ((1,(2,'B')), Default::default(), Checked(1)),
+    ]);
+}
+
+#[test]
+#[should_panic(expected = "difference overflow")]
+fn checked_overflow() {
+    let product = Checked(i64::max_value() / 2).multiply(&Checked(3));
+    assert!(product.is_zero());
+}
+
+#[test]
+fn saturating_overflow() {
+    let mut sum = Saturating(i32::max_value() - 1);
+    sum.plus_equals(&Saturating(5));
+    assert_eq!(sum, Saturating(i32::max_value()));
+    assert!(sum.is_saturated());
+    assert_eq!(Saturating(i32::min_value()).negate(), Saturating(i32::max_value()));
+    assert!(!Saturating(7i32).multiply(&Saturating(6)).is_saturated());
+}
+
+#[test]
+fn aggregate_semigroups() {
+
+    use differential_dataflow::difference::{Min, Max, Sum};
+    use differential_dataflow::operators::CountTotal;
+
+    let data = timely::example(|scope| {
+
+        // Several aggregates of the values of each key, accumulated by `count_total`.
+        (1 .. 5i64)
+            .map(|x| (x % 2, Default::default(), (Min(x), Max(x), Sum::new(x as f64 / 2.0))))
+            .to_stream(scope)
+            .as_collection()
+            .count_total()
+            .inner
+            .capture()
+    });
+
+    let extracted = data.extract();
+    assert_eq!(extracted.len(), 1);
+    let results = extracted[0].1.iter().map(|&((key, (min, max, ref sum)), _, diff)| (key, min, max, sum.value(), diff)).collect::<Vec<_>>();
+    assert_eq!(results, vec![(0, Min(2), Max(4), 3.0, 1), (1, Min(1), Max(3), 2.0, 1)]);
+}
+
+#[test]
+fn compensated_sum() {
+
+    use differential_dataflow::difference::{Monoid, Sum};
+
+    // Naive summation loses the small values entirely.
+    let mut sum = Sum::new(1e16f64);
+    for _ in 0 .. 10 {
+        sum.plus_equals(&Sum::new(1.0));
+    }
+    sum.plus_equals(&Sum::new(-1e16));
+    assert_eq!(sum.value(), 10.0);
+
+    sum.plus_equals(&Sum::new(-10.0));
+    assert!(sum.is_zero());
+    assert!(Sum::<f32>::zero().is_zero());
+}
+
+#[test]
+fn map_semigroup() {
+
+    use std::collections::BTreeMap;
+    use differential_dataflow::difference::Monoid;
+
+    let mut map = BTreeMap::<&str, isize>::zero();
+    map.plus_equals(&vec![("a", 1), ("b", 2)].into_iter().collect());
+    map.plus_equals(&vec![("a", -1), ("c", 3)].into_iter().collect());
+    assert_eq!(map, vec![("b", 2), ("c", 3)].into_iter().collect());
+    map.plus_equals(&map.clone().negate());
+    assert!(map.is_zero() && map.is_empty());
+}
+
+#[test]
+fn exchanged_map() {
+
+    use differential_dataflow::difference::Map;
+    use differential_dataflow::operators::CountTotal;
+
+    let data = timely::example(|scope| {
+
+        // The number of records of each parity of each key, accumulated by `count_total`.
+        vec![(0, 1u32), (0, 2), (0, 4), (1, 3), (1, 5)]
+            .into_iter()
+            .map(|(key, val)| (key, Default::default(), Map::singleton(val % 2, 1isize)))
+            .to_stream(scope)
+            .as_collection()
+            .count_total()
+            .inner
+            .capture()
+    });
+
+    let extracted = data.extract();
+    assert_eq!(extracted.len(), 1);
+    assert_eq!(extracted[0].1, vec![
+        ((0, vec![(0, 2), (1, 1)].into_iter().collect::<Map<_,_>>()), Default::default(), 1),
+        ((1, vec![(1, 2)].into_iter().collect::<Map<_,_>>()), Default::default(), 1),
+    ]);
+
+    let mut map = vec![(0u32, 2isize), (1, 1)].into_iter().collect::<Map<_,_>>();
+    map.plus_equals(&vec![(1, -1), (2, 3)].into_iter().collect());
+    assert_eq!(map.iter().cloned().collect::<Vec<_>>(), vec![(0, 2), (2, 3)]);
+    assert_eq!(map.get(&2), Some(&3));
+    assert_eq!(map.get(&1), None);
+    map.plus_equals(&map.clone().negate());
+    assert!(map.is_zero());
+}
+
+#[test]
+fn extreme_zeros() {
+
+    use differential_dataflow::difference::{Monoid, Min, Max};
+
+    let mut min = Min::<u32>::zero();
+    let mut max = Max::<u32>::zero();
+    assert!(min.is_zero() && max.is_zero());
+    min.plus_equals(&Min(5));
+    max.plus_equals(&Max(5));
+    assert_eq!((min, max), (Min(5), Max(5)));
+    assert!(!min.is_zero() && !max.is_zero());
+    assert!(min.multiply(&0u64).is_zero() && max.multiply(&0u64).is_zero());
+    assert_eq!(min.multiply(&2u64), Min(5));
+}
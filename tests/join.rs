@@ -76,6 +76,258 @@ fn antijoin() {
     assert_eq!(extracted[0].1, vec![((1,2), Default::default(),1)]);
 }
 
+#[test]
+fn left_join() {
+    let data = timely::example(|scope| {
+        let col1 = vec![((0,0), Default::default(),1),((1,2), Default::default(),2)].into_iter().to_stream(scope).as_collection();
+        let col2 = vec![((0,'a'), Default::default(),1),((0,'b'), Default::default(),1),((2,'c'), Default::default(),1)].into_iter().to_stream(scope).as_collection();
+
+        // should match `(0,0)` with both values, and retain `(1,2)` without a match.
+        col1.left_join(&col2).consolidate().inner.capture()
+    });
+    let extracted = data.extract();
+    assert_eq!(extracted.len(), 1);
+    assert_eq!(extracted[0].1, vec![((0,(0,Some('a'))), Default::default(),1), ((0,(0,Some('b'))), Default::default(),1), ((1,(2,None)), Default::default(),2)]);
+}
+
+#[test]
+fn full_outer_join() {
+    let data = timely::example(|scope| {
+        let col1 = vec![((0,0), Default::default(),1),((1,2), Default::default(),1)].into_iter().to_stream(scope).as_collection();
+        let col2 = vec![((0,'a'), Default::default(),1),((2,'c'), Default::default(),1)].into_iter().to_stream(scope).as_collection();
+
+        // should match key `0`, and retain keys `1` and `2` from either side.
+        col1.full_outer_join(&col2).consolidate().inner.capture()
+    });
+    let extracted = data.extract();
+    assert_eq!(extracted.len(), 1);
+    assert_eq!(extracted[0].1, vec![((0,(Some(0),Some('a'))), Default::default(),1), ((1,(Some(2),None)), Default::default(),1), ((2,(None,Some('c'))), Default::default(),1)]);
+}
+
+#[test]
+fn left_join_retracts_unmatched() {
+
+    use differential_dataflow::input::Input;
+
+    let data = timely::execute_directly(|worker| {
+        let (mut input1, mut input2, captured) = worker.dataflow::<u32,_,_>(|scope| {
+            let (input1, col1) = scope.new_collection::<(u32, u32), isize>();
+            let (input2, col2) = scope.new_collection::<(u32, char), isize>();
+            (input1, input2, col1.left_join(&col2).consolidate().inner.capture())
+        });
+
+        input1.insert((0, 0));
+        input1.advance_to(1);
+        input2.advance_to(1);
+        input2.insert((0, 'a'));
+        input1.close();
+        input2.close();
+        while worker.step() { }
+        captured
+    });
+
+    // The unmatched record is replaced by the match once the key appears in the second input.
+    let mut updates = data.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    updates.sort();
+    assert_eq!(updates, vec![((0,(0,None)),0,1), ((0,(0,None)),1,-1), ((0,(0,Some('a'))),1,1)]);
+}
+
+#[test]
+fn band_join() {
+
+    use differential_dataflow::input::Input;
+    use differential_dataflow::operators::band::BandJoin;
+
+    let data = timely::execute_directly(|worker| {
+        let (mut input1, mut input2, captured) = worker.dataflow::<u32,_,_>(|scope| {
+            let (input1, col1) = scope.new_collection::<(u64, char), isize>();
+            let (input2, col2) = scope.new_collection::<(u64, u32), isize>();
+
+            // the band join should agree with a filtered cross join.
+            let expected = col1.map(|x| ((), x))
+                               .join(&col2.map(|x| ((), x)))
+                               .map(|(_, pair)| pair)
+                               .filter(|&((k1, _), (k2, _))| k1 <= k2 + 2 && k2 <= k1 + 5);
+
+            col1.band_join(&col2, 2, 5)
+                .assert_eq(&expected);
+
+            (input1, input2, col1.band_join(&col2, 2, 5).consolidate().inner.capture())
+        });
+
+        input1.insert((10, 'a'));
+        input1.insert((1, 'b'));
+        input2.insert((7, 0));
+        input2.insert((8, 1));
+        input2.insert((15, 2));
+        input2.insert((16, 3));
+        input2.insert((0, 4));
+        input1.advance_to(1);
+        input2.advance_to(1);
+        input2.remove((15, 2));
+        input1.insert((11, 'c'));
+        input1.close();
+        input2.close();
+        while worker.step() { }
+        captured
+    });
+
+    let mut updates = data.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    updates.sort();
+    assert_eq!(updates, vec![
+        (((1,'b'),(0,4)),0,1),
+        (((10,'a'),(8,1)),0,1),
+        (((10,'a'),(15,2)),0,1),
+        (((10,'a'),(15,2)),1,-1),
+        (((11,'c'),(16,3)),1,1),
+    ]);
+}
+
+#[test]
+fn as_of_join() {
+
+    use differential_dataflow::input::Input;
+    use differential_dataflow::operators::arrange::ArrangeByKey;
+    use differential_dataflow::operators::asof::AsOfJoin;
+
+    let data = timely::execute_directly(|worker| {
+        let (mut records, mut versions, captured) = worker.dataflow::<u32,_,_>(|scope| {
+            let (records, col1) = scope.new_collection::<((u32, u64), char), isize>();
+            let (versions, col2) = scope.new_collection::<((u32, u64), u32), isize>();
+            (records, versions, col1.as_of_join(&col2.arrange_by_key()).consolidate().inner.capture())
+        });
+
+        records.insert(((0, 5), 'a'));
+        records.insert(((0, 12), 'b'));
+        records.insert(((1, 3), 'c'));
+        versions.insert(((0, 0), 100));
+        versions.insert(((0, 10), 200));
+        records.advance_to(1);
+        versions.advance_to(1);
+
+        // new versions supersede old versions for later records, and retracted versions expose earlier versions.
+        records.insert(((0, 2), 'd'));
+        versions.insert(((0, 4), 300));
+        versions.insert(((1, 1), 400));
+        versions.remove(((0, 10), 200));
+        records.close();
+        versions.close();
+        while worker.step() { }
+        captured
+    });
+
+    let mut updates = data.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    updates.sort();
+    assert_eq!(updates, vec![
+        (((0,2),('d',100)),1,1),
+        (((0,5),('a',100)),0,1),
+        (((0,5),('a',100)),1,-1),
+        (((0,5),('a',300)),1,1),
+        (((0,12),('b',200)),0,1),
+        (((0,12),('b',200)),1,-1),
+        (((0,12),('b',300)),1,1),
+        (((1,3),('c',400)),1,1),
+    ]);
+}
+
+#[test]
+fn as_of_join_lagging_records() {
+
+    use differential_dataflow::input::Input;
+    use differential_dataflow::operators::arrange::ArrangeByKey;
+    use differential_dataflow::operators::asof::AsOfJoin;
+
+    let data = timely::execute_directly(|worker| {
+        let (mut records, mut versions, captured) = worker.dataflow::<u32,_,_>(|scope| {
+            let (records, col1) = scope.new_collection::<((u32, u64), char), isize>();
+            let (versions, col2) = scope.new_collection::<((u32, u64), u32), isize>();
+            (records, versions, col1.as_of_join(&col2.arrange_by_key()).consolidate().inner.capture())
+        });
+
+        // versions change several times before records at later times are known.
+        records.insert(((0, 15), 'a'));
+        records.flush();
+        versions.insert(((0, 10), 1));
+        versions.advance_to(1);
+        versions.insert(((0, 12), 2));
+        versions.advance_to(2);
+        versions.remove(((0, 12), 2));
+        versions.insert(((0, 14), 3));
+        versions.advance_to(3);
+        versions.flush();
+        for _ in 0 .. 100 { worker.step(); }
+
+        records.advance_to(1);
+        records.insert(((0, 13), 'b'));
+        records.close();
+        versions.close();
+        while worker.step() { }
+        captured
+    });
+
+    let mut updates = data.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    updates.sort();
+    assert_eq!(updates, vec![
+        (((0,13),('b',1)),2,1),
+        (((0,13),('b',2)),1,1),
+        (((0,13),('b',2)),2,-1),
+        (((0,15),('a',1)),0,1),
+        (((0,15),('a',1)),1,-1),
+        (((0,15),('a',2)),1,1),
+        (((0,15),('a',2)),2,-1),
+        (((0,15),('a',3)),2,1),
+    ]);
+}
+
+#[test]
+fn skew_join() {
+
+    use differential_dataflow::input::Input;
+    use differential_dataflow::operators::skew::SkewJoin;
+    use timely::dataflow::operators::Probe;
+
+    timely::execute(timely::Config::process(3), |worker| {
+
+        let index = worker.index();
+        let (mut input1, mut input2, probe) = worker.dataflow::<u32,_,_>(|scope| {
+            let (input1, col1) = scope.new_collection::<(u32, u32), isize>();
+            let (input2, col2) = scope.new_collection::<(u32, char), isize>();
+
+            // the skew join should agree with the join, whichever keys are heavy.
+            let expected = col1.join(&col2);
+            col1.skew_join(&col2, 10)
+                .assert_eq(&expected);
+
+            (input1, input2, expected.inner.probe())
+        });
+
+        if index == 0 {
+            // key `0` is heavy, and keys `1` and `2` are light.
+            for val in 0 .. 100 { input1.insert((0, val)); }
+            for val in 0 .. 5 { input1.insert((1, val)); }
+            input1.insert((2, 0));
+            input2.insert((0, 'a'));
+            input2.insert((0, 'b'));
+            input2.insert((1, 'c'));
+        }
+        input1.advance_to(1); input1.flush();
+        input2.advance_to(1); input2.flush();
+        worker.step_while(|| probe.less_than(input1.time()));
+
+        if index == 0 {
+            // key `0` becomes light, and key `1` becomes heavy.
+            for val in 5 .. 100 { input1.remove((0, val)); }
+            for val in 5 .. 20 { input1.insert((1, val)); }
+            input2.remove((0, 'b'));
+            input2.insert((1, 'd'));
+        }
+        input1.advance_to(2); input1.flush();
+        input2.advance_to(2); input2.flush();
+        worker.step_while(|| probe.less_than(input1.time()));
+
+    }).unwrap();
+}
+
 #[test] fn join_scale_1() { join_scaling(1); }
 #[test] fn join_scale_10() { join_scaling(10); }
 #[test] fn join_scale_100() { join_scaling(100); }
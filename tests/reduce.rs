@@ -42,4 +42,134 @@ fn reduce_scaling() {
 
     let extracted = data.extract();
     assert_eq!(extracted.len(), 1);
-}
\ No newline at end of file
+}
+#[test]
+fn count_distinct_approx() {
+
+    use differential_dataflow::difference::Present;
+    use differential_dataflow::operators::count::CountDistinctApprox;
+
+    let data = timely::example(|scope| {
+
+        // Ten thousand distinct values for key zero, each twice, and one hundred for key one.
+        let col1 = (0 .. 20_000u64)
+                        .map(|x| ((0, x % 10_000), Default::default(), Present))
+                        .chain((0 .. 100u64).map(|x| ((1, x), Default::default(), Present)))
+                        .to_stream(scope)
+                        .as_collection();
+
+        col1.count_distinct_approx().inner.capture()
+    });
+
+    let extracted = data.extract();
+    assert_eq!(extracted.len(), 1);
+    let estimates = extracted[0].1.iter().map(|&((key, estimate), _, diff)| (key, estimate, diff)).collect::<Vec<_>>();
+    assert_eq!(estimates.len(), 2);
+    assert!(estimates[0].0 == 0 && estimates[0].2 == 1);
+    assert!(estimates[0].1 > 9_500 && estimates[0].1 < 10_500, "estimate {} too far from 10000", estimates[0].1);
+    assert!(estimates[1].0 == 1 && estimates[1].2 == 1);
+    assert!(estimates[1].1 > 95 && estimates[1].1 < 105, "estimate {} too far from 100", estimates[1].1);
+}
+
+#[test]
+fn hyperloglog_semigroup() {
+
+    use differential_dataflow::difference::{Semigroup, Monoid, HyperLogLog};
+
+    let mut sketch = HyperLogLog::zero();
+    assert!(sketch.is_zero());
+    for hash in 0 .. 1000u64 {
+        sketch.plus_equals(&HyperLogLog::from_hash(hash));
+    }
+    let mut other = HyperLogLog::zero();
+    for hash in 500 .. 1500u64 {
+        other.plus_equals(&HyperLogLog::from_hash(hash));
+    }
+
+    // Addition unions the sketched sets, and is idempotent.
+    let mut union = sketch.clone();
+    union.plus_equals(&other);
+    union.plus_equals(&other);
+    let mut reversed = other.clone();
+    reversed.plus_equals(&sketch);
+    assert_eq!(union, reversed);
+    assert!(union.estimate() > 1_425 && union.estimate() < 1_575);
+
+    // Multiplication by a positive count leaves the sketch unchanged, and by zero empties it.
+    use differential_dataflow::difference::Multiply;
+    assert_eq!(union.clone().multiply(&3u64), union);
+    assert!(union.multiply(&0u64).is_zero());
+}
+
+#[test]
+fn combine() {
+
+    use differential_dataflow::input::Input;
+    use differential_dataflow::operators::consolidate::Combine;
+
+    let data = timely::execute_directly(|worker| {
+        let (mut input, captured) = worker.dataflow::<u32,_,_>(|scope| {
+            let (input, col) = scope.new_collection::<u32, isize>();
+            (input, col.combine().inner.capture())
+        });
+
+        // updates at the same time are collapsed across messages, and cancelling updates vanish.
+        for round in 0 .. 3 {
+            for _ in 0 .. 100 {
+                input.insert(round % 2);
+                input.flush();
+                worker.step();
+            }
+            input.remove(round % 2);
+            input.insert(7);
+            input.remove(7);
+            input.advance_to(round + 1);
+        }
+        input.close();
+        while worker.step() { }
+        captured
+    });
+
+    let mut updates = data.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    updates.sort();
+    assert_eq!(updates, vec![(0, 0, 99), (0, 2, 99), (1, 1, 99)]);
+}
+
+#[test]
+fn combine_switch() {
+
+    use differential_dataflow::input::Input;
+    use differential_dataflow::operators::{Count, CountTotal, Threshold, ThresholdTotal, Consolidate};
+    use differential_dataflow::operators::consolidate::Combine;
+
+    let data = timely::execute_directly(|worker| {
+        let (mut input, captured) = worker.dataflow::<u32,_,_>(|scope| {
+            let (input, col) = scope.new_collection::<u32, isize>();
+            let counts = col.count_with_combine(false).concat(&col.count().negate());
+            let totals = col.count_total_with_combine(false).concat(&col.count_total().negate());
+            let distinct = col.threshold_named_with_combine("Distinct", |_,_| 1, false).concat(&col.distinct().negate());
+            let semigroup = col.threshold_semigroup_with_combine(|_,_,old| if old.is_none() { Some(1) } else { None }, false)
+                               .concat(&col.distinct_total().negate());
+            let combined = col.combine().count().concat(&col.count().negate());
+            (input, counts.concat(&totals).concat(&combined).map(|(x, _)| x).concat(&distinct).concat(&semigroup).consolidate().inner.capture())
+        });
+
+        // enough updates in many messages that the combiner consolidates them several times.
+        for round in 0 .. 3 {
+            for index in 0 .. 5_000 {
+                input.insert(index % 17);
+                if index % 100 == 0 {
+                    input.flush();
+                    worker.step();
+                }
+            }
+            input.remove(round);
+            input.advance_to(round + 1);
+        }
+        input.close();
+        while worker.step() { }
+        captured
+    });
+
+    assert!(data.extract().into_iter().all(|(_, data)| data.is_empty()));
+}
@@ -0,0 +1,49 @@
+extern crate timely;
+extern crate differential_dataflow;
+
+use timely::dataflow::operators::{Capture, Probe};
+use timely::dataflow::operators::capture::Extract;
+
+use differential_dataflow::input::InputSession;
+use differential_dataflow::operators::TemporalFilter;
+
+#[test]
+fn test_temporal_filter() {
+
+    let captured = timely::execute_directly(move |worker| {
+
+        let mut input = InputSession::<u64, (u64, u64), isize>::new();
+        let (probe, captured) = worker.dataflow(|scope| {
+            let filtered = input.to_collection(scope).temporal_filter(|&(from, to)| (from, to));
+            (filtered.inner.probe(), filtered.inner.capture())
+        });
+
+        // Valid from the start, from the future, never, and from the past.
+        input.insert((0, 3));
+        input.insert((2, 5));
+        input.insert((4, 4));
+        input.advance_to(1);
+        input.insert((0, 2));
+        input.advance_to(4);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+
+        // Retractions are held back until their times complete.
+        input.advance_to(10);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+
+        captured
+    });
+
+    let mut updates = captured.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    updates.sort();
+    assert_eq!(updates, vec![
+        ((0, 2), 1, 1),
+        ((0, 2), 2, -1),
+        ((0, 3), 0, 1),
+        ((0, 3), 3, -1),
+        ((2, 5), 2, 1),
+        ((2, 5), 5, -1),
+    ]);
+}
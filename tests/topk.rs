@@ -0,0 +1,119 @@
+extern crate timely;
+extern crate differential_dataflow;
+
+use timely::dataflow::operators::Probe;
+
+use differential_dataflow::input::InputSession;
+use differential_dataflow::operators::{Consolidate, MinMax, Reduce, TopK};
+
+#[test]
+fn test_top_k() {
+
+    timely::execute_directly(move |worker| {
+
+        let mut input = InputSession::<u64, (u64, u64), isize>::new();
+        let probe = worker.dataflow(|scope| {
+
+            let values = input.to_collection(scope);
+
+            // The five largest records of each key after the first two, computed in one reduction.
+            let expected = values.reduce(|_key, input, output| {
+                let mut records = Vec::new();
+                for &(val, count) in input.iter().rev() {
+                    for _ in 0 .. count {
+                        records.push(val);
+                    }
+                }
+                for val in records.into_iter().skip(2).take(5) {
+                    output.push((*val, 1));
+                }
+            });
+
+            values
+                .top_k(|x, y| y.cmp(x), 5, Some(2))
+                .consolidate()
+                .assert_eq(&expected);
+
+            expected.inner.probe()
+        });
+
+        for key in 0 .. 4 {
+            for val in 0 .. 1000 {
+                input.insert((key, val));
+            }
+        }
+        input.insert((0, 998));
+        input.advance_to(1);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+
+        // Retract the largest values, including one copy of a repeated value.
+        for key in 0 .. 4 {
+            input.remove((key, 999));
+            input.remove((key, 998));
+        }
+        input.advance_to(2);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+
+        for key in 0 .. 4 {
+            input.insert((key, 2000 + key));
+        }
+        input.advance_to(3);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+    });
+}
+
+#[test]
+fn test_min_max_by_key() {
+
+    timely::execute_directly(move |worker| {
+
+        let mut input = InputSession::<u64, (u64, u64), isize>::new();
+        let probe = worker.dataflow(|scope| {
+
+            let values = input.to_collection(scope);
+
+            // The extreme values of each key modulo 100, computed in one reduction.
+            let min = values.reduce(|_key, input, output| {
+                let val = input.iter().min_by_key(|&&(val, _)| (val % 100, val)).unwrap().0;
+                output.push((*val, 1));
+            });
+            let max = values.reduce(|_key, input, output| {
+                let val = input.iter().min_by_key(|&&(val, _)| (::std::cmp::Reverse(val % 100), val)).unwrap().0;
+                output.push((*val, 1));
+            });
+
+            values
+                .min_by_key(|val| val % 100)
+                .as_collection(|k,v| (*k, *v))
+                .assert_eq(&min);
+
+            values
+                .max_by_key(|val| val % 100)
+                .as_collection(|k,v| (*k, *v))
+                .assert_eq(&max);
+
+            max.inner.probe()
+        });
+
+        for key in 0 .. 4 {
+            for val in 0 .. 1000 {
+                input.insert((key, val));
+            }
+        }
+        input.advance_to(1);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+
+        // Retract the current extreme values of each key.
+        for key in 0 .. 4 {
+            input.remove((key, 0));
+            input.remove((key, 99));
+        }
+        input.advance_to(2);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+    });
+}
@@ -63,3 +63,359 @@ fn test_trace() {
     let vec_4 = cursor4.to_vec(&storage4);
     assert_eq!(vec_4, vec_3);
 }
+
+#[test]
+fn test_radix_batcher() {
+
+    use differential_dataflow::trace::implementations::radix_batcher::RadixBatcher;
+
+    let op_info = OperatorInfo::new(0, 0, &[]);
+    let mut trace = IntegerTrace::new(op_info, None, None);
+    {
+        let mut batcher = RadixBatcher::<u64, u64, usize, i64, <IntegerTrace as TraceReader>::Batch>::new();
+
+        batcher.push_batch(&mut vec![
+            ((2, 3), 2, -1),
+            ((1, 2), 0, 1),
+            ((300, 1), 1, 1),
+            ((2, 3), 1, 1),
+            ((300, 1), 1, -1),
+            ((1, 2), 0, 1),
+        ]);
+
+        for upper in 1 .. 4 {
+            trace.insert(batcher.seal(Antichain::from_elem(upper)));
+        }
+    }
+
+    let (mut cursor, storage) = trace.cursor();
+    assert_eq!(cursor.to_vec(&storage), vec![
+               ((1, 2), vec![(0, 2)]),
+               ((2, 3), vec![(1, 1), (2, -1)]),
+    ]);
+}
+
+#[test]
+fn test_hashed_trace() {
+
+    use differential_dataflow::hashable::HashableWrapper;
+    use differential_dataflow::trace::Cursor;
+    use differential_dataflow::trace::implementations::hashed::HashValSpine;
+
+    type HashTrace = HashValSpine<HashableWrapper<u64>, u64, usize, i64>;
+
+    let op_info = OperatorInfo::new(0, 0, &[]);
+    let mut trace = HashTrace::new(op_info, None, None);
+    {
+        let mut batcher = <<HashTrace as TraceReader>::Batch as Batch<HashableWrapper<u64>, u64, usize, i64>>::Batcher::new();
+
+        let mut updates = Vec::new();
+        for key in 0 .. 1000u64 {
+            updates.push(((HashableWrapper::from(key), key + 1), (key % 3) as usize, 1));
+        }
+        batcher.push_batch(&mut updates);
+
+        for upper in 1 .. 4 {
+            trace.insert(batcher.seal(Antichain::from_elem(upper)));
+        }
+    }
+
+    trace.exert(&mut 1_000_000);
+
+    let (mut cursor, storage) = trace.cursor();
+    assert_eq!(cursor.to_vec(&storage).len(), 1000);
+
+    // Each key can be found by seeking, and only once.
+    for key in 0 .. 1000u64 {
+        let wrapped = HashableWrapper::from(key);
+        cursor.rewind_keys(&storage);
+        cursor.seek_key(&storage, &wrapped);
+        assert!(cursor.key_valid(&storage));
+        assert_eq!(cursor.key(&storage), &wrapped);
+        assert_eq!(cursor.val(&storage), &(key + 1));
+    }
+}
+
+#[test]
+fn test_hashed_merge_vacant_slots() {
+
+    use differential_dataflow::hashable::{Hashable, HashableWrapper};
+    use differential_dataflow::trace::{BatchReader, Builder, Merger};
+    use differential_dataflow::trace::implementations::hashed::HashValBatch;
+
+    type HashBatch = HashValBatch<HashableWrapper<u64>, u64, usize, i64>;
+
+    fn build(mut keys: Vec<HashableWrapper<u64>>, lower: usize) -> HashBatch {
+        keys.sort();
+        let mut builder = <HashBatch as Batch<HashableWrapper<u64>, u64, usize, i64>>::Builder::new();
+        for key in keys {
+            let val = key.item + 1;
+            builder.push((key, val, lower, 1));
+        }
+        builder.done(Antichain::from_elem(lower), Antichain::from_elem(lower + 1), Antichain::from_elem(0))
+    }
+
+    // A few keys with small hashes, then more than a thousand vacant slots before keys with large hashes.
+    // Merged with an empty batch, the slots are copied in ranges of a thousand, one of which is vacant.
+    let keys = (0u64 ..).map(HashableWrapper::from);
+    let mut large = keys.clone().filter(|key| key.hashed() >= u64::max_value() / 4 * 3).take(2000).collect::<Vec<_>>();
+    let small = keys.filter(|key| key.hashed() < u64::max_value() / 4096).take(5).collect::<Vec<_>>();
+    large.extend(small);
+    let batch1 = build(large, 0);
+    let batch2 = build(Vec::new(), 1);
+
+    let mut merger = batch1.begin_merge(&batch2, None);
+    loop {
+        let mut fuel = 10;
+        merger.work(&batch1, &batch2, &mut fuel);
+        if fuel > 0 { break; }
+    }
+    let merged = merger.done();
+
+    assert_eq!(merged.len(), 2005);
+    let mut cursor = merged.cursor();
+    assert_eq!(cursor.to_vec(&merged).len(), 2005);
+}
+
+#[test]
+fn test_spilled_trace() {
+
+    use std::path::PathBuf;
+    use differential_dataflow::trace::implementations::spilled::{OrdValSpineSpill, SpillPolicy};
+
+    // Spill every merged batch with any updates.
+    struct SpillEverything;
+    impl SpillPolicy for SpillEverything {
+        fn threshold() -> usize { 0 }
+        fn directory() -> PathBuf { std::env::temp_dir() }
+    }
+
+    type SpillTrace = OrdValSpineSpill<u64, u64, usize, i64, SpillEverything>;
+
+    let op_info = OperatorInfo::new(0, 0, &[]);
+    let mut trace = SpillTrace::new(op_info, None, None);
+    {
+        let mut batcher = <<SpillTrace as TraceReader>::Batch as Batch<u64, u64, usize, i64>>::Batcher::new();
+
+        let mut updates = Vec::new();
+        for key in 0 .. 1000u64 {
+            updates.push(((key, key + 1), (key % 3) as usize, 1));
+        }
+        batcher.push_batch(&mut updates);
+
+        for upper in 1 .. 4 {
+            trace.insert(batcher.seal(Antichain::from_elem(upper)));
+        }
+    }
+
+    trace.exert(&mut 1_000_000);
+
+    let mut spilled = false;
+    trace.map_batches(|batch| spilled |= batch.is_spilled());
+    assert!(spilled);
+
+    let (mut cursor, storage) = trace.cursor();
+    let contents = cursor.to_vec(&storage);
+    assert_eq!(contents.len(), 1000);
+    for ((key, val), times) in contents {
+        assert_eq!(val, key + 1);
+        assert_eq!(times, vec![((key % 3) as usize, 1)]);
+    }
+}
+
+#[test]
+fn test_background_trace() {
+
+    use differential_dataflow::trace::implementations::background::{self, OrdValSpineBackground};
+
+    // Hand every merge to the helper threads.
+    background::set_merge_threshold(0);
+
+    type BackgroundTrace = OrdValSpineBackground<u64, u64, usize, i64>;
+
+    let op_info = OperatorInfo::new(0, 0, &[]);
+    let mut trace = BackgroundTrace::new(op_info, None, None);
+    {
+        let mut batcher = <<BackgroundTrace as TraceReader>::Batch as Batch<u64, u64, usize, i64>>::Batcher::new();
+
+        let mut updates = Vec::new();
+        for key in 0 .. 1000u64 {
+            updates.push(((key, key + 1), (key % 10) as usize, 1));
+        }
+        batcher.push_batch(&mut updates);
+
+        for upper in 1 .. 11 {
+            trace.insert(batcher.seal(Antichain::from_elem(upper)));
+        }
+    }
+
+    // Merges proceed in the background, and are awaited once given enough fuel to complete.
+    trace.exert(&mut 100);
+    trace.exert(&mut 1_000_000);
+
+    let (mut cursor, storage) = trace.cursor();
+    let contents = cursor.to_vec(&storage);
+    assert_eq!(contents.len(), 1000);
+    for ((key, val), times) in contents {
+        assert_eq!(val, key + 1);
+        assert_eq!(times, vec![((key % 10) as usize, 1)]);
+    }
+}
+
+#[test]
+fn test_adaptive_effort() {
+
+    use std::time::Duration;
+    use differential_dataflow::trace::implementations::effort::{AdaptiveEffort, MergeEffort, MergeProgress};
+
+    let mut policy = AdaptiveEffort::new(Duration::from_millis(10), 16);
+    assert_eq!(policy.fuel(4), 8 << 4);
+
+    // A growing backlog within the time budget increases effort.
+    let progress = MergeProgress { spent: 100, elapsed: Duration::from_millis(1), backlog: 1000 };
+    assert_eq!(policy.observe(&progress), None);
+    assert_eq!(policy.effort(), 2);
+    assert_eq!(policy.fuel(4), 16 << 4);
+
+    // Exceeding the time budget decreases effort, and starting merges is not a shortfall.
+    let progress = MergeProgress { spent: 100, elapsed: Duration::from_millis(20), backlog: 1500 };
+    assert_eq!(policy.observe(&progress), None);
+    assert_eq!(policy.effort(), 1);
+
+    // Introducing more updates than the backlog without retiring any of it is a shortfall.
+    for _ in 0 .. 100 {
+        policy.fuel(4);
+    }
+    assert_eq!(policy.observe(&progress), Some(1500));
+    assert_eq!(policy.observe(&progress), None);
+
+    // Retiring merges restarts the count of introduced updates.
+    for _ in 0 .. 100 {
+        policy.fuel(4);
+    }
+    let progress = MergeProgress { spent: 100, elapsed: Duration::from_millis(20), backlog: 500 };
+    assert_eq!(policy.observe(&progress), None);
+
+    // A spine using the policy maintains its contents.
+    let op_info = OperatorInfo::new(0, 0, &[]);
+    let mut trace = IntegerTrace::with_effort_policy(Box::new(policy), op_info, None, None);
+    {
+        let mut batcher = <<IntegerTrace as TraceReader>::Batch as Batch<u64, u64, usize, i64>>::Batcher::new();
+
+        let mut updates = Vec::new();
+        for key in 0 .. 1000u64 {
+            updates.push(((key, key + 1), (key % 10) as usize, 1));
+        }
+        batcher.push_batch(&mut updates);
+
+        for upper in 1 .. 11 {
+            trace.insert(batcher.seal(Antichain::from_elem(upper)));
+        }
+    }
+    trace.exert(&mut 1_000_000);
+
+    let (mut cursor, storage) = trace.cursor();
+    assert_eq!(cursor.to_vec(&storage).len(), 1000);
+}
+
+#[test]
+fn test_key_summaries() {
+
+    use differential_dataflow::trace::{BatchReader, Cursor};
+    use differential_dataflow::trace::implementations::ord::OrdValSpineBloom;
+
+    type BloomTrace = OrdValSpineBloom<u64, u64, usize, i64>;
+
+    let op_info = OperatorInfo::new(0, 0, &[]);
+    let mut trace = BloomTrace::new(op_info, None, None);
+    {
+        let mut batcher = <<BloomTrace as TraceReader>::Batch as Batch<u64, u64, usize, i64>>::Batcher::new();
+
+        // Each batch holds even keys from a distinct range.
+        for upper in 1 .. 5 {
+            let mut updates = Vec::new();
+            for key in 0 .. 100u64 {
+                let key = 2 * (100 * (upper as u64 - 1) + key);
+                updates.push(((key, key + 1), upper - 1, 1));
+            }
+            batcher.push_batch(&mut updates);
+            trace.insert(batcher.seal(Antichain::from_elem(upper)));
+        }
+    }
+
+    // Batches contain their least key, and rule out keys beyond their greatest key.
+    trace.map_batches(|batch| {
+        let cursor = batch.cursor();
+        let lower = 200 * batch.description().lower().elements()[0] as u64;
+        let upper = 200 * batch.description().upper().elements()[0] as u64;
+        assert!(cursor.may_contain(batch, &lower));
+        assert!(!cursor.may_contain(batch, &upper));
+    });
+
+    // Seeking present and absent keys produces the same results as without summaries.
+    let (mut cursor, storage) = trace.cursor();
+    for key in 0 .. 800u64 {
+        cursor.seek_key(&storage, &key);
+        let expected = if key % 2 == 0 { key } else { key + 1 };
+        if expected < 800 {
+            assert_eq!(cursor.get_key(&storage), Some(&expected));
+            assert_eq!(cursor.get_val(&storage), Some(&(expected + 1)));
+            if key % 2 == 0 {
+                cursor.step_key(&storage);
+                if key + 2 < 800 {
+                    assert_eq!(cursor.get_key(&storage), Some(&(key + 2)));
+                }
+            }
+        }
+        else {
+            assert!(!cursor.key_valid(&storage));
+        }
+    }
+}
+
+#[test]
+fn test_key_summaries_without_values() {
+
+    use differential_dataflow::trace::{BatchReader, Cursor};
+    use differential_dataflow::trace::implementations::ord::OrdKeySpineBloom;
+
+    type BloomTrace = OrdKeySpineBloom<u64, usize, i64>;
+
+    let op_info = OperatorInfo::new(0, 0, &[]);
+    let mut trace = BloomTrace::new(op_info, None, None);
+    {
+        let mut batcher = <<BloomTrace as TraceReader>::Batch as Batch<u64, (), usize, i64>>::Batcher::new();
+
+        // Each batch holds even keys from a distinct range.
+        for upper in 1 .. 5 {
+            let mut updates = Vec::new();
+            for key in 0 .. 100u64 {
+                updates.push(((2 * (100 * (upper as u64 - 1) + key), ()), upper - 1, 1));
+            }
+            batcher.push_batch(&mut updates);
+            trace.insert(batcher.seal(Antichain::from_elem(upper)));
+        }
+    }
+
+    // Batches contain their least key, and rule out keys beyond their greatest key.
+    trace.map_batches(|batch| {
+        let cursor = batch.cursor();
+        let lower = 200 * batch.description().lower().elements()[0] as u64;
+        let upper = 200 * batch.description().upper().elements()[0] as u64;
+        assert!(cursor.may_contain(batch, &lower));
+        assert!(!cursor.may_contain(batch, &upper));
+    });
+
+    // Seeking present and absent keys produces the same results as without summaries.
+    let (mut cursor, storage) = trace.cursor();
+    for key in 0 .. 800u64 {
+        cursor.seek_key(&storage, &key);
+        let expected = if key % 2 == 0 { key } else { key + 1 };
+        if expected < 800 {
+            assert_eq!(cursor.get_key(&storage), Some(&expected));
+        }
+        else {
+            assert!(!cursor.key_valid(&storage));
+        }
+    }
+}
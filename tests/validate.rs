@@ -0,0 +1,76 @@
+extern crate timely;
+extern crate differential_dataflow;
+
+use timely::dataflow::operators::generic::OperatorInfo;
+use timely::progress::Antichain;
+
+use differential_dataflow::trace::{Trace, TraceReader, Batch, Batcher, Builder};
+use differential_dataflow::trace::implementations::ord::{OrdValBatch, OrdValSpine};
+use differential_dataflow::trace::validate::{validate, validate_batch, Violation, ViolationKind};
+
+type IntegerBatch = OrdValBatch<u64, u64, usize, i64>;
+
+#[test]
+fn test_validate_trace() {
+
+    let op_info = OperatorInfo::new(0, 0, &[]);
+    let mut trace = OrdValSpine::<u64, u64, usize, i64>::new(op_info, None, None);
+    {
+        let mut batcher = <<OrdValSpine<u64, u64, usize, i64> as TraceReader>::Batch as Batch<u64, u64, usize, i64>>::Batcher::new();
+
+        let mut updates = Vec::new();
+        for key in 0 .. 100u64 {
+            updates.push(((key, key), (key % 5) as usize, 1));
+            updates.push(((key, key + 1), (key % 3) as usize, 1));
+        }
+        batcher.push_batch(&mut updates);
+
+        for upper in 1 .. 6 {
+            trace.insert(batcher.seal(Antichain::from_elem(upper)));
+        }
+    }
+
+    assert_eq!(validate(&trace), Vec::new());
+}
+
+#[test]
+fn test_validate_batch() {
+
+    // Builders expect sorted, consolidated updates, and do not check them.
+    let mut builder = <IntegerBatch as Batch<u64, u64, usize, i64>>::Builder::new();
+    builder.push((2, 0, 3, 1));
+    builder.push((1, 0, 3, 0));
+    builder.push((1, 0, 0, 1));
+    let batch = builder.done(Antichain::from_elem(1), Antichain::from_elem(4), Antichain::from_elem(2));
+
+    let violations = validate_batch(&batch);
+    assert_eq!(violations, vec![
+        Violation { batch: 0, kind: ViolationKind::KeyOrder { key: 1 } },
+        Violation { batch: 0, kind: ViolationKind::ZeroDiff { key: 1, val: 0, time: 3 } },
+        Violation { batch: 0, kind: ViolationKind::BeforeLower { key: 1, val: 0, time: 0 } },
+        Violation { batch: 0, kind: ViolationKind::BeforeSince { key: 1, val: 0, time: 0 } },
+    ]);
+}
+
+#[test]
+fn test_validate_partial_order_since() {
+
+    use timely::order::Product;
+
+    // Advancing (0, 0) by {(1, 0), (0, 1)} leaves it unchanged, though it is not in advance of either element.
+    let mut since = Antichain::new();
+    since.insert(Product::new(1, 0));
+    since.insert(Product::new(0, 1));
+
+    let mut builder = <OrdValBatch<u64, u64, Product<usize, usize>, i64> as Batch<u64, u64, Product<usize, usize>, i64>>::Builder::new();
+    builder.push((0, 0, Product::new(0, 0), 1));
+    let batch = builder.done(Antichain::from_elem(Product::new(0, 0)), Antichain::from_elem(Product::new(2, 2)), since);
+    assert_eq!(validate_batch(&batch), Vec::new());
+
+    let mut builder = <OrdValBatch<u64, u64, Product<usize, usize>, i64> as Batch<u64, u64, Product<usize, usize>, i64>>::Builder::new();
+    builder.push((0, 0, Product::new(0, 0), 1));
+    let batch = builder.done(Antichain::from_elem(Product::new(0, 0)), Antichain::from_elem(Product::new(2, 2)), Antichain::from_elem(Product::new(1, 1)));
+    assert_eq!(validate_batch(&batch), vec![
+        Violation { batch: 0, kind: ViolationKind::BeforeSince { key: 0, val: 0, time: Product::new(0, 0) } },
+    ]);
+}
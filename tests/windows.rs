@@ -0,0 +1,137 @@
+extern crate timely;
+extern crate differential_dataflow;
+
+use timely::dataflow::operators::{Capture, Probe};
+use timely::dataflow::operators::capture::Extract;
+
+use differential_dataflow::input::InputSession;
+use differential_dataflow::operators::windows::{Window, Windows, Sessions};
+
+#[test]
+fn test_hopping_windows() {
+
+    let captured = timely::execute_directly(move |worker| {
+
+        let mut input = InputSession::<u64, char, isize>::new();
+        let (probe, captured) = worker.dataflow(|scope| {
+            let windows = input.to_collection(scope).hopping(4, 2);
+            (windows.inner.probe(), windows.inner.capture())
+        });
+
+        input.advance_to(3);
+        input.insert('a');
+        input.advance_to(10);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+
+        captured
+    });
+
+    let mut updates = captured.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    updates.sort();
+    assert_eq!(updates, vec![
+        ((Window { start: 0, end: 4 }, 'a'), 3, 1),
+        ((Window { start: 0, end: 4 }, 'a'), 4, -1),
+        ((Window { start: 2, end: 6 }, 'a'), 3, 1),
+        ((Window { start: 2, end: 6 }, 'a'), 6, -1),
+    ]);
+}
+
+#[test]
+fn test_session_windows() {
+
+    let captured = timely::execute_directly(move |worker| {
+
+        let mut input = InputSession::<u64, (char, u32), isize>::new();
+        let (probe, captured) = worker.dataflow(|scope| {
+            let sessions = input.to_collection(scope).sessions(5);
+            (sessions.inner.probe(), sessions.inner.capture())
+        });
+
+        // The first two events of 'a' share a session, which closes at 8 before the third event.
+        input.insert(('a', 0));
+        input.insert(('b', 0));
+        input.advance_to(3);
+        input.insert(('a', 1));
+        input.advance_to(10);
+        input.insert(('a', 2));
+        input.advance_to(20);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+
+        captured
+    });
+
+    let mut updates = captured.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    updates.sort();
+    assert_eq!(updates, vec![
+        ((('a', 0), 0), 0, 1),
+        ((('a', 0), 0), 8, -1),
+        ((('a', 0), 1), 3, 1),
+        ((('a', 0), 1), 8, -1),
+        ((('a', 10), 2), 10, 1),
+        ((('a', 10), 2), 15, -1),
+        ((('b', 0), 0), 0, 1),
+        ((('b', 0), 0), 5, -1),
+    ]);
+}
+
+#[test]
+fn test_tumbling_windows() {
+
+    let captured = timely::execute_directly(move |worker| {
+
+        let mut input = InputSession::<u64, char, isize>::new();
+        let (probe, captured) = worker.dataflow(|scope| {
+            let windows = input.to_collection(scope).tumbling(4);
+            (windows.inner.probe(), windows.inner.capture())
+        });
+
+        input.advance_to(3);
+        input.insert('a');
+        input.advance_to(5);
+        input.insert('b');
+        input.advance_to(10);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+
+        captured
+    });
+
+    let mut updates = captured.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    updates.sort();
+    assert_eq!(updates, vec![
+        ((Window { start: 0, end: 4 }, 'a'), 3, 1),
+        ((Window { start: 0, end: 4 }, 'a'), 4, -1),
+        ((Window { start: 4, end: 8 }, 'b'), 5, 1),
+        ((Window { start: 4, end: 8 }, 'b'), 8, -1),
+    ]);
+}
+
+#[test]
+fn test_windows_near_maximum() {
+
+    let captured = timely::execute_directly(move |worker| {
+
+        let mut input = InputSession::<u8, char, isize>::new();
+        let (probe, captured) = worker.dataflow(|scope| {
+            let windows = input.to_collection(scope).tumbling(100);
+            (windows.inner.probe(), windows.inner.capture())
+        });
+
+        input.advance_to(250);
+        input.insert('a');
+        input.advance_to(255);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+
+        captured
+    });
+
+    // The window starting at 200 would end at 300, and instead ends at the greatest time.
+    let mut updates = captured.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    updates.sort();
+    assert_eq!(updates, vec![
+        ((Window { start: 200, end: 255 }, 'a'), 250, 1),
+    ]);
+}